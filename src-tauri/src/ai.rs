@@ -1,11 +1,15 @@
 use chrono::Datelike;
 use regex::Regex;
 use reqwest::Client;
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use chromiumoxide::{Browser, BrowserConfig};
 use futures::StreamExt;
 use std::sync::Arc;
+use std::collections::HashMap;
 use tokio::sync::Mutex;
 use once_cell::sync::Lazy;
 
@@ -93,56 +97,195 @@ pub async fn test_google_api(api_key: &str, cx: &str) -> Result<String, String>
     }
 }
 
-/// AI 응답에서 JSON만 추출하는 헬퍼 함수
-fn extract_json(text: &str) -> String {
-    let text = text.trim();
+// ===== 구조화 응답 (JSON 스키마) =====
 
-    // ```json ... ``` 블록에서 추출
-    if let Some(start) = text.find("```json") {
-        if let Some(end) = text[start..].find("```\n").or_else(|| text[start..].rfind("```")) {
-            let json_start = start + 7; // "```json" 길이
-            let json_end = start + end;
-            if json_end > json_start {
-                return text[json_start..json_end].trim().to_string();
+/// 스키마 검증 실패 시 재요청 횟수
+const SCHEMA_REPAIR_ROUNDS: usize = 1;
+
+/// Rust 응답 구조체로부터 Gemini responseSchema 생성
+/// schemars의 OpenAPI 3 스키마를 Gemini가 지원하는 부분집합으로 변환
+fn response_schema<T: JsonSchema>() -> serde_json::Value {
+    let settings = SchemaSettings::openapi3().with(|s| {
+        s.inline_subschemas = true;
+        s.meta_schema = None;
+    });
+    let root = settings.into_generator().into_root_schema_for::<T>();
+    to_gemini_schema(serde_json::to_value(root.schema).unwrap_or_default())
+}
+
+/// Gemini Schema 객체로 변환 (지원하지 않는 키 제거, 타입 이름은 대문자)
+fn to_gemini_schema(schema: serde_json::Value) -> serde_json::Value {
+    let mut obj = match schema {
+        serde_json::Value::Object(obj) => obj,
+        _ => return json!({ "type": "STRING" }),
+    };
+
+    // Option<구조체>는 allOf 한 개로 감싸져 나오므로 펼침
+    if let Some(serde_json::Value::Array(mut all_of)) = obj.remove("allOf") {
+        if all_of.len() == 1 {
+            if let serde_json::Value::Object(inner) = all_of.remove(0) {
+                for (k, v) in inner {
+                    obj.entry(k).or_insert(v);
+                }
             }
         }
     }
 
-    // ``` ... ``` 블록에서 추출
-    if let Some(start) = text.find("```") {
-        let after_first = start + 3;
-        if let Some(end) = text[after_first..].find("```") {
-            let content = &text[after_first..after_first + end];
-            // 첫 줄이 언어 지정이면 건너뛰기
-            let json_content = if content.starts_with('\n') {
-                content.trim()
-            } else if let Some(newline) = content.find('\n') {
-                content[newline..].trim()
-            } else {
-                content.trim()
-            };
-            return json_content.to_string();
+    let mut out = serde_json::Map::new();
+    for (key, value) in obj {
+        match key.as_str() {
+            "type" => {
+                // ["string", "null"] 형태는 nullable로 변환
+                let (type_name, nullable) = match &value {
+                    serde_json::Value::Array(types) => (
+                        types.iter().filter_map(|t| t.as_str()).find(|t| *t != "null").unwrap_or("string").to_string(),
+                        types.iter().any(|t| t == "null"),
+                    ),
+                    _ => (value.as_str().unwrap_or("string").to_string(), false),
+                };
+                out.insert("type".to_string(), json!(type_name.to_uppercase()));
+                if nullable {
+                    out.insert("nullable".to_string(), json!(true));
+                }
+            }
+            "properties" => {
+                let props: serde_json::Map<String, serde_json::Value> = value
+                    .as_object()
+                    .map(|m| m.iter().map(|(k, v)| (k.clone(), to_gemini_schema(v.clone()))).collect())
+                    .unwrap_or_default();
+                out.insert(key, serde_json::Value::Object(props));
+            }
+            "items" => {
+                out.insert(key, to_gemini_schema(value));
+            }
+            "anyOf" => {
+                let variants: Vec<serde_json::Value> = value
+                    .as_array()
+                    .map(|a| a.iter().cloned().map(to_gemini_schema).collect())
+                    .unwrap_or_default();
+                out.insert(key, json!(variants));
+            }
+            "required" | "enum" | "nullable" | "description" | "minItems" | "maxItems" => {
+                out.insert(key, value);
+            }
+            _ => {}
         }
     }
+    serde_json::Value::Object(out)
+}
 
-    // { ... } 또는 [ ... ] JSON 블록 추출
-    if let Some(start) = text.find('{') {
-        if let Some(end) = text.rfind('}') {
-            if end > start {
-                return text[start..=end].to_string();
-            }
-        }
+/// 응답 텍스트를 구조체로 파싱 (스키마 검증)
+fn parse_structured<T: DeserializeOwned>(text: &str) -> Result<T, String> {
+    serde_json::from_str(text.trim()).map_err(|e| e.to_string())
+}
+
+/// Gemini generateContent 호출 (대화 내용 그대로 전달)
+/// 반환: (응답 텍스트, 입력 토큰, 출력 토큰, 비용)
+async fn generate_content(
+    client: &Client,
+    api_key: &str,
+    model: &str,
+    contents: serde_json::Value,
+    generation_config: &serde_json::Value,
+) -> Result<(String, i64, i64, f64), String> {
+    let response = client
+        .post(format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+            model, api_key
+        ))
+        .json(&json!({
+            "contents": contents,
+            "generationConfig": generation_config
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("API 요청 실패: {}", e))?;
+
+    let gemini_resp: GeminiResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
+
+    let text = gemini_resp
+        .candidates
+        .first()
+        .and_then(|c| c.content.parts.first())
+        .map(|p| p.text.clone())
+        .ok_or("응답 없음")?;
+
+    let (input_tokens, output_tokens, cost) = usage_cost(PROVIDER, model, gemini_resp.usage_metadata.as_ref());
+    Ok((text, input_tokens, output_tokens, cost))
+}
+
+/// 구조화 응답 검증/재요청 실패 (실패해도 사용량은 기록해야 하므로 재요청 포함 토큰과 비용을 함께 전달)
+struct RepairError {
+    message: String,
+    input_tokens: i64,
+    output_tokens: i64,
+    cost: f64,
+}
+
+impl From<RepairError> for String {
+    fn from(error: RepairError) -> String {
+        error.message
     }
+}
 
-    if let Some(start) = text.find('[') {
-        if let Some(end) = text.rfind(']') {
-            if end > start {
-                return text[start..=end].to_string();
-            }
+/// 구조화 응답 파싱, 검증 실패 시 오류 내용을 알려주고 재요청
+/// response: 최초 호출 결과 (응답 텍스트, 입력 토큰, 출력 토큰, 비용)
+/// 실패한 원본 응답은 ai_output_failures 테이블에 기록
+/// 반환: (파싱 결과, 최종 응답 텍스트, 재요청 포함 입력 토큰, 출력 토큰, 비용)
+async fn parse_or_repair<T: DeserializeOwned>(
+    client: &Client,
+    api_key: &str,
+    model: &str,
+    operation: &str,
+    prompt: &str,
+    generation_config: &serde_json::Value,
+    response: (String, i64, i64, f64),
+) -> Result<(T, String, i64, i64, f64), RepairError> {
+    let (mut text, mut input_tokens, mut output_tokens, mut cost) = response;
+    let mut round = 0;
+    let fail = |message: String, input_tokens: i64, output_tokens: i64, cost: f64| RepairError {
+        message,
+        input_tokens,
+        output_tokens,
+        cost,
+    };
+
+    loop {
+        let error = match parse_structured::<T>(&text) {
+            Ok(value) => return Ok((value, text, input_tokens, output_tokens, cost)),
+            Err(e) => e,
+        };
+
+        let retry = round < SCHEMA_REPAIR_ROUNDS;
+        crate::db::log_ai_output_failure(operation, model, &error, &text, round, retry).ok();
+        if !retry {
+            return Err(fail(format!("응답 스키마 검증 실패: {} - 원본: {}", error, text), input_tokens, output_tokens, cost));
         }
+        round += 1;
+
+        let repair_request = format!(
+            "이전 응답이 JSON 스키마 검증에 실패했습니다.\n오류: {}\n\n스키마에 맞는 올바른 JSON만 다시 응답하세요. 설명이나 코드 블록 없이 JSON만 출력하세요.",
+            error
+        );
+        check_budget(PROVIDER, operation, model, &format!("{}{}{}", prompt, text, repair_request), cost)
+            .map_err(|e| fail(e, input_tokens, output_tokens, cost))?;
+
+        let contents = json!([
+            {"role": "user", "parts": [{"text": prompt}]},
+            {"role": "model", "parts": [{"text": text}]},
+            {"role": "user", "parts": [{"text": repair_request}]}
+        ]);
+        let (repaired, input, output, repair_cost) = generate_content(client, api_key, model, contents, generation_config)
+            .await
+            .map_err(|e| fail(e, input_tokens, output_tokens, cost))?;
+        input_tokens += input;
+        output_tokens += output;
+        cost += repair_cost;
+        text = repaired;
     }
-
-    text.to_string()
 }
 
 // 전역 브라우저 인스턴스 (재사용)
@@ -263,53 +406,102 @@ async fn get_browser() -> Result<Arc<Browser>, String> {
 
 const DEFAULT_MODEL: &str = "gemini-3-flash-preview";
 
+/// AI 호출 제공자 (모델 가격과 캐시 키를 제공자별로 구분)
+pub const PROVIDER: &str = "gemini";
+
 // Gemini API 기본 가격 (USD per 1M tokens) - 2.0 Flash 기준
+// model_prices 테이블을 읽을 수 없을 때만 사용
 const INPUT_PRICE_PER_M: f64 = 0.10;
 const OUTPUT_PRICE_PER_M: f64 = 0.40;
 
-// 모델별 가격 (input, output per 1M tokens)
-pub fn get_model_price(model: &str) -> (f64, f64) {
-    match model {
-        // Gemini 2.0 (저렴)
-        "gemini-2.0-flash-lite" => (0.075, 0.30),
-        "gemini-2.0-flash" => (0.10, 0.40),
-        // Gemini 2.5
-        "gemini-2.5-flash-lite" => (0.10, 0.40),
-        "gemini-2.5-flash" => (0.30, 2.50),
-        "gemini-2.5-pro" => (1.25, 10.00),
-        // Gemini 3
-        "gemini-3-flash-preview" => (0.50, 3.00),
-        "gemini-3-pro-preview" => (2.00, 12.00),
-        _ => (INPUT_PRICE_PER_M, OUTPUT_PRICE_PER_M),
+/// 토큰 종류별 단가 (USD per 1M tokens)
+struct ModelPricing {
+    input: f64,
+    output: f64,
+    cached_input: f64,
+    reasoning: f64,
+}
+
+/// 현재 적용되는 모델 가격 (model_prices 테이블, 없으면 기본 가격)
+/// 캐시 입력 가격이 없으면 입력 가격, 추론 가격이 없으면 출력 가격을 적용
+fn get_model_pricing(provider: &str, model: &str) -> ModelPricing {
+    match crate::db::get_model_price(provider, model) {
+        Ok(Some(price)) => ModelPricing {
+            input: price.input_per_m,
+            output: price.output_per_m,
+            cached_input: price.cached_input_per_m.unwrap_or(price.input_per_m),
+            reasoning: price.reasoning_per_m.unwrap_or(price.output_per_m),
+        },
+        _ => ModelPricing {
+            input: INPUT_PRICE_PER_M,
+            output: OUTPUT_PRICE_PER_M,
+            cached_input: INPUT_PRICE_PER_M,
+            reasoning: OUTPUT_PRICE_PER_M,
+        },
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ScheduleInfo {
     pub title: String,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
     pub location: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,  // RFC 5545 RRULE (예: FREQ=WEEKLY;BYDAY=TU), 반복 아니면 null
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct TodoInfo {
     pub title: String,
     pub priority: Option<String>,  // high, medium, low
     pub due_date: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,  // 반복 할일 RRULE (예: "3일마다" → FREQ=DAILY;INTERVAL=3)
+    #[serde(default)]
+    pub subtasks: Vec<SubtaskInfo>,  // 체크리스트 항목 (하위 할일)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct SubtaskInfo {
+    pub title: String,
+    pub priority: Option<String>,
+    pub due_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct TransactionInfo {
     pub tx_type: String,  // "income" or "expense"
-    pub amount: i64,
+    pub amount: f64,      // 해당 통화 기준 금액 (USD 12.5 = 12.5)
+    #[serde(default)]
+    pub currency: Option<String>,  // ISO 4217 코드 (KRW, USD, EUR, JPY ...), 없으면 KRW
     pub description: String,
     pub category: Option<String>,
     pub tx_date: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 영수증 인식 결과 (저장 전 사용자 검토용, 금액은 주 단위)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub struct ReceiptInfo {
+    pub merchant: Option<String>,   // 상호명
+    pub date: Option<String>,       // 거래일 (YYYY-MM-DD 또는 YYYY-MM-DD HH:MM)
+    pub total: f64,                 // 결제 총액 (USD 12.5 = 12.5)
+    #[serde(default)]
+    pub currency: Option<String>,   // ISO 4217 코드, 없으면 기본 통화
+    pub category: Option<String>,
+    #[serde(default)]
+    pub items: Vec<ReceiptLineInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ReceiptLineInfo {
+    pub name: String,
+    pub quantity: Option<f64>,
+    pub amount: f64,  // 품목 합계 금액 (단가 × 수량)
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AnalysisResult {
     pub title: String,
     pub formatted_content: String,
@@ -325,7 +517,7 @@ pub struct AnalysisResult {
     pub transactions: Vec<TransactionInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MultiAnalysisResult {
     pub items: Vec<AnalysisResult>,
 }
@@ -363,8 +555,14 @@ struct Part {
 struct UsageMetadata {
     #[serde(rename = "promptTokenCount")]
     prompt_token_count: i64,
-    #[serde(rename = "candidatesTokenCount")]
+    #[serde(rename = "candidatesTokenCount", default)]
     candidates_token_count: i64,
+    // 컨텍스트 캐시에서 읽은 입력 토큰 (promptTokenCount에 포함됨)
+    #[serde(rename = "cachedContentTokenCount", default)]
+    cached_content_token_count: i64,
+    // 추론(thinking) 토큰 (candidatesTokenCount에 포함되지 않음)
+    #[serde(rename = "thoughtsTokenCount", default)]
+    thoughts_token_count: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -377,10 +575,189 @@ struct EmbeddingData {
     values: Vec<f32>,
 }
 
-pub fn calculate_cost(model: &str, input_tokens: i64, output_tokens: i64) -> f64 {
-    let (input_price, output_price) = get_model_price(model);
-    (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0)
+pub fn calculate_cost(provider: &str, model: &str, input_tokens: i64, output_tokens: i64) -> f64 {
+    let pricing = get_model_pricing(provider, model);
+    (input_tokens as f64 * pricing.input / 1_000_000.0)
+        + (output_tokens as f64 * pricing.output / 1_000_000.0)
+}
+
+/// 응답 사용량으로 (입력 토큰, 출력 토큰, 비용) 계산
+/// 캐시 입력 토큰과 추론 토큰은 각각의 단가로 계산하고, 출력 토큰 수에는 추론 토큰을 포함
+fn usage_cost(provider: &str, model: &str, usage: Option<&UsageMetadata>) -> (i64, i64, f64) {
+    let usage = match usage {
+        Some(u) => u,
+        None => return (0, 0, 0.0),
+    };
+    let pricing = get_model_pricing(provider, model);
+    let cached = usage.cached_content_token_count.clamp(0, usage.prompt_token_count);
+    let cost = ((usage.prompt_token_count - cached) as f64 * pricing.input
+        + cached as f64 * pricing.cached_input
+        + usage.candidates_token_count as f64 * pricing.output
+        + usage.thoughts_token_count as f64 * pricing.reasoning)
+        / 1_000_000.0;
+    (
+        usage.prompt_token_count,
+        usage.candidates_token_count + usage.thoughts_token_count,
+        cost,
+    )
+}
+
+// ===== AI 응답 캐시 =====
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 작업별 캐시 TTL (초)
+/// 설정 `ai_cache_ttl_{operation}` (시간 단위)로 변경 가능, 0이면 캐시 사용 안 함
+fn cache_ttl_secs(operation: &str) -> i64 {
+    let default_hours = match operation {
+        "dataset_analyze" => 24 * 7,
+        "extract" => 24,
+        _ => 0,
+    };
+    let hours = crate::db::get_setting(&format!("ai_cache_ttl_{}", operation))
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(default_hours);
+    hours.max(0) * 3600
+}
+
+/// 캐시 키 생성: provider + model + 프롬프트 해시 + 생성 설정 해시
+/// 반환: (cache_key, prompt_hash, config_hash)
+fn make_cache_key(model: &str, prompt: &str, generation_config: &serde_json::Value) -> (String, String, String) {
+    let prompt_hash = sha256_hex(prompt.as_bytes());
+    let config_hash = sha256_hex(generation_config.to_string().as_bytes());
+    let cache_key = sha256_hex(format!("{}|{}|{}|{}", PROVIDER, model, prompt_hash, config_hash).as_bytes());
+    (cache_key, prompt_hash, config_hash)
+}
+
+/// 캐시 조회 (캐시 비활성화 또는 TTL 0이면 None)
+fn cache_lookup(operation: &str, cache_key: &str) -> Option<crate::db::AiCacheEntry> {
+    if crate::db::get_setting("ai_cache_enabled").unwrap_or_default() == "false" || cache_ttl_secs(operation) == 0 {
+        return None;
+    }
+    crate::db::get_ai_cache(cache_key).ok().flatten()
+}
+
+/// 캐시 적중으로 절감한 비용 (저장할 때 계산한 실제 비용, 이전 항목은 토큰으로 추정)
+fn cache_saved_usd(provider: &str, model: &str, entry: &crate::db::AiCacheEntry) -> f64 {
+    entry
+        .cost_usd
+        .unwrap_or_else(|| calculate_cost(provider, model, entry.input_tokens, entry.output_tokens))
+}
+
+/// 응답을 캐시에 저장 (파싱에 성공한 응답만 저장해야 함)
+fn cache_store(
+    operation: &str,
+    model: &str,
+    keys: (String, String, String),
+    response_text: &str,
+    input_tokens: i64,
+    output_tokens: i64,
+    cost_usd: f64,
+) {
+    let ttl = cache_ttl_secs(operation);
+    if crate::db::get_setting("ai_cache_enabled").unwrap_or_default() == "false" || ttl == 0 {
+        return;
+    }
+    let (cache_key, prompt_hash, config_hash) = keys;
+    let entry = crate::db::AiCacheEntry {
+        cache_key,
+        provider: PROVIDER.to_string(),
+        model: model.to_string(),
+        operation: operation.to_string(),
+        prompt_hash,
+        config_hash,
+        response_text: response_text.to_string(),
+        input_tokens,
+        output_tokens,
+        created_at: String::new(),
+        expires_at: String::new(),
+        cost_usd: Some(cost_usd),
+    };
+    crate::db::save_ai_cache(&entry, ttl).ok(); // 캐시 저장 실패는 무시
+}
+
+// ===== API 사용 예산 =====
+
+/// 예산 초과 에러 접두사 (프론트엔드에서 구분용)
+pub const BUDGET_EXCEEDED_PREFIX: &str = "[BUDGET_EXCEEDED]";
+pub const BUDGET_CONFIRM_PREFIX: &str = "[BUDGET_CONFIRM]";
+
+/// 응답 토큰 예상치 (출력 길이는 미리 알 수 없으므로 고정값 사용)
+const ESTIMATED_OUTPUT_TOKENS: i64 = 1_000;
+
+/// 예산 초과 승인 후 확인 없이 진행되는 시간 (초)
+const BUDGET_OVERRIDE_SECS: i64 = 600;
+
+/// 사용자가 초과를 승인한 예산별 만료 시각 (예산 id → unix timestamp)
+static BUDGET_OVERRIDES: Lazy<parking_lot::Mutex<HashMap<i64, i64>>> =
+    Lazy::new(|| parking_lot::Mutex::new(HashMap::new()));
+
+/// 프롬프트 토큰 수 추정 (영문 약 4자 = 1토큰, 한글 등 비ASCII 문자는 1자 = 1토큰으로 보수적으로 계산)
+pub fn estimate_tokens(text: &str) -> i64 {
+    let (ascii, other) = text.chars().fold((0i64, 0i64), |(a, o), c| {
+        if c.is_ascii() { (a + 1, o) } else { (a, o + 1) }
+    });
+    ascii / 4 + other
+}
+
+/// 호출 예상 비용 (프롬프트 크기 × 모델 가격)
+pub fn estimate_cost(provider: &str, model: &str, prompt: &str) -> f64 {
+    calculate_cost(provider, model, estimate_tokens(prompt), ESTIMATED_OUTPUT_TOKENS)
+}
+
+/// 예산 초과 승인 (해당 확인 모드 예산만 일정 시간 동안 통과시킴)
+pub fn approve_budget_overrun(budget_id: i64) {
+    let now = chrono::Utc::now().timestamp();
+    let mut overrides = BUDGET_OVERRIDES.lock();
+    overrides.retain(|_, until| *until > now);
+    overrides.insert(budget_id, now + BUDGET_OVERRIDE_SECS);
+}
+
+fn budget_override_active(budget_id: i64) -> bool {
+    BUDGET_OVERRIDES
+        .lock()
+        .get(&budget_id)
+        .is_some_and(|until| *until > chrono::Utc::now().timestamp())
+}
+
+/// AI 호출 전 예산 확인
+/// pending_usd: 아직 api_usage에 기록되지 않은 현재 실행분 비용 (리서치/에이전트 루프용)
+pub fn check_budget(provider: &str, operation: &str, model: &str, prompt: &str, pending_usd: f64) -> Result<(), String> {
+    let budgets = crate::db::get_all_api_budgets().map_err(|e| e.to_string())?;
+    if budgets.is_empty() {
+        return Ok(());
+    }
+
+    let estimate = estimate_cost(provider, model, prompt);
+
+    for budget in budgets.iter().filter(|b| b.operation == "*" || b.operation == operation) {
+        let spent = crate::db::get_spent_usd(&budget.operation, &budget.period).map_err(|e| e.to_string())? + pending_usd;
+        if spent + estimate <= budget.limit_usd {
+            continue;
+        }
+
+        let scope = if budget.operation == "*" { "전체" } else { budget.operation.as_str() };
+        let period = if budget.period == "monthly" { "월" } else { "일" };
+        let detail = format!(
+            "{} {} 예산 ${:.2} 초과 예상 (사용 ${:.4} + 예상 ${:.4})",
+            scope, period, budget.limit_usd, spent, estimate
+        );
+
+        if budget.action == "confirm" {
+            if budget_override_active(budget.id) {
+                continue;
+            }
+            // 승인할 예산 id를 함께 보냄 (approve_budget_overrun에 전달)
+            return Err(format!("{} budget_id={} {}", BUDGET_CONFIRM_PREFIX, budget.id, detail));
+        }
+        return Err(format!("{} {}", BUDGET_EXCEEDED_PREFIX, detail));
+    }
+
+    Ok(())
 }
 
 /// 민감 정보 마스킹 결과 (마스킹된 텍스트 + 복원용 매핑)
@@ -530,6 +907,8 @@ pub async fn ask_question(
         context, question
     );
 
+    check_budget(PROVIDER, "search", model, &prompt, 0.0)?;
+
     let response = client
         .post(format!(
             "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
@@ -557,15 +936,11 @@ pub async fn ask_question(
         .map(|p| p.text.clone())
         .ok_or("응답 없음")?;
 
-    let usage = gemini_resp.usage_metadata.unwrap_or(UsageMetadata {
-        prompt_token_count: 0,
-        candidates_token_count: 0,
-    });
-
+    let (input_tokens, output_tokens, cost_usd) = usage_cost(PROVIDER, model, gemini_resp.usage_metadata.as_ref());
     let token_usage = TokenUsage {
-        input_tokens: usage.prompt_token_count,
-        output_tokens: usage.candidates_token_count,
-        cost_usd: calculate_cost(model, usage.prompt_token_count, usage.candidates_token_count),
+        input_tokens,
+        output_tokens,
+        cost_usd,
     };
 
     Ok((text, token_usage))
@@ -575,6 +950,7 @@ pub async fn ask_question(
 pub async fn analyze_multi_memo(
     api_key: &str,
     model: &str,
+    operation: &str,  // 예산 확인용 작업 이름 (analyze / reanalyze)
     content: &str,
     existing_memos: &[(i64, String, String)],
     existing_categories: &[String],
@@ -742,6 +1118,19 @@ pub async fn analyze_multi_memo(
 - "오늘 저녁 8시 강의" → start_time: "{}T20:00" ✓
 - "오늘 3시 회의" → start_time: "{}T15:00" ✓
 
+**반복 일정 (recurrence, RFC 5545 RRULE 형식):**
+- 반복 표현이 있으면 recurrence에 RRULE을, start_time에는 오늘 이후 첫 발생 일시를 넣으세요
+- "매일 아침 7시" → "FREQ=DAILY"
+- "매주 화요일 10시 스탠드업" → "FREQ=WEEKLY;BYDAY=TU"
+- "평일마다", "주중 매일" → "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"
+- "격주 금요일", "every other Friday" → "FREQ=WEEKLY;INTERVAL=2;BYDAY=FR"
+- "매주 월, 수요일" → "FREQ=WEEKLY;BYDAY=MO,WE"
+- "매달 15일", "매월 15일" → "FREQ=MONTHLY;BYMONTHDAY=15"
+- "매달 마지막 금요일" → "FREQ=MONTHLY;BYDAY=-1FR", "매달 둘째 주 화요일" → "FREQ=MONTHLY;BYDAY=2TU"
+- "매년 3월 1일" → "FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=1"
+- 횟수/기한이 있으면 COUNT=횟수 또는 UNTIL=YYYYMMDD 추가 (예: "10주 동안" → ";COUNT=10")
+- 반복이 아니면 recurrence: null
+
 ### 5. 할일 추출 (적극적으로!)
 - 다음 패턴 모두 할일로 추출:
   - "~해야 함/한다/해", "~할 것", "~하기", "~까지"
@@ -753,6 +1142,10 @@ pub async fn analyze_multi_memo(
 - 기한 없으면 → due_date: null
 - **마찬가지로 "내일까지" → 실제 날짜 "2026-01-15"로 변환!**
 - **하나의 입력에서 여러 개의 할일을 적극적으로 추출!**
+- 체크리스트/목록("- [ ] 항목", "1. 항목", "준비물: A, B, C")이 하나의 목표에 속하면
+  → 목표를 할일로, 각 항목을 그 할일의 subtasks로 추출 (하위 항목이 없으면 subtasks: [])
+- 반복되는 할일("3일마다 화분 물주기", "매주 월요일 분리수거")은 일정과 같은 RRULE 형식으로 recurrence에,
+  due_date에는 첫 마감일을 넣으세요 (예: "3일마다" → "FREQ=DAILY;INTERVAL=3", 반복 아니면 null)
 
 ### 6. 가계부(거래) 추출 - 핵심!!! 금액이 있으면 반드시 거래로 추출!!!
 **중요: 숫자+원 패턴이 있으면 무조건 transactions 배열에 추가해야 함!!!**
@@ -765,6 +1158,10 @@ pub async fn analyze_multi_memo(
 - amount 변환 (반드시 숫자로!):
   - "5천원" → 5000, "3만원" → 30000, "3만5천원" → 35000
   - "100만원" → 1000000, "1억" → 100000000
+- currency (ISO 4217 코드, 통화 기호/이름을 보고 판단):
+  - "원", "₩", 통화 표시 없음 → "KRW"
+  - "$", "달러", "USD" → "USD" / "€", "유로" → "EUR" / "¥", "엔" → "JPY" / "위안" → "CNY" / "£", "파운드" → "GBP"
+  - 외화는 amount를 그 통화 금액 그대로 (예: "$12.50" → amount: 12.5, currency: "USD") - 원화로 환산하지 마세요!
 - description: 무엇에 대한 거래인지 (예: "커피", "점심", "월급")
 - category: "식비", "교통비", "월급", "쇼핑", "생활비", "카페", "문화" 등
 - tx_date:
@@ -776,6 +1173,7 @@ pub async fn analyze_multi_memo(
 - "커피 5000원" → {{tx_type: "expense", amount: 5000, description: "커피"}}
 - "점심 1만원" → {{tx_type: "expense", amount: 10000, description: "점심"}}
 - "월급 300만원 입금" → {{tx_type: "income", amount: 3000000, description: "월급"}}
+- "출장 택시 $23.40" → {{tx_type: "expense", amount: 23.4, currency: "USD", description: "택시"}}
 
 ## 응답 형식 (JSON 배열):
 {{
@@ -793,20 +1191,26 @@ pub async fn analyze_multi_memo(
           "start_time": "2026-01-15T15:00",
           "end_time": "2026-01-15T16:00",
           "location": "장소",
-          "description": "설명"
+          "description": "설명",
+          "recurrence": null
         }}
       ],
       "todos": [
         {{
           "title": "할일 내용",
           "priority": "high",
-          "due_date": "2026-01-15"
+          "due_date": "2026-01-15",
+          "recurrence": null,
+          "subtasks": [
+            {{ "title": "하위 할일", "priority": null, "due_date": null }}
+          ]
         }}
       ],
       "transactions": [
         {{
           "tx_type": "expense",
           "amount": 5000,
+          "currency": "KRW",
           "description": "커피",
           "category": "식비",
           "tx_date": "2026-01-14"
@@ -825,42 +1229,18 @@ pub async fn analyze_multi_memo(
         current_datetime
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
+    check_budget(PROVIDER, operation, model, &prompt, 0.0)?;
 
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let usage = gemini_resp.usage_metadata.unwrap_or(UsageMetadata {
-        prompt_token_count: 0,
-        candidates_token_count: 0,
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<MultiAnalysisResult>()
     });
 
-    let json_text = extract_json(&text);
-    let multi_result: MultiAnalysisResult = serde_json::from_str(&json_text)
-        .map_err(|e| format!("JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
+
+    let (multi_result, _, input_tokens, output_tokens, cost_usd) =
+        parse_or_repair::<MultiAnalysisResult>(&client, api_key, model, operation, &prompt, &generation_config, response).await?;
 
     // 마스킹된 민감 정보 복원
     let restored_items: Vec<AnalysisResult> = multi_result.items.into_iter().map(|mut item| {
@@ -877,6 +1257,9 @@ pub async fn analyze_multi_memo(
         }).collect();
         item.todos = item.todos.into_iter().map(|mut t| {
             t.title = unmask_text(&t.title, &mask_result.mappings);
+            for sub in &mut t.subtasks {
+                sub.title = unmask_text(&sub.title, &mask_result.mappings);
+            }
             t
         }).collect();
         // 거래도 복원
@@ -889,22 +1272,106 @@ pub async fn analyze_multi_memo(
     }).collect();
 
     let token_usage = TokenUsage {
-        input_tokens: usage.prompt_token_count,
-        output_tokens: usage.candidates_token_count,
-        cost_usd: calculate_cost(model, usage.prompt_token_count, usage.candidates_token_count),
+        input_tokens,
+        output_tokens,
+        cost_usd,
     };
 
     Ok((restored_items, token_usage))
 }
 
+// ===== 영수증 인식 =====
+
+/// 영수증 입력 (이미지는 비전 모델로, 로컬 OCR 텍스트는 텍스트 모델로 구조화)
+pub enum ReceiptSource<'a> {
+    Image { data: &'a [u8], mime_type: &'a str },
+    Text(&'a str),
+}
+
+/// 영수증에서 상호, 날짜, 총액, 품목 추출
+pub async fn extract_receipt(
+    api_key: &str,
+    model: &str,
+    source: ReceiptSource<'_>,
+    existing_categories: &[String],
+) -> Result<(ReceiptInfo, TokenUsage), String> {
+    let model = if model.is_empty() { DEFAULT_MODEL } else { model };
+    let client = Client::new();
+
+    let categories_info = if existing_categories.is_empty() {
+        "없음 (적절한 이름으로 새로 지정)".to_string()
+    } else {
+        existing_categories.join(", ")
+    };
+
+    // OCR 텍스트는 민감 정보를 가린 뒤 전송 (이미지는 원본 그대로 전송됨)
+    let mask_result = match &source {
+        ReceiptSource::Text(text) => Some(mask_sensitive_info(text)),
+        ReceiptSource::Image { .. } => None,
+    };
+    let input_desc = match &mask_result {
+        Some(mask) => format!("## OCR로 읽은 영수증 텍스트:\n{}", mask.masked),
+        None => "## 첨부된 영수증 이미지를 읽으세요.".to_string(),
+    };
+
+    let prompt = format!(
+        r#"당신은 영수증 인식 AI입니다. 영수증에서 결제 정보를 추출하세요.
+
+{}
+
+## 기존 가계부 카테고리:
+{}
+
+## 추출 규칙:
+- merchant: 상호명 (가맹점명), 알 수 없으면 null
+- date: 거래 일시 "YYYY-MM-DD" 또는 "YYYY-MM-DD HH:MM", 알 수 없으면 null
+- total: 최종 결제 금액 (할인 적용 후 합계, 부가세 포함), 숫자만
+- currency: ISO 4217 코드 (₩/원 → KRW, $ → USD, ¥/円 → JPY, € → EUR)
+- category: 기존 카테고리 중 가장 알맞은 것, 없으면 새 이름 (식비, 교통비, 쇼핑 등)
+- items: 구매 품목 목록 (name, quantity, amount = 품목 합계 금액)
+- 소계, 부가세, 할인, 결제수단, 거스름돈 줄은 items에 넣지 마세요
+- 카드번호, 승인번호 같은 정보는 추출하지 마세요
+- 금액에서 쉼표와 통화 기호는 빼고 숫자만 넣으세요"#,
+        input_desc, categories_info
+    );
+
+    check_budget(PROVIDER, "receipt", model, &prompt, 0.0)?;
+
+    let mut parts = Vec::new();
+    if let ReceiptSource::Image { data, mime_type } = &source {
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+        parts.push(json!({"inline_data": {"mime_type": mime_type, "data": encoded}}));
+    }
+    parts.push(json!({"text": prompt}));
+
+    let generation_config = json!({
+        "temperature": 0.1,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<ReceiptInfo>()
+    });
+
+    let response = generate_content(&client, api_key, model, json!([{"parts": parts}]), &generation_config).await?;
+    let (mut receipt, _, input_tokens, output_tokens, cost_usd) =
+        parse_or_repair::<ReceiptInfo>(&client, api_key, model, "receipt", &prompt, &generation_config, response).await?;
+
+    if let Some(mask) = &mask_result {
+        receipt.merchant = receipt.merchant.map(|m| unmask_text(&m, &mask.mappings));
+        for item in &mut receipt.items {
+            item.name = unmask_text(&item.name, &mask.mappings);
+        }
+    }
+
+    Ok((receipt, TokenUsage { input_tokens, output_tokens, cost_usd }))
+}
+
 // ===== 폴더 정리 AI 기능 =====
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct OrganizeResponse {
     files: Vec<OrganizePlanResponse>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct OrganizePlanResponse {
     file_name: String,
     suggested_folder: String,
@@ -1002,37 +1469,17 @@ async fn analyze_files_batch(
         file_names
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
+    check_budget(PROVIDER, "organize", model, &prompt, 0.0)?;
 
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<OrganizeResponse>()
+    });
 
-    let json_text = extract_json(&text);
-    let organize_resp: OrganizeResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let response = generate_content(client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
+    let (organize_resp, _, _, _, _) =
+        parse_or_repair::<OrganizeResponse>(client, api_key, model, "organize", &prompt, &generation_config, response).await?;
 
     // 파일 경로와 매핑하여 결과 반환
     let mut results: Vec<(String, String, String, String)> = Vec::new();
@@ -1110,51 +1557,21 @@ pub async fn plan_research(
         query
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.7,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.7,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<PlanResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct PlanResponse {
         queries: Vec<String>,
     }
 
-    let json_text = extract_json(&text);
-    let plan: PlanResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let (plan, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<PlanResponse>(&client, api_key, model, "research", &prompt, &generation_config, response).await?;
 
     Ok((plan.queries, input_tokens, output_tokens, cost))
 }
@@ -1200,51 +1617,21 @@ pub async fn select_pages_to_crawl(
         query, results_text
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<SelectResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct SelectResponse {
         selected_urls: Vec<String>,
     }
 
-    let json_text = extract_json(&text);
-    let selection: SelectResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let (selection, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<SelectResponse>(&client, api_key, model, "research", &prompt, &generation_config, response).await?;
 
     Ok((selection.selected_urls, input_tokens, output_tokens, cost))
 }
@@ -1292,53 +1679,27 @@ pub async fn extract_insights(
         query, url, truncated_content
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<InsightResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct InsightResponse {
         insights: Vec<String>,
     }
 
-    let json_text = extract_json(&text);
-    let result: InsightResponse = serde_json::from_str(&json_text)
-        .unwrap_or(InsightResponse { insights: Vec::new() });
+    // 재요청 후에도 실패하면 빈 결과로 진행 (재요청 사용량까지 반영)
+    let (insights, input_tokens, output_tokens, cost) =
+        match parse_or_repair::<InsightResponse>(&client, api_key, model, "research", &prompt, &generation_config, response).await {
+            Ok((result, _, input, output, cost)) => (result.insights, input, output, cost),
+            Err(e) => (Vec::new(), e.input_tokens, e.output_tokens, e.cost),
+        };
 
-    Ok((result.insights, input_tokens, output_tokens, cost))
+    Ok((insights, input_tokens, output_tokens, cost))
 }
 
 /// 3-2단계: 각 출처별 개별 요약 생성 (별첨용)
@@ -1371,62 +1732,36 @@ URL: {}
 2. 주제와 관련된 구체적인 정보, 수치, 통계가 있다면 반드시 포함
 3. 이 출처가 다른 출처와 다른 독특한 관점이나 주장이 있다면 언급
 4. 객관적이고 중립적인 톤으로 작성
-5. 마크다운 기호(#, **, *, -)를 절대 사용하지 마세요. 일반 텍스트로만 작성
-
-응답 형식 (JSON):
-{{
-    "summary": "이 출처의 상세 요약 (5-8문장). 마크다운 기호 없이 일반 텍스트로만 작성."
-}}"#,
-        query, title, url, truncated_content
-    );
-
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
+5. 마크다운 기호(#, **, *, -)를 절대 사용하지 마세요. 일반 텍스트로만 작성
 
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
+응답 형식 (JSON):
+{{
+    "summary": "이 출처의 상세 요약 (5-8문장). 마크다운 기호 없이 일반 텍스트로만 작성."
+}}"#,
+        query, title, url, truncated_content
+    );
 
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<SummaryResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct SummaryResponse {
         summary: String,
     }
 
-    let json_text = extract_json(&text);
-    let result: SummaryResponse = serde_json::from_str(&json_text)
-        .unwrap_or(SummaryResponse { summary: "요약 생성 실패".to_string() });
+    // 재요청 후에도 실패하면 기본 문구로 진행 (재요청 사용량까지 반영)
+    let (summary, input_tokens, output_tokens, cost) =
+        match parse_or_repair::<SummaryResponse>(&client, api_key, model, "research", &prompt, &generation_config, response).await {
+            Ok((result, _, input, output, cost)) => (result.summary, input, output, cost),
+            Err(e) => ("요약 생성 실패".to_string(), e.input_tokens, e.output_tokens, e.cost),
+        };
 
-    Ok((result.summary, input_tokens, output_tokens, cost))
+    Ok((summary, input_tokens, output_tokens, cost))
 }
 
 /// 4단계: 최종 리포트 작성
@@ -1496,53 +1831,23 @@ pub async fn compile_final_report(
         query, insights_text, sources_text
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.4,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.4,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<ReportResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct ReportResponse {
         summary: String,
         key_points: Vec<String>,
         full_report: String,
     }
 
-    let json_text = extract_json(&text);
-    let report: ReportResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let (report, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<ReportResponse>(&client, api_key, model, "research", &prompt, &generation_config, response).await?;
 
     Ok((report.summary, report.full_report, report.key_points, input_tokens, output_tokens, cost))
 }
@@ -1808,7 +2113,7 @@ pub struct DatasetAnalysisResult {
     pub chart_data: Option<ChartDataResult>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ChartDataResult {
     pub chart_type: String,
     pub title: String,
@@ -1817,13 +2122,16 @@ pub struct ChartDataResult {
 }
 
 /// 데이터셋 분석
+/// 반환: (분석 결과, 입력 토큰, 출력 토큰, 비용, 캐시 적중 시 절감액)
 pub async fn analyze_dataset_data(
     gemini_api_key: &str,
     model: &str,
     dataset_name: &str,
     columns: &[String],
     rows: &[Vec<String>],
-) -> Result<(DatasetAnalysisResult, i64, i64, f64), String> {
+    row_count: i64,
+    column_profile: &str,
+) -> Result<(DatasetAnalysisResult, i64, i64, f64, Option<f64>), String> {
     let client = Client::new();
     let model = if model.is_empty() { "gemini-2.0-flash" } else { model };
 
@@ -1834,10 +2142,13 @@ pub async fn analyze_dataset_data(
         .map(|r| r.join(" | "))
         .collect();
 
+    // 통계는 샘플이 아니라 전체 행 기준
     let data_preview = format!(
-        "컬럼: {}\n\n샘플 데이터 ({}행 중 {}행):\n{}",
+        "컬럼: {}\n\n컬럼별 통계 (전체 {}행 기준):\n{}\n\n샘플 데이터 ({}행 중 {}행):\n{}",
         columns.join(" | "),
-        rows.len(),
+        row_count,
+        column_profile,
+        row_count,
         sample_size,
         sample_rows.join("\n")
     );
@@ -1876,7 +2187,7 @@ pub async fn analyze_dataset_data(
 }}
 
 요구사항:
-1. 데이터의 특성을 정확히 파악하여 분석
+1. 데이터의 특성을 정확히 파악하여 분석 (수치는 전체 행 기준 컬럼별 통계 사용)
 2. 실용적이고 actionable한 인사이트 제공
 3. chart_data는 데이터에서 시각화할 수 있는 적절한 정보 선택
 4. chart_type은 "bar", "line", "pie" 중 하나
@@ -1884,48 +2195,11 @@ pub async fn analyze_dataset_data(
 6. 한국어로 작성"#,
         dataset_name,
         data_preview,
-        rows.len(),
+        row_count,
         columns.len()
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, gemini_api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
-
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
-
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct AnalysisResponse {
         summary: String,
         insights: Vec<String>,
@@ -1933,9 +2207,29 @@ pub async fn analyze_dataset_data(
         chart_data: Option<ChartDataResult>,
     }
 
-    let json_text = extract_json(&text);
-    let analysis: AnalysisResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("분석 JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<AnalysisResponse>()
+    });
+
+    // 캐시 확인 (같은 데이터셋/모델/설정이면 재사용)
+    let cache_keys = make_cache_key(model, &prompt, &generation_config);
+    let cached = cache_lookup("dataset_analyze", &cache_keys.0);
+
+    let response = if let Some(entry) = &cached {
+        (entry.response_text.clone(), entry.input_tokens, entry.output_tokens, 0.0)
+    } else {
+        check_budget(PROVIDER, "dataset_analyze", model, &prompt, 0.0)?;
+        generate_content(&client, gemini_api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?
+    };
+
+    let (analysis, text, input_tokens, output_tokens, cost) =
+        parse_or_repair::<AnalysisResponse>(&client, gemini_api_key, model, "dataset_analyze", &prompt, &generation_config, response).await?;
+
+    if cached.is_none() {
+        cache_store("dataset_analyze", model, cache_keys, &text, input_tokens, output_tokens, cost);
+    }
 
     let statistics: Vec<(String, String)> = analysis.statistics
         .into_iter()
@@ -1953,117 +2247,147 @@ pub async fn analyze_dataset_data(
         input_tokens,
         output_tokens,
         cost,
+        cached.map(|entry| cache_saved_usd(PROVIDER, model, &entry)),
     ))
 }
 
-/// 데이터셋 질문 답변
-pub async fn query_dataset_data(
+/// 데이터셋 질문을 SQL로 바꾼 결과
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DatasetSqlPlan {
+    pub sql: String,
+    pub explanation: String,
+}
+
+/// 데이터셋 질문 → SQLite SELECT 문 (전체 행에서 로컬로 실행할 쿼리)
+/// table_description: 테이블 정의 + 행 수 + 예시 행
+/// previous: 이전에 만든 SQL과 실행 오류 (있으면 고쳐서 다시 만듦)
+/// 반환: (SQL, 입력 토큰, 출력 토큰, 비용)
+pub async fn generate_dataset_sql(
     gemini_api_key: &str,
     model: &str,
     dataset_name: &str,
-    columns: &[String],
-    rows: &[Vec<String>],
+    table_description: &str,
     question: &str,
-) -> Result<(String, Vec<usize>, i64, i64, f64), String> {
+    previous: Option<(&str, &str)>,
+) -> Result<(DatasetSqlPlan, i64, i64, f64), String> {
     let client = Client::new();
     let model = if model.is_empty() { "gemini-2.0-flash" } else { model };
 
-    // 데이터 전체 (최대 300행)
-    let data_rows: Vec<String> = rows.iter()
-        .take(300)
-        .enumerate()
-        .map(|(i, r)| format!("[{}] {}", i, r.join(" | ")))
-        .collect();
-
-    let data_content = format!(
-        "컬럼: {}\n\n데이터 ({}행):\n{}",
-        columns.join(" | "),
-        rows.len().min(300),
-        data_rows.join("\n")
-    );
+    let retry = previous
+        .map(|(sql, error)| format!(
+            "\n## 이전 SQL (실행 실패)\n{}\n\n## 오류\n{}\n\n오류를 고친 SQL을 다시 작성하세요.\n",
+            sql, error
+        ))
+        .unwrap_or_default();
 
-    let prompt = format!(r#"당신은 데이터 분석 전문가입니다. 사용자의 질문에 데이터를 기반으로 답변해주세요.
+    let prompt = format!(r#"당신은 SQLite 전문가입니다. 사용자의 질문에 답하기 위한 SQL 쿼리를 작성해주세요.
+쿼리는 아래 테이블의 전체 행에 대해 실행되고, 그 결과로 답변을 만듭니다.
 
 ## 데이터셋: {}
 
-## 데이터
+## 테이블
 {}
 
 ## 사용자 질문
 {}
-
+{}
 ## 응답 형식 (JSON):
 {{
-    "answer": "질문에 대한 상세하고 친절한 답변 (데이터 기반)",
-    "relevant_row_indices": [0, 1, 2]
+    "sql": "SELECT ...",
+    "explanation": "쿼리가 무엇을 계산하는지 한 문장 설명"
 }}
 
 요구사항:
-1. 데이터에서 찾은 정보를 기반으로 정확하게 답변
-2. 수치가 있다면 정확한 수치 제공
-3. 관련된 행의 인덱스(0부터 시작)를 relevant_row_indices에 포함
-4. 답변은 친절하고 이해하기 쉽게
-5. 마크다운 기호(#, *, -, 등) 사용하지 않기
-6. 한국어로 작성"#,
+1. SQLite 문법의 SELECT (또는 WITH ... SELECT) 한 문장만 작성
+2. 테이블은 data 하나뿐이며 컬럼 이름은 반드시 큰따옴표로 감싸기
+3. 합계/평균/개수/순위 등은 SQL의 집계(SUM, AVG, COUNT, GROUP BY, ORDER BY)로 계산
+4. 행 목록이 필요하면 관련 컬럼만 고르고 LIMIT 100 이하로 제한
+5. 날짜는 'YYYY-MM-DD' 문자열이므로 문자열 비교나 strftime/substr 사용
+6. explanation은 한국어로 작성"#,
         dataset_name,
-        data_content,
-        question
+        table_description,
+        question,
+        retry
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, gemini_api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.2,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
+    check_budget(PROVIDER, "dataset_query", model, &prompt, 0.0)?;
 
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
+    let generation_config = json!({
+        "temperature": 0.1,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<DatasetSqlPlan>()
+    });
 
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
+    let response = generate_content(&client, gemini_api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
+
+    let (plan, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<DatasetSqlPlan>(&client, gemini_api_key, model, "dataset_query", &prompt, &generation_config, response).await?;
+
+    Ok((plan, input_tokens, output_tokens, cost))
+}
+
+/// 로컬에서 실행한 SQL 결과로 질문 답변
+/// 반환: (답변, 입력 토큰, 출력 토큰, 비용)
+pub async fn answer_dataset_question(
+    gemini_api_key: &str,
+    model: &str,
+    dataset_name: &str,
+    question: &str,
+    sql: &str,
+    result_table: &str,
+) -> Result<(String, i64, i64, f64), String> {
+    let client = Client::new();
+    let model = if model.is_empty() { "gemini-2.0-flash" } else { model };
 
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let prompt = format!(r#"당신은 데이터 분석 전문가입니다. 데이터셋 전체 행에 대해 실행한 SQL 결과를 바탕으로 사용자의 질문에 답변해주세요.
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+## 데이터셋: {}
+
+## 사용자 질문
+{}
+
+## 실행한 SQL
+{}
+
+## 실행 결과
+{}
+
+## 응답 형식 (JSON):
+{{
+    "answer": "질문에 대한 상세하고 친절한 답변 (실행 결과 기반)"
+}}
+
+요구사항:
+1. 실행 결과에 있는 수치만 사용하고 추측하지 않기
+2. 결과가 비어 있으면 조건에 맞는 데이터가 없다고 답변
+3. 답변은 친절하고 이해하기 쉽게
+4. 마크다운 기호(#, *, -, 등) 사용하지 않기
+5. 한국어로 작성"#,
+        dataset_name,
+        question,
+        sql,
+        result_table
+    );
 
-    #[derive(Deserialize)]
-    struct QueryResponse {
+    check_budget(PROVIDER, "dataset_query", model, &prompt, 0.0)?;
+
+    #[derive(Deserialize, JsonSchema)]
+    struct AnswerResponse {
         answer: String,
-        relevant_row_indices: Vec<usize>,
     }
 
-    let json_text = extract_json(&text);
-    let query_resp: QueryResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("응답 JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let generation_config = json!({
+        "temperature": 0.2,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<AnswerResponse>()
+    });
 
-    Ok((
-        query_resp.answer,
-        query_resp.relevant_row_indices,
-        input_tokens,
-        output_tokens,
-        cost,
-    ))
+    let response = generate_content(&client, gemini_api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
+
+    let (answer, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<AnswerResponse>(&client, gemini_api_key, model, "dataset_query", &prompt, &generation_config, response).await?;
+
+    Ok((answer.answer, input_tokens, output_tokens, cost))
 }
 
 // ===== AI 기반 데이터 추출 (Extract) =====
@@ -2075,6 +2399,10 @@ pub struct ExtractResult {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cost_usd: f64,
+    #[serde(default)]
+    pub cached: bool,
+    #[serde(default)]
+    pub saved_usd: f64,   // 캐시 적중 시 절감액
 }
 
 /// URL에서 스키마에 맞는 데이터 추출
@@ -2083,6 +2411,18 @@ pub async fn extract_data_from_url(
     model: &str,
     url: &str,
     schema: &str,  // 사용자가 정의한 추출 스키마 (자연어 또는 JSON 형식)
+) -> Result<ExtractResult, String> {
+    extract_data_with_budget(gemini_api_key, model, url, schema, "extract", 0.0).await
+}
+
+/// URL 데이터 추출 (예산 확인용 작업 이름과 아직 기록되지 않은 비용 지정)
+async fn extract_data_with_budget(
+    gemini_api_key: &str,
+    model: &str,
+    url: &str,
+    schema: &str,
+    operation: &str,
+    pending_usd: f64,
 ) -> Result<ExtractResult, String> {
     // 1. 페이지 내용 가져오기
     let content = fetch_page_content(url).await?;
@@ -2119,47 +2459,34 @@ JSON 외의 텍스트는 포함하지 마세요."#,
         schema
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, gemini_api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.1,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
+    let generation_config = json!({
+        "temperature": 0.1,
+        "responseMimeType": "application/json"
+    });
 
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    // 캐시 확인 (같은 페이지 내용 + 스키마면 재사용)
+    let cache_keys = make_cache_key(model, &prompt, &generation_config);
+    let cached = cache_lookup("extract", &cache_keys.0);
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = if let Some(entry) = &cached {
+        (entry.response_text.clone(), entry.input_tokens, entry.output_tokens, 0.0)
+    } else {
+        check_budget(PROVIDER, operation, model, &prompt, pending_usd)?;
+        generate_content(&client, gemini_api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?
+    };
 
-    // JSON 파싱
-    let json_text = extract_json(&text);
-    let data: serde_json::Value = serde_json::from_str(&json_text)
-        .unwrap_or(serde_json::json!({"raw_text": text}));
+    // 추출 형식은 사용자가 정하므로 스키마 없이 JSON 여부만 검증 (파싱에 성공한 응답만 캐시)
+    let first_text = response.0.clone();
+    let (data, input_tokens, output_tokens, cost) =
+        match parse_or_repair::<serde_json::Value>(&client, gemini_api_key, model, operation, &prompt, &generation_config, response).await {
+            Ok((value, text, input, output, cost)) => {
+                if cached.is_none() {
+                    cache_store("extract", model, cache_keys, &text, input, output, cost);
+                }
+                (value, input, output, cost)
+            }
+            Err(e) => (serde_json::json!({"raw_text": first_text}), e.input_tokens, e.output_tokens, e.cost),
+        };
 
     Ok(ExtractResult {
         url: url.to_string(),
@@ -2167,6 +2494,8 @@ JSON 외의 텍스트는 포함하지 마세요."#,
         input_tokens,
         output_tokens,
         cost_usd: cost,
+        saved_usd: cached.as_ref().map(|entry| cache_saved_usd(PROVIDER, model, entry)).unwrap_or(0.0),
+        cached: cached.is_some(),
     })
 }
 
@@ -2178,10 +2507,14 @@ pub async fn extract_data_batch(
     schema: &str,
 ) -> Result<Vec<ExtractResult>, String> {
     let mut results = Vec::new();
+    let mut pending_usd = 0.0; // 일괄 추출 비용은 끝난 뒤 한 번에 기록되므로 누적해서 예산 확인
 
     for url in urls {
-        match extract_data_from_url(gemini_api_key, model, url, schema).await {
-            Ok(result) => results.push(result),
+        match extract_data_with_budget(gemini_api_key, model, url, schema, "extract_batch", pending_usd).await {
+            Ok(result) => {
+                pending_usd += result.cost_usd;
+                results.push(result);
+            }
             Err(e) => {
                 // 실패해도 계속 진행, 에러는 로그만
                 results.push(ExtractResult {
@@ -2190,6 +2523,8 @@ pub async fn extract_data_batch(
                     input_tokens: 0,
                     output_tokens: 0,
                     cost_usd: 0.0,
+                    cached: false,
+                    saved_usd: 0.0,
                 });
             }
         }
@@ -2301,44 +2636,15 @@ async fn decide_next_action(
         steps = if steps_summary.is_empty() { "없음".to_string() } else { steps_summary }
     );
 
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            model, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "temperature": 0.3,
-                "responseMimeType": "application/json"
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?;
-
-    let gemini_resp: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = gemini_resp
-        .candidates
-        .first()
-        .and_then(|c| c.content.parts.first())
-        .map(|p| p.text.clone())
-        .ok_or("응답 없음")?;
-
-    let (input_tokens, output_tokens) = gemini_resp
-        .usage_metadata
-        .map(|u| (u.prompt_token_count, u.candidates_token_count))
-        .unwrap_or((0, 0));
+    let generation_config = json!({
+        "temperature": 0.3,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<ActionResponse>()
+    });
 
-    let (input_price, output_price) = get_model_price(model);
-    let cost = (input_tokens as f64 * input_price / 1_000_000.0)
-        + (output_tokens as f64 * output_price / 1_000_000.0);
+    let response = generate_content(&client, api_key, model, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, JsonSchema)]
     struct ActionResponse {
         action_type: String,
         selector: Option<String>,
@@ -2346,9 +2652,8 @@ async fn decide_next_action(
         reason: String,
     }
 
-    let json_text = extract_json(&text);
-    let action_resp: ActionResponse = serde_json::from_str(&json_text)
-        .map_err(|e| format!("액션 JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let (action_resp, _, input_tokens, output_tokens, cost) =
+        parse_or_repair::<ActionResponse>(&client, api_key, model, "agent", &prompt, &generation_config, response).await?;
 
     let action_type = match action_resp.action_type.to_lowercase().as_str() {
         "navigate" => AgentActionType::Navigate,
@@ -2894,7 +3199,28 @@ where
             current_url = url.to_string();
         }
 
-        // 2. AI가 다음 액션 결정
+        // 2. 예산 확인 (초과 시 지금까지의 사용량과 함께 중단)
+        let price_model = if model.is_empty() { "gemini-2.0-flash" } else { model };
+        let budget_prompt = format!(
+            "{}\n{}\n{}",
+            goal,
+            page_elements.chars().take(3000).collect::<String>(),
+            page_text.chars().take(2000).collect::<String>()
+        );
+        if let Err(e) = check_budget(PROVIDER, "agent", price_model, &budget_prompt, total_cost) {
+            let _ = page.close().await;
+            return Ok(AgentResult {
+                goal: goal.to_string(),
+                success: false,
+                steps,
+                final_data: Some(serde_json::json!({"error": e})),
+                total_input_tokens,
+                total_output_tokens,
+                total_cost_usd: total_cost,
+            });
+        }
+
+        // 3. AI가 다음 액션 결정
         let (action, input_tokens, output_tokens, cost) = decide_next_action(
             gemini_api_key,
             model,
@@ -2910,7 +3236,7 @@ where
         total_output_tokens += output_tokens;
         total_cost += cost;
 
-        // 3. 액션 실행 (동일한 페이지에서)
+        // 4. 액션 실행 (동일한 페이지에서)
         let result = execute_action_on_page(&page, &mut current_url, &action)
             .await
             .unwrap_or_else(|e| format!("액션 실행 실패: {}", e));
@@ -2918,7 +3244,7 @@ where
         // 액션 실행 후 대기 (페이지 로딩 및 렌더링 대기)
         sleep(Duration::from_millis(1500)).await;
 
-        // 4. 단계 기록
+        // 5. 단계 기록
         let step = AgentStep {
             step_number: step_num,
             action: action.clone(),
@@ -2931,7 +3257,7 @@ where
 
         steps.push(step);
 
-        // 5. Done 액션이면 종료
+        // 6. Done 액션이면 종료
         if matches!(action.action_type, AgentActionType::Done) {
             // 페이지 닫기
             let _ = page.close().await;
@@ -3852,6 +4178,8 @@ pub async fn get_ai_consulting(
         result.recommendations.join("\n")
     );
 
+    check_budget(PROVIDER, "consulting", "gemini-2.0-flash", &prompt, 0.0)?;
+
     let client = Client::new();
     let response = client
         .post(format!(
@@ -3943,58 +4271,46 @@ pub async fn get_folder_rename_suggestions(
         folder_list
     );
 
-    let client = Client::new();
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            DEFAULT_MODEL, api_key
-        ))
-        .json(&json!({
-            "contents": [{"parts": [{"text": prompt}]}],
-            "generationConfig": {
-                "maxOutputTokens": 2000,
-                "temperature": 0.5
-            }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("AI 요청 실패: {}", e))?;
+    check_budget(PROVIDER, "folder_rename", DEFAULT_MODEL, &prompt, 0.0)?;
 
-    if !response.status().is_success() {
-        return Err(format!("AI 오류: {}", response.status()));
+    #[derive(Deserialize, JsonSchema)]
+    struct RenameResponse {
+        index: usize,
+        suggested_name: String,
+        #[serde(default)]
+        reason: String,
     }
 
-    let body: serde_json::Value = response.json().await.map_err(|e| format!("응답 파싱 실패: {}", e))?;
-
-    let text = body["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .ok_or_else(|| "AI 응답을 파싱할 수 없습니다".to_string())?;
+    let client = Client::new();
+    let generation_config = json!({
+        "maxOutputTokens": 2000,
+        "temperature": 0.5,
+        "responseMimeType": "application/json",
+        "responseSchema": response_schema::<Vec<RenameResponse>>()
+    });
 
-    // JSON 추출 및 파싱
-    let json_text = extract_json(text);
-    let suggestions: Vec<serde_json::Value> = serde_json::from_str(&json_text)
-        .map_err(|e| format!("응답 JSON 파싱 실패: {} - 원본: {}", e, json_text))?;
+    let response = generate_content(&client, api_key, DEFAULT_MODEL, json!([{"parts": [{"text": prompt}]}]), &generation_config).await?;
+    let (suggestions, _, _, _, _) =
+        parse_or_repair::<Vec<RenameResponse>>(&client, api_key, DEFAULT_MODEL, "folder_rename", &prompt, &generation_config, response).await?;
 
     let result: Vec<FolderRenameSuggestion> = suggestions
-        .iter()
+        .into_iter()
         .filter_map(|s| {
-            let index = s["index"].as_i64()? as usize;
-            if index == 0 || index > folders_info.len() {
+            if s.index == 0 || s.index > folders_info.len() {
                 return None;
             }
-            let (original_path, original_name) = folders_info.get(index - 1)?;
-            let suggested_name = s["suggested_name"].as_str()?;
+            let (original_path, original_name) = folders_info.get(s.index - 1)?;
 
             // 같은 이름이면 제외
-            if *original_name == suggested_name {
+            if *original_name == s.suggested_name {
                 return None;
             }
 
             Some(FolderRenameSuggestion {
                 original_path: original_path.to_string(),
                 original_name: original_name.to_string(),
-                suggested_name: suggested_name.to_string(),
-                reason: s["reason"].as_str().unwrap_or("").to_string(),
+                suggested_name: s.suggested_name,
+                reason: s.reason,
             })
         })
         .collect();
@@ -0,0 +1,176 @@
+// ===== 첨부파일 저장소 (SHA-256 내용 주소) =====
+//
+// 첨부파일마다 내용의 SHA-256을 기록
+// - 복사 모드: <저장 폴더>/<해시 앞 2자리>/<해시>.<확장자> 에 저장해 같은 파일은 한 번만 보관
+// - 링크 모드: 원본이 없어지거나 바뀐 것을 해시로 확인하고, 옮겨진 파일은 같은 해시를 가진 파일을 찾아 다시 연결
+// 저장소 정리(GC)와 저장 폴더 이동은 lib의 collect_attachment_garbage / move_attachment_storage 참고
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 옮겨진 파일을 찾을 때 살펴볼 최대 파일 수 / 폴더 깊이
+const MAX_SCAN_FILES: usize = 200_000;
+const MAX_SCAN_DEPTH: usize = 8;
+/// 이 시간보다 오래된 임시 파일만 정리 대상 (그 전에는 복사 중일 수 있음)
+const TEMP_FILE_GRACE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// 파일 SHA-256 (16진수 소문자)
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 해시로 정해지는 저장 경로 (확장자는 원래 파일명에서, 연결 프로그램으로 열 수 있도록 유지)
+pub fn content_path(storage_dir: &Path, sha256: &str, file_name: &str) -> PathBuf {
+    let ext = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let name = if ext.is_empty() { sha256.to_string() } else { format!("{}.{}", sha256, ext) };
+    storage_dir.join(&sha256[..2]).join(name)
+}
+
+/// 저장소에 파일 보관 (같은 내용이 이미 있으면 복사하지 않음), 저장 경로 반환
+pub fn store_file(storage_dir: &Path, source: &Path, sha256: &str, file_name: &str) -> Result<PathBuf, String> {
+    let target = content_path(storage_dir, sha256, file_name);
+    let source_size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+    if fs::metadata(&target).map(|m| m.len() == source_size).unwrap_or(false) {
+        return Ok(target);
+    }
+
+    let dir = target.parent().ok_or("잘못된 저장 경로")?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    // 복사 중 중단돼도 불완전한 파일이 해시 이름으로 남지 않도록 임시 파일에 복사 후 이름 변경
+    let temp = dir.join(format!(".{}.tmp", sha256));
+    fs::copy(source, &temp).map_err(|e| e.to_string())?;
+    fs::rename(&temp, &target).map_err(|e| {
+        fs::remove_file(&temp).ok();
+        e.to_string()
+    })?;
+    Ok(target)
+}
+
+/// roots 아래에서 크기와 SHA-256이 같은 파일 찾기 (숨김 폴더와 심볼릭 링크는 건너뜀)
+pub fn find_by_hash(roots: &[PathBuf], sha256: &str, size: u64) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut visited = 0usize;
+    let mut stack: Vec<(PathBuf, usize)> = roots.iter().map(|r| (r.clone(), 0)).collect();
+
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > MAX_SCAN_FILES {
+                return found;
+            }
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if depth < MAX_SCAN_DEPTH {
+                    stack.push((path, depth + 1));
+                }
+            } else if file_type.is_file()
+                && entry.metadata().map(|m| m.len() == size).unwrap_or(false)
+                && hash_file(&path).map(|h| h == sha256).unwrap_or(false)
+                && !found.contains(&path)
+            {
+                found.push(path);
+            }
+        }
+    }
+    found
+}
+
+/// 저장소 형식(<해시 앞 2자리>/<해시>[.확장자], 복사 중 남은 임시 파일 포함)의 파일인지
+pub fn is_content_file(storage_dir: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(storage_dir) else {
+        return false;
+    };
+    let parts: Vec<&str> = relative.iter().filter_map(|p| p.to_str()).collect();
+    let [shard, name] = parts.as_slice() else {
+        return false;
+    };
+    let name = name.strip_prefix('.').and_then(|n| n.strip_suffix(".tmp")).unwrap_or(name);
+    let hash = name.split('.').next().unwrap_or("");
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) && hash.starts_with(*shard)
+}
+
+/// 아직 복사 중일 수 있는 임시 파일 (.<해시>.tmp, 만든 지 얼마 안 된 것)
+pub fn is_fresh_temp_file(path: &Path) -> bool {
+    let is_temp = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.starts_with('.') && n.ends_with(".tmp"))
+        .unwrap_or(false);
+    is_temp
+        && fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|modified| modified.elapsed().map(|age| age < TEMP_FILE_GRACE).unwrap_or(true))
+            .unwrap_or(true)
+}
+
+/// 폴더 아래 모든 파일 (심볼릭 링크는 따라가지 않음)
+pub fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => stack.push(entry.path()),
+                Ok(t) if t.is_file() => files.push(entry.path()),
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+/// 파일 복사 후 크기 확인 (다른 드라이브로 옮길 수 있도록 rename 대신 복사, 원본 삭제는 호출한 쪽에서)
+pub fn copy_verified(source: &Path, target: &Path) -> Result<u64, String> {
+    if let Some(dir) = target.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let copied = fs::copy(source, target).map_err(|e| format!("{}: {}", source.display(), e))?;
+    let expected = fs::metadata(source).map_err(|e| e.to_string())?.len();
+    if copied != expected {
+        fs::remove_file(target).ok();
+        return Err(format!("{}: 복사한 크기가 다릅니다", source.display()));
+    }
+    Ok(copied)
+}
+
+/// 비어 있는 해시 폴더(<해시 앞 2자리>) 정리 (저장 폴더의 다른 폴더는 건드리지 않음)
+pub fn remove_empty_shards(storage_dir: &Path) {
+    let Ok(entries) = fs::read_dir(storage_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_shard = name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit());
+        if is_shard && entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            fs::remove_dir(entry.path()).ok(); // 비어 있지 않으면 실패 → 무시
+        }
+    }
+}
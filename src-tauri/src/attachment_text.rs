@@ -0,0 +1,150 @@
+// ===== 첨부파일 본문 추출 =====
+//
+// 첨부할 때 PDF, DOCX, 엑셀(XLSX/XLS/ODS), 마크다운, 텍스트 파일의 본문을 뽑아 attachment_texts에 저장
+// 저장된 본문은 첨부파일 검색과 AI 찾기(search_memo)의 참고 자료로 사용
+// 링크 모드 첨부파일은 원본이 바뀔 수 있으므로 추출 당시 수정 시각/크기를 기록해 두고 바뀌면 다시 추출
+
+use regex::Regex;
+use std::io::Read;
+use std::path::Path;
+
+/// 저장할 본문 최대 길이 (문자 수)
+pub const MAX_TEXT_CHARS: usize = 500_000;
+
+/// 본문을 추출할 수 있는 파일인지 (확장자 기준)
+pub fn is_supported(path: &str) -> bool {
+    matches!(
+        extension(path).as_str(),
+        "pdf" | "docx" | "xlsx" | "xlsm" | "xls" | "ods" | "md" | "markdown" | "txt" | "csv" | "tsv" | "log"
+    )
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// 파일 수정 시각(unix 초)과 크기
+pub fn file_stamp(path: &str) -> Option<(i64, i64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs() as i64;
+    Some((modified, meta.len() as i64))
+}
+
+/// 파일 본문 추출 (지원하지 않는 형식이면 None)
+pub fn extract_text(path: &str) -> Result<Option<String>, String> {
+    let text = match extension(path).as_str() {
+        "pdf" => extract_pdf(path)?,
+        "docx" => extract_docx(path)?,
+        "xlsx" | "xlsm" | "xls" | "ods" => extract_spreadsheet(path)?,
+        "md" | "markdown" | "txt" | "csv" | "tsv" | "log" => {
+            let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+            crate::bank_import::decode(&bytes, None)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(truncate(text.trim())))
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+fn extract_pdf(path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    // 손상되거나 특이한 PDF에서 라이브러리가 panic하는 경우가 있어 격리
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes))
+        .map_err(|_| "PDF를 읽을 수 없습니다".to_string())?
+        .map_err(|e| format!("PDF 읽기 실패: {}", e))
+}
+
+fn extract_docx(path: &str) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("DOCX 읽기 실패: {}", e))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("DOCX 읽기 실패: {}", e))?
+        .read_to_string(&mut xml)
+        .map_err(|e| e.to_string())?;
+    Ok(docx_xml_to_text(&xml))
+}
+
+/// WordprocessingML → 텍스트 (문단/줄바꿈/탭만 살리고 태그 제거)
+fn docx_xml_to_text(xml: &str) -> String {
+    let breaks = Regex::new(r"</w:p>|<w:br[^>]*/>|<w:cr[^>]*/>").unwrap();
+    let tabs = Regex::new(r"<w:tab[^>]*/>").unwrap();
+    let tags = Regex::new(r"<[^>]+>").unwrap();
+    let text = breaks.replace_all(xml, "\n");
+    let text = tabs.replace_all(&text, "\t");
+    let text = tags.replace_all(&text, "");
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn extract_spreadsheet(path: &str) -> Result<String, String> {
+    use calamine::Reader;
+
+    let mut workbook = calamine::open_workbook_auto(path).map_err(|e| format!("엑셀 읽기 실패: {}", e))?;
+    let mut text = String::new();
+    for name in workbook.sheet_names() {
+        let Ok(range) = workbook.worksheet_range(&name) else {
+            continue;
+        };
+        text.push_str(&format!("## {}\n", name));
+        for row in range.rows() {
+            let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+            if cells.iter().any(|c| !c.is_empty()) {
+                text.push_str(&cells.join("\t"));
+                text.push('\n');
+            }
+        }
+        text.push('\n');
+        if text.chars().count() > MAX_TEXT_CHARS {
+            break;
+        }
+    }
+    Ok(text)
+}
+
+/// 검색어가 처음 나오는 곳 주변 발췌 (없으면 앞부분)
+pub fn excerpt(content: &str, query: &str, max_chars: usize) -> String {
+    let lower = content.to_lowercase();
+    // to_lowercase로 길이가 바뀌는 문자가 있으면 위치가 어긋나므로 그때는 앞부분 사용
+    let position = if lower.len() == content.len() {
+        query
+            .split_whitespace()
+            .filter_map(|term| lower.find(&term.to_lowercase()))
+            .min()
+    } else {
+        None
+    };
+    let start_char = position
+        .map(|pos| content[..pos].chars().count().saturating_sub(max_chars / 4))
+        .unwrap_or(0);
+
+    let text: String = content.chars().skip(start_char).take(max_chars).collect();
+    let mut result = String::new();
+    if start_char > 0 {
+        result.push('…');
+    }
+    result.push_str(&text);
+    if content.chars().count() > start_char + max_chars {
+        result.push('…');
+    }
+    result
+}
@@ -0,0 +1,429 @@
+// ===== 은행 거래내역 가져오기 (CSV / OFX·QFX) =====
+//
+// CSV는 열 매핑(CsvMapping)으로 날짜/적요/금액 열을 지정 - 은행별로 프로필로 저장해 재사용
+// 금액은 한 열(부호로 입출금 구분) 또는 출금/입금 두 열 모두 지원
+// 국내 은행 CSV는 대부분 CP949(EUC-KR)라서 UTF-8이 아니면 EUC-KR로 읽음
+
+use crate::{currency, db};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// CSV 열 매핑 (열은 헤더 이름 또는 1부터 시작하는 열 번호)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvMapping {
+    #[serde(default)]
+    pub delimiter: Option<String>,          // 없으면 자동 감지 (, ; 탭 |)
+    #[serde(default)]
+    pub encoding: Option<String>,           // utf-8, euc-kr (없으면 자동)
+    #[serde(default)]
+    pub skip_rows: usize,                   // 헤더 앞 안내문 줄 수
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    pub date_column: String,
+    #[serde(default)]
+    pub date_format: Option<String>,        // chrono 형식 (예: "%Y.%m.%d %H:%M:%S"), 없으면 흔한 형식 자동
+    pub description_column: String,
+    #[serde(default)]
+    pub memo_column: Option<String>,        // 적요 뒤에 덧붙일 열
+    #[serde(default)]
+    pub amount_column: Option<String>,      // 부호 있는 금액 (음수 = 지출)
+    #[serde(default)]
+    pub withdrawal_column: Option<String>,  // 출금액
+    #[serde(default)]
+    pub deposit_column: Option<String>,     // 입금액
+    #[serde(default)]
+    pub negate_amount: bool,                // 카드 명세서처럼 양수가 지출이면 true
+    #[serde(default)]
+    pub currency_column: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,           // 통화 열이 없을 때의 통화 (없으면 기준 통화)
+    #[serde(default)]
+    pub category_column: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 가져온 거래 한 줄 (금액은 통화의 최소 단위, 항상 양수)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedRow {
+    pub line: usize,
+    pub tx_date: String,
+    pub description: String,
+    pub amount: i64,
+    pub tx_type: String,
+    pub currency: String,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedStatement {
+    pub format: String,        // csv, ofx
+    pub headers: Vec<String>,
+    pub rows: Vec<ImportedRow>,
+    pub errors: Vec<String>,
+}
+
+/// 파일 내용을 문자열로 (BOM 제거, UTF-8이 아니면 EUC-KR)
+pub fn decode(bytes: &[u8], encoding: Option<&str>) -> String {
+    let label = encoding.map(|e| e.trim().to_lowercase());
+    let use_euc_kr = match label.as_deref() {
+        Some("euc-kr") | Some("cp949") | Some("ms949") => true,
+        Some(_) => false,
+        None => std::str::from_utf8(bytes).is_err(),
+    };
+    if use_euc_kr {
+        let (text, _, _) = encoding_rs::EUC_KR.decode(bytes);
+        text.into_owned()
+    } else {
+        let (text, _, _) = encoding_rs::UTF_8.decode(bytes);
+        text.into_owned()
+    }
+}
+
+/// OFX/QFX 파일인지 (확장자 또는 내용)
+pub fn is_ofx(file_name: &str, text: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower.ends_with(".ofx") || lower.ends_with(".qfx") || text.trim_start().starts_with("OFXHEADER") || text.contains("<OFX>")
+}
+
+/// 파일 읽기 + 형식에 맞게 파싱 (CSV인데 매핑이 없으면 헤더로 추측)
+pub fn parse_file(path: &str, mapping: Option<&CsvMapping>, default_currency: &str) -> Result<ParsedStatement, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("파일을 읽을 수 없습니다: {}", e))?;
+    let text = decode(&bytes, mapping.and_then(|m| m.encoding.as_deref()));
+    if is_ofx(path, &text) {
+        return Ok(parse_ofx(&text, default_currency));
+    }
+    match mapping {
+        Some(mapping) => parse_csv(&text, mapping, default_currency),
+        None => {
+            let headers = read_headers(&text, None, 0)?;
+            let mapping = guess_mapping(&headers).ok_or_else(|| {
+                format!("열 매핑을 지정해주세요 (헤더: {})", headers.join(", "))
+            })?;
+            parse_csv(&text, &mapping, default_currency)
+        }
+    }
+}
+
+fn detect_delimiter(text: &str, skip_rows: usize) -> u8 {
+    let line = text.lines().nth(skip_rows).unwrap_or_default();
+    [b',', b';', b'\t', b'|']
+        .into_iter()
+        .max_by_key(|d| line.matches(*d as char).count())
+        .unwrap_or(b',')
+}
+
+fn delimiter_of(mapping: Option<&CsvMapping>, text: &str, skip_rows: usize) -> u8 {
+    match mapping.and_then(|m| m.delimiter.as_deref()) {
+        Some("\\t") | Some("\t") | Some("tab") => b'\t',
+        Some(d) if d.len() == 1 => d.as_bytes()[0],
+        _ => detect_delimiter(text, skip_rows),
+    }
+}
+
+fn csv_records(text: &str, delimiter: u8) -> csv::StringRecordsIntoIter<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .into_records()
+}
+
+/// 헤더 줄 읽기 (매핑 편집 화면에서 열 목록 표시용)
+pub fn read_headers(text: &str, delimiter: Option<&str>, skip_rows: usize) -> Result<Vec<String>, String> {
+    let mapping = CsvMapping { delimiter: delimiter.map(str::to_string), ..Default::default() };
+    let delimiter = delimiter_of(Some(&mapping), text, skip_rows);
+    let record = csv_records(text, delimiter)
+        .nth(skip_rows)
+        .ok_or("빈 파일입니다")?
+        .map_err(|e| e.to_string())?;
+    Ok(record.iter().map(|h| h.trim().to_string()).collect())
+}
+
+/// 흔한 은행 CSV 헤더로 매핑 추측 (날짜/적요와 금액 열을 모두 찾았을 때만)
+pub fn guess_mapping(headers: &[String]) -> Option<CsvMapping> {
+    let find = |keys: &[&str]| {
+        headers
+            .iter()
+            .find(|h| {
+                let h = h.to_lowercase().replace(' ', "");
+                keys.iter().any(|k| h.contains(k))
+            })
+            .cloned()
+    };
+    let date_column = find(&["거래일시", "거래일자", "거래일", "이용일", "승인일", "날짜", "일자", "date"])?;
+    let description_column = find(&["적요", "거래내용", "내용", "가맹점", "이용처", "사용처", "description", "payee", "name"])?;
+    let withdrawal_column = find(&["출금", "찾으신", "withdrawal", "debit"]);
+    let deposit_column = find(&["입금", "맡기신", "deposit", "credit"]);
+    let amount_column = if withdrawal_column.is_none() && deposit_column.is_none() {
+        Some(find(&["이용금액", "승인금액", "거래금액", "금액", "amount"])?)
+    } else {
+        None
+    };
+    Some(CsvMapping {
+        has_header: true,
+        date_column,
+        description_column,
+        memo_column: find(&["메모", "memo", "비고"]),
+        amount_column,
+        withdrawal_column,
+        deposit_column,
+        currency_column: find(&["통화", "currency"]),
+        category_column: find(&["분류", "카테고리", "category"]),
+        ..Default::default()
+    })
+}
+
+fn column_index(headers: &[String], column: &str) -> Option<usize> {
+    let column = column.trim();
+    headers
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case(column))
+        .or_else(|| column.parse::<usize>().ok().filter(|n| *n >= 1).map(|n| n - 1))
+}
+
+/// 금액 문자열 파싱 ("1,234", "₩1,234", "(1,234)" = -1234, "1,234-" = -1234, "12.50 USD")
+pub fn parse_amount(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let negative = text.starts_with('-') || text.ends_with('-') || (text.starts_with('(') && text.ends_with(')'));
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+    let value: f64 = digits.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// 날짜 문자열 파싱 → 시간이 있으면 "YYYY-MM-DDTHH:MM", 없으면 "YYYY-MM-DD"
+pub fn parse_date(text: &str, format: Option<&str>) -> Option<String> {
+    let text = text.trim();
+    let datetime_formats = [
+        "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M",
+        "%Y.%m.%d %H:%M:%S", "%Y.%m.%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M",
+        "%Y%m%d%H%M%S",
+    ];
+    let date_formats = ["%Y-%m-%d", "%Y.%m.%d", "%Y/%m/%d", "%Y%m%d", "%Y. %m. %d", "%m/%d/%Y", "%d.%m.%Y"];
+
+    let format_datetime = |dt: NaiveDateTime| {
+        if dt.time() == NaiveTime::MIN {
+            dt.format("%Y-%m-%d").to_string()
+        } else {
+            dt.format("%Y-%m-%dT%H:%M").to_string()
+        }
+    };
+
+    if let Some(format) = format {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(format_datetime(dt));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return Some(date.format("%Y-%m-%d").to_string());
+        }
+    }
+    let text = text.trim_end_matches('.');
+    for fmt in datetime_formats {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, fmt) {
+            return Some(format_datetime(dt));
+        }
+    }
+    for fmt in date_formats {
+        if let Ok(date) = NaiveDate::parse_from_str(text, fmt) {
+            return Some(date.format("%Y-%m-%d").to_string());
+        }
+    }
+    None
+}
+
+/// 매핑에 따라 CSV 파싱 (문제 있는 줄은 건너뛰고 errors에 기록)
+pub fn parse_csv(text: &str, mapping: &CsvMapping, default_currency: &str) -> Result<ParsedStatement, String> {
+    let delimiter = delimiter_of(Some(mapping), text, mapping.skip_rows);
+    let mut records = csv_records(text, delimiter).skip(mapping.skip_rows);
+
+    let headers: Vec<String> = if mapping.has_header {
+        records
+            .next()
+            .ok_or("빈 파일입니다")?
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let column = |name: &str| column_index(&headers, name).ok_or_else(|| format!("열을 찾을 수 없습니다: {}", name));
+    let optional = |name: &Option<String>| name.as_deref().map(column).transpose();
+
+    let date_col = column(&mapping.date_column)?;
+    let description_col = column(&mapping.description_column)?;
+    let memo_col = optional(&mapping.memo_column)?;
+    let amount_col = optional(&mapping.amount_column)?;
+    let withdrawal_col = optional(&mapping.withdrawal_column)?;
+    let deposit_col = optional(&mapping.deposit_column)?;
+    let currency_col = optional(&mapping.currency_column)?;
+    let category_col = optional(&mapping.category_column)?;
+    if amount_col.is_none() && withdrawal_col.is_none() && deposit_col.is_none() {
+        return Err("금액 열(amount_column) 또는 출금/입금 열을 지정해주세요".to_string());
+    }
+    let fallback_currency = match mapping.currency.as_deref() {
+        Some(code) => currency::normalize(code)?,
+        None => default_currency.to_string(),
+    };
+
+    let first_line = mapping.skip_rows + usize::from(mapping.has_header) + 1;
+    let mut statement = ParsedStatement { format: "csv".to_string(), headers, ..Default::default() };
+
+    for (index, record) in records.enumerate() {
+        let line = first_line + index;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                statement.errors.push(format!("{}행: {}", line, e));
+                continue;
+            }
+        };
+        let cell = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).unwrap_or_default();
+        if record.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+
+        let Some(tx_date) = parse_date(cell(Some(date_col)), mapping.date_format.as_deref()) else {
+            // 합계 줄 등 날짜가 없는 줄은 건너뜀
+            statement.errors.push(format!("{}행: 날짜를 읽을 수 없습니다 ({})", line, cell(Some(date_col))));
+            continue;
+        };
+
+        let mut signed = match amount_col {
+            Some(col) => parse_amount(cell(Some(col))),
+            None => {
+                let withdrawal = parse_amount(cell(withdrawal_col)).unwrap_or(0.0).abs();
+                let deposit = parse_amount(cell(deposit_col)).unwrap_or(0.0).abs();
+                Some(deposit - withdrawal)
+            }
+        };
+        if mapping.negate_amount {
+            signed = signed.map(|v| -v);
+        }
+        let Some(signed) = signed.filter(|v| *v != 0.0) else {
+            statement.errors.push(format!("{}행: 금액이 없습니다", line));
+            continue;
+        };
+
+        let currency = match cell(currency_col) {
+            "" => fallback_currency.clone(),
+            code => currency::normalize(code).unwrap_or_else(|_| fallback_currency.clone()),
+        };
+        let mut description = cell(Some(description_col)).to_string();
+        let memo = cell(memo_col);
+        if !memo.is_empty() && !description.contains(memo) {
+            description = if description.is_empty() { memo.to_string() } else { format!("{} {}", description, memo) };
+        }
+
+        statement.rows.push(ImportedRow {
+            line,
+            tx_date,
+            description,
+            amount: currency::to_minor(signed.abs(), &currency),
+            tx_type: if signed > 0.0 { "income" } else { "expense" }.to_string(),
+            currency,
+            category: Some(cell(category_col).to_string()).filter(|c| !c.is_empty()),
+        });
+    }
+
+    Ok(statement)
+}
+
+/// OFX/QFX 파싱 (SGML 형식과 XML 형식 모두, STMTTRN만 읽음)
+pub fn parse_ofx(text: &str, default_currency: &str) -> ParsedStatement {
+    let start_re = Regex::new(r"(?i)<STMTTRN>").unwrap();
+    let end_re = Regex::new(r"(?i)</STMTTRN>|<STMTTRN>|</BANKTRANLIST>").unwrap();
+    let tag_re = Regex::new(r"(?i)<(TRNTYPE|DTPOSTED|TRNAMT|NAME|MEMO|PAYEE|CURRENCY|CURSYM)>([^<\r\n]*)").unwrap();
+    let curdef_re = Regex::new(r"(?i)<CURDEF>([^<\r\n]*)").unwrap();
+
+    let statement_currency = curdef_re
+        .captures(text)
+        .and_then(|c| currency::normalize(&c[1]).ok())
+        .unwrap_or_else(|| default_currency.to_string());
+    let mut statement = ParsedStatement { format: "ofx".to_string(), ..Default::default() };
+
+    for (index, start) in start_re.find_iter(text).enumerate() {
+        let rest = &text[start.end()..];
+        let block = &rest[..end_re.find(rest).map(|m| m.start()).unwrap_or(rest.len())];
+        let mut fields = std::collections::HashMap::new();
+        for caps in tag_re.captures_iter(block) {
+            fields.insert(caps[1].to_uppercase(), caps[2].trim().to_string());
+        }
+        let line = index + 1;
+
+        let tx_date = fields.get("DTPOSTED").and_then(|d| {
+            let digits: String = d.chars().take_while(|c| c.is_ascii_digit()).collect();
+            match digits.len() {
+                n if n >= 12 => parse_date(&digits[..12], Some("%Y%m%d%H%M")),
+                n if n >= 8 => parse_date(&digits[..8], Some("%Y%m%d")),
+                _ => None,
+            }
+        });
+        let Some(tx_date) = tx_date else {
+            statement.errors.push(format!("{}번째 거래: 날짜를 읽을 수 없습니다", line));
+            continue;
+        };
+        let Some(signed) = fields.get("TRNAMT").and_then(|a| parse_amount(a)).filter(|v| *v != 0.0) else {
+            statement.errors.push(format!("{}번째 거래: 금액이 없습니다", line));
+            continue;
+        };
+        let currency = fields
+            .get("CURSYM")
+            .and_then(|c| currency::normalize(c).ok())
+            .unwrap_or_else(|| statement_currency.clone());
+        let name = fields.get("NAME").or(fields.get("PAYEE")).cloned().unwrap_or_default();
+        let memo = fields.get("MEMO").cloned().unwrap_or_default();
+        let description = match (name.is_empty(), memo.is_empty() || name.contains(&memo)) {
+            (true, _) => memo,
+            (false, true) => name,
+            (false, false) => format!("{} {}", name, memo),
+        };
+
+        statement.rows.push(ImportedRow {
+            line,
+            tx_date,
+            description,
+            amount: currency::to_minor(signed.abs(), &currency),
+            tx_type: if signed > 0.0 { "income" } else { "expense" }.to_string(),
+            currency,
+            category: None,
+        });
+    }
+
+    statement
+}
+
+/// 분류 규칙용 패턴 (소문자, 숫자/기호 제거, 공백 정리 - "스타벅스 강남점 #1234" → "스타벅스 강남점")
+pub fn category_pattern(description: &str) -> String {
+    description
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphabetic() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 학습한 규칙으로 카테고리 추천 (같은 패턴 우선, 없으면 적요에 포함된 가장 긴 패턴, 같으면 적용 횟수 많은 것)
+pub fn suggest_category<'a>(rules: &'a [db::CategoryRule], description: &str) -> Option<&'a str> {
+    let pattern = category_pattern(description);
+    if pattern.is_empty() {
+        return None;
+    }
+    if let Some(rule) = rules.iter().find(|r| r.pattern == pattern) {
+        return Some(&rule.category);
+    }
+    rules
+        .iter()
+        .filter(|r| !r.pattern.is_empty() && pattern.contains(&r.pattern))
+        .max_by_key(|r| (r.pattern.chars().count(), r.hits))
+        .map(|r| r.category.as_str())
+}
@@ -0,0 +1,594 @@
+// ===== CalDAV 일정 동기화 =====
+//
+// 설정: caldav_url (캘린더 컬렉션 URL), caldav_username, caldav_password,
+//       caldav_conflict_policy ("last_writer_wins" 또는 "prompt")
+//
+// 일정마다 서버 리소스 href(schedules.google_event_id), ETag, 마지막 동기화 시점의 내용 해시(sync_hash)를 저장
+// - 서버 변경: ETag가 저장된 값과 다름
+// - 로컬 변경: 현재 내용 해시가 sync_hash와 다름
+// - 양쪽 모두 변경: last_writer_wins면 LAST-MODIFIED와 로컬 수정 시각을 비교, prompt면 충돌로 기록
+
+use crate::{db, ics, recurrence};
+use regex::Regex;
+use reqwest::{Client, Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    LastWriterWins,
+    Prompt,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalDavConfig {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub policy: ConflictPolicy,
+}
+
+impl CalDavConfig {
+    /// 설정에서 읽기 (URL이 없으면 에러)
+    pub fn load() -> Result<Self, String> {
+        let setting = |key: &str| db::get_setting(key).map_err(|e| e.to_string());
+        let mut url = setting("caldav_url")?.trim().to_string();
+        if url.is_empty() {
+            return Err("CalDAV 서버 URL이 설정되지 않았습니다".to_string());
+        }
+        // 컬렉션 URL은 항상 /로 끝나야 리소스 URL을 만들 수 있음
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        Url::parse(&url).map_err(|e| format!("잘못된 CalDAV URL: {}", e))?;
+
+        let policy = match setting("caldav_conflict_policy")?.as_str() {
+            "prompt" => ConflictPolicy::Prompt,
+            _ => ConflictPolicy::LastWriterWins,
+        };
+
+        Ok(Self {
+            url,
+            username: setting("caldav_username")?,
+            password: setting("caldav_password")?,
+            policy,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub pushed: usize,          // 서버로 올린 일정 (새로 만들거나 수정)
+    pub pulled: usize,          // 서버에서 받아 로컬에 반영한 일정
+    pub deleted_local: usize,   // 서버에서 삭제되어 로컬에서도 지운 일정
+    pub deleted_remote: usize,  // 로컬에서 삭제되어 서버에서도 지운 일정
+    pub conflicts: usize,       // 사용자 선택을 기다리는 충돌
+    pub errors: Vec<String>,    // 개별 일정 실패 (동기화는 계속 진행)
+}
+
+enum PutOutcome {
+    Saved,
+    PreconditionFailed,
+}
+
+// ----- HTTP -----
+
+fn request(client: &Client, config: &CalDavConfig, method: Method, url: &str) -> reqwest::RequestBuilder {
+    let builder = client.request(method, url);
+    if config.username.is_empty() {
+        builder
+    } else {
+        builder.basic_auth(&config.username, Some(&config.password))
+    }
+}
+
+/// href(절대 경로 또는 URL)를 컬렉션 기준 절대 URL로 변환
+/// 경로 조각마다 인코딩을 풀었다가 다시 인코딩해서, 서버마다 다른 퍼센트 인코딩을 통일 (비교와 요청에 그대로 사용)
+fn resolve_href(config: &CalDavConfig, href: &str) -> Option<String> {
+    let base = Url::parse(&config.url).ok()?;
+    let mut url = base.join(href.trim()).ok()?;
+    let path = url
+        .path_segments()?
+        .map(|segment| {
+            let decoded = urlencoding::decode(segment).map(|s| s.into_owned()).unwrap_or_else(|_| segment.to_string());
+            urlencoding::encode(&decoded).into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    url.set_path(&format!("/{}", path));
+    Some(url.to_string())
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// 컬렉션의 일정 리소스 목록 (절대 URL → ETag)
+async fn list_resources(client: &Client, config: &CalDavConfig) -> Result<HashMap<String, Option<String>>, String> {
+    let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/><d:resourcetype/></d:prop></d:propfind>"#;
+
+    let response = request(client, config, Method::from_bytes(b"PROPFIND").unwrap(), &config.url)
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("CalDAV 요청 실패: {}", e))?;
+
+    let status = response.status();
+    if status != StatusCode::MULTI_STATUS && !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("CalDAV 목록 조회 실패 ({}): {}", status, body));
+    }
+    let text = response.text().await.map_err(|e| e.to_string())?;
+
+    let response_re = Regex::new(r"(?s)<(?:[\w-]+:)?response\b[^>]*>(.*?)</(?:[\w-]+:)?response>").unwrap();
+    let href_re = Regex::new(r"(?s)<(?:[\w-]+:)?href\b[^>]*>(.*?)</(?:[\w-]+:)?href>").unwrap();
+    let etag_re = Regex::new(r"(?s)<(?:[\w-]+:)?getetag\b[^>]*>(.*?)</(?:[\w-]+:)?getetag>").unwrap();
+    let collection_re = Regex::new(r"<(?:[\w-]+:)?collection\b").unwrap();
+
+    let collection = resolve_href(config, &config.url);
+    let mut resources = HashMap::new();
+    for caps in response_re.captures_iter(&text) {
+        let block = &caps[1];
+        if collection_re.is_match(block) {
+            continue;
+        }
+        let href = match href_re.captures(block) {
+            Some(h) => xml_unescape(h[1].trim()),
+            None => continue,
+        };
+        let url = match resolve_href(config, &href) {
+            Some(url) if Some(&url) != collection.as_ref() => url,
+            _ => continue,
+        };
+        let etag = etag_re.captures(block).map(|e| xml_unescape(e[1].trim()));
+        resources.insert(url, etag);
+    }
+
+    Ok(resources)
+}
+
+/// 리소스 내용과 ETag (없으면 None)
+async fn fetch_resource(client: &Client, config: &CalDavConfig, url: &str) -> Result<Option<(String, Option<String>)>, String> {
+    let response = request(client, config, Method::GET, url)
+        .send()
+        .await
+        .map_err(|e| format!("CalDAV 요청 실패: {}", e))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("일정 조회 실패 ({}): {}", response.status(), url));
+    }
+    let etag = response
+        .headers()
+        .get("ETag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    Ok(Some((text, etag)))
+}
+
+/// 리소스 저장 (if_match가 없으면 새로 만들기 - 이미 있으면 실패)
+async fn put_resource(
+    client: &Client,
+    config: &CalDavConfig,
+    url: &str,
+    body: String,
+    if_match: Option<&str>,
+) -> Result<(PutOutcome, Option<String>), String> {
+    let builder = request(client, config, Method::PUT, url)
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .body(body);
+    let builder = match if_match {
+        Some(etag) => builder.header("If-Match", etag),
+        None => builder.header("If-None-Match", "*"),
+    };
+
+    let response = builder.send().await.map_err(|e| format!("CalDAV 요청 실패: {}", e))?;
+    let status = response.status();
+    if status == StatusCode::PRECONDITION_FAILED {
+        return Ok((PutOutcome::PreconditionFailed, None));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("일정 저장 실패 ({}): {}", status, body));
+    }
+
+    let etag = response
+        .headers()
+        .get("ETag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    // ETag를 돌려주지 않는 서버는 다시 조회
+    let etag = match etag {
+        Some(etag) => Some(etag),
+        None => fetch_resource(client, config, url).await?.and_then(|(_, etag)| etag),
+    };
+    Ok((PutOutcome::Saved, etag))
+}
+
+/// 리소스 삭제 (서버에서 이미 바뀌었으면 false)
+async fn delete_resource(client: &Client, config: &CalDavConfig, url: &str, etag: Option<&str>) -> Result<bool, String> {
+    let mut builder = request(client, config, Method::DELETE, url);
+    if let Some(etag) = etag {
+        builder = builder.header("If-Match", etag);
+    }
+    let response = builder.send().await.map_err(|e| format!("CalDAV 요청 실패: {}", e))?;
+    match response.status() {
+        StatusCode::PRECONDITION_FAILED => Ok(false),
+        status if status.is_success() || status == StatusCode::NOT_FOUND => Ok(true),
+        status => Err(format!("일정 삭제 실패 ({}): {}", status, url)),
+    }
+}
+
+/// 연결 테스트 (컬렉션 PROPFIND)
+pub async fn test_connection(url: &str, username: &str, password: &str) -> Result<String, String> {
+    let mut url = url.trim().to_string();
+    if url.is_empty() {
+        return Err("CalDAV 서버 URL이 비어있습니다".to_string());
+    }
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    let config = CalDavConfig {
+        url,
+        username: username.to_string(),
+        password: password.to_string(),
+        policy: ConflictPolicy::LastWriterWins,
+    };
+    let resources = list_resources(&Client::new(), &config).await?;
+    Ok(format!("✅ CalDAV 연결 성공 (일정 {}개)", resources.len()))
+}
+
+// ----- 변환 -----
+
+/// 동기화 대상 내용의 해시 (로컬 변경 감지용)
+fn schedule_hash(schedule: &db::Schedule) -> String {
+    use sha2::{Digest, Sha256};
+    let fields = [
+        schedule.title.as_str(),
+        schedule.start_time.as_deref().unwrap_or(""),
+        schedule.end_time.as_deref().unwrap_or(""),
+        schedule.location.as_deref().unwrap_or(""),
+        schedule.description.as_deref().unwrap_or(""),
+        schedule.recurrence.as_deref().unwrap_or(""),
+        schedule.exdates.as_deref().unwrap_or(""),
+    ];
+    Sha256::digest(fields.join("\u{1f}").as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn schedule_to_ics(schedule: &db::Schedule, uid: &str, updated_at: &str) -> String {
+    let event = ics::IcsEvent {
+        uid: uid.to_string(),
+        summary: schedule.title.clone(),
+        start: schedule.start_time.clone(),
+        end: schedule.end_time.clone(),
+        location: schedule.location.clone(),
+        description: schedule.description.clone(),
+        rrule: schedule.recurrence.clone(),
+        exdates: schedule
+            .exdates
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(|e| e.trim().to_string())
+            .filter(|e| !e.is_empty())
+            .collect(),
+        last_modified: recurrence::parse_datetime(updated_at),
+    };
+    ics::build_calendar(&ics::IcsCalendar { events: vec![event], todos: Vec::new() })
+}
+
+/// 서버 일정 내용을 로컬 일정에 반영 (없으면 새로 만들기), 반영된 일정 반환
+fn apply_remote_event(existing: Option<db::Schedule>, event: ics::IcsEvent) -> Result<db::Schedule, String> {
+    let rule = event.rrule.as_deref().map(str::trim).map(|r| r.strip_prefix("RRULE:").unwrap_or(r));
+    let recurrence = rule.filter(|r| recurrence::parse_rrule(r).is_ok()).map(str::to_string);
+    let exdates = if recurrence.is_some() && !event.exdates.is_empty() {
+        Some(event.exdates.join(","))
+    } else {
+        None
+    };
+    let title = if event.summary.trim().is_empty() { "(제목 없음)".to_string() } else { event.summary };
+
+    match existing {
+        Some(existing) => {
+            let schedule = db::Schedule {
+                title,
+                start_time: event.start,
+                end_time: event.end,
+                location: event.location,
+                description: event.description,
+                recurrence,
+                exdates,
+                ..existing
+            };
+            db::update_schedule(&schedule).map_err(|e| e.to_string())?;
+            Ok(schedule)
+        }
+        None => {
+            let mut schedule = db::Schedule {
+                id: 0,
+                memo_id: None,
+                title,
+                start_time: event.start,
+                end_time: event.end,
+                location: event.location,
+                description: event.description,
+                google_event_id: None,
+                created_at: String::new(),
+                recurrence,
+                exdates,
+            };
+            schedule.id = db::save_schedule(&schedule).map_err(|e| e.to_string())?;
+            db::set_ical_uid("schedules", schedule.id, &event.uid).map_err(|e| e.to_string())?;
+            Ok(schedule)
+        }
+    }
+}
+
+/// 리소스의 첫 VEVENT
+fn first_event(text: &str) -> Option<ics::IcsEvent> {
+    ics::parse_calendar(text).events.into_iter().next()
+}
+
+// ----- 동기화 -----
+
+struct Syncer {
+    client: Client,
+    config: CalDavConfig,
+    report: SyncReport,
+}
+
+impl Syncer {
+    /// 로컬 일정을 서버에 저장 (url이 없으면 새 리소스)
+    async fn push(&mut self, state: &db::ScheduleSyncState, url: Option<&str>, if_match: Option<&str>) -> Result<PutOutcome, String> {
+        let schedule = &state.schedule;
+        let uid = match state.ical_uid.as_deref().filter(|u| !u.is_empty()) {
+            Some(uid) => uid.to_string(),
+            None => db::ensure_ical_uid("schedules", schedule.id).map_err(|e| e.to_string())?,
+        };
+        let url = match url {
+            Some(url) => url.to_string(),
+            // UID에 /, @, 공백, # 등이 들어 있어도 리소스 이름 하나가 되도록 인코딩
+            None => format!("{}{}.ics", self.config.url, urlencoding::encode(&uid)),
+        };
+        let request_url = Url::parse(&url).map_err(|e| e.to_string())?;
+
+        let body = schedule_to_ics(schedule, &uid, &state.updated_at);
+        let (outcome, etag) = put_resource(&self.client, &self.config, request_url.as_str(), body, if_match).await?;
+        if let PutOutcome::Saved = outcome {
+            let stored_url = resolve_href(&self.config, request_url.as_str()).unwrap_or(url);
+            db::set_schedule_sync(schedule.id, Some(&stored_url), etag.as_deref(), Some(&schedule_hash(schedule)))
+                .map_err(|e| e.to_string())?;
+            self.report.pushed += 1;
+        }
+        Ok(outcome)
+    }
+
+    /// 서버 내용을 로컬에 반영
+    fn pull(&mut self, existing: Option<db::Schedule>, url: &str, text: &str, etag: Option<&str>) -> Result<(), String> {
+        let event = match first_event(text) {
+            Some(event) => event,
+            None => return Ok(()),  // VEVENT가 없는 리소스 (할일 등)는 건너뜀
+        };
+        let schedule = apply_remote_event(existing, event)?;
+        db::set_schedule_sync(schedule.id, Some(url), etag, Some(&schedule_hash(&schedule)))
+            .map_err(|e| e.to_string())?;
+        self.report.pulled += 1;
+        Ok(())
+    }
+
+    /// 양쪽이 모두 바뀐 일정 처리
+    async fn resolve(&mut self, state: &db::ScheduleSyncState, url: &str) -> Result<(), String> {
+        let (text, etag) = match fetch_resource(&self.client, &self.config, url).await? {
+            Some(resource) => resource,
+            None => {
+                // 그 사이 서버에서 삭제됨 → 로컬 수정본을 다시 올림
+                self.push(state, None, None).await?;
+                return Ok(());
+            }
+        };
+
+        if self.config.policy == ConflictPolicy::Prompt {
+            db::save_caldav_conflict(state.schedule.id, url, etag.as_deref(), &text).map_err(|e| e.to_string())?;
+            self.report.conflicts += 1;
+            return Ok(());
+        }
+
+        // 나중에 수정된 쪽이 이김 (서버에 LAST-MODIFIED가 없으면 로컬 우선)
+        let remote_modified = first_event(&text).and_then(|e| e.last_modified);
+        let local_modified = recurrence::parse_datetime(&state.updated_at);
+        let remote_wins = match (remote_modified, local_modified) {
+            (Some(remote), Some(local)) => remote > local,
+            (Some(_), None) => true,
+            _ => false,
+        };
+
+        if remote_wins {
+            self.pull(Some(state.schedule.clone()), url, &text, etag.as_deref())
+        } else {
+            match self.push(state, Some(url), etag.as_deref()).await? {
+                PutOutcome::Saved => Ok(()),
+                PutOutcome::PreconditionFailed => Err(format!("일정이 동기화 중에 다시 변경되었습니다: {}", state.schedule.title)),
+            }
+        }
+    }
+
+    async fn sync_linked(&mut self, state: &db::ScheduleSyncState, url: &str, remote: &HashMap<String, Option<String>>) -> Result<(), String> {
+        let local_changed = state.sync_hash.as_deref() != Some(schedule_hash(&state.schedule).as_str());
+
+        let remote_etag = match remote.get(url) {
+            Some(etag) => etag,
+            None => {
+                // 서버에서 삭제됨: 로컬에서 고친 적이 없으면 로컬도 삭제, 고쳤으면 다시 올림
+                if local_changed {
+                    self.push(state, None, None).await?;
+                } else {
+                    db::delete_synced_schedule(state.schedule.id).map_err(|e| e.to_string())?;
+                    self.report.deleted_local += 1;
+                }
+                return Ok(());
+            }
+        };
+        let remote_changed = remote_etag.is_none() || remote_etag != &state.etag;
+
+        match (local_changed, remote_changed) {
+            (false, false) => Ok(()),
+            (true, false) => match self.push(state, Some(url), state.etag.as_deref()).await? {
+                PutOutcome::Saved => Ok(()),
+                PutOutcome::PreconditionFailed => self.resolve(state, url).await,
+            },
+            (false, true) => match fetch_resource(&self.client, &self.config, url).await? {
+                Some((text, etag)) => self.pull(Some(state.schedule.clone()), url, &text, etag.as_deref().or(remote_etag.as_deref())),
+                None => Ok(()),
+            },
+            (true, true) => self.resolve(state, url).await,
+        }
+    }
+}
+
+/// 양방향 동기화
+pub async fn sync(config: CalDavConfig) -> Result<SyncReport, String> {
+    let client = Client::new();
+    let mut remote = list_resources(&client, &config).await?;
+    let mut syncer = Syncer { client, config, report: SyncReport::default() };
+
+    // 1. 로컬에서 삭제한 일정을 서버에서도 삭제
+    for (stored_url, etag) in db::get_caldav_tombstones().map_err(|e| e.to_string())? {
+        // 이전 버전은 인코딩을 푼 href를 저장했으므로 같은 형식으로 맞춰 비교
+        let url = resolve_href(&syncer.config, &stored_url).unwrap_or_else(|| stored_url.clone());
+        if remote.contains_key(&url) {
+            match delete_resource(&syncer.client, &syncer.config, &url, etag.as_deref()).await {
+                Ok(true) => {
+                    remote.remove(&url);
+                    syncer.report.deleted_remote += 1;
+                }
+                // 서버에서 그 사이 수정됨 → 지우지 않고 아래에서 새 일정으로 다시 가져옴
+                Ok(false) => {}
+                Err(e) => {
+                    syncer.report.errors.push(e);
+                    continue;
+                }
+            }
+        }
+        db::remove_caldav_tombstone(&stored_url).map_err(|e| e.to_string())?;
+    }
+
+    let states = db::get_schedule_sync_states().map_err(|e| e.to_string())?;
+    let pending_conflicts: HashSet<i64> = db::get_caldav_conflicts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| c.schedule_id)
+        .collect();
+    let mut linked: HashSet<String> = HashSet::new();
+    let mut by_uid: HashMap<String, db::ScheduleSyncState> = HashMap::new();
+
+    // 2. 서버와 연결된 로컬 일정
+    for state in states {
+        let url = match state.external_id.as_deref() {
+            Some(url) => resolve_href(&syncer.config, url).unwrap_or_else(|| url.to_string()),
+            None => {
+                if let Some(uid) = state.ical_uid.clone().filter(|u| !u.is_empty()) {
+                    by_uid.insert(uid, state.clone());
+                }
+                continue;
+            }
+        };
+        linked.insert(url.clone());
+
+        // 사용자 선택을 기다리는 충돌은 해결될 때까지 건드리지 않음
+        if pending_conflicts.contains(&state.schedule.id) {
+            syncer.report.conflicts += 1;
+            continue;
+        }
+
+        if let Err(e) = syncer.sync_linked(&state, &url, &remote).await {
+            syncer.report.errors.push(format!("{}: {}", state.schedule.title, e));
+        }
+    }
+
+    // 3. 서버에만 있는 일정 가져오기 (UID가 같은 로컬 일정이 있으면 연결)
+    let new_remote: Vec<(String, Option<String>)> = remote
+        .iter()
+        .filter(|(url, _)| !linked.contains(*url))
+        .map(|(url, etag)| (url.clone(), etag.clone()))
+        .collect();
+    for (url, etag) in new_remote {
+        let result = match fetch_resource(&syncer.client, &syncer.config, &url).await {
+            Ok(Some((text, fetched_etag))) => {
+                let existing = first_event(&text)
+                    .and_then(|e| by_uid.remove(&e.uid))
+                    .map(|state| state.schedule);
+                syncer.pull(existing, &url, &text, fetched_etag.as_deref().or(etag.as_deref()))
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            syncer.report.errors.push(e);
+        }
+    }
+
+    // 4. 서버에 없는 로컬 일정 올리기 (시작 시각이 없는 일정은 VEVENT로 만들 수 없어 제외)
+    for state in db::get_schedule_sync_states().map_err(|e| e.to_string())? {
+        if state.external_id.is_some() || state.schedule.start_time.is_none() {
+            continue;
+        }
+        match syncer.push(&state, None, None).await {
+            Ok(PutOutcome::Saved) => {}
+            Ok(PutOutcome::PreconditionFailed) => syncer
+                .report
+                .errors
+                .push(format!("{}: 서버에 같은 UID의 일정이 이미 있습니다", state.schedule.title)),
+            Err(e) => syncer.report.errors.push(format!("{}: {}", state.schedule.title, e)),
+        }
+    }
+
+    Ok(syncer.report)
+}
+
+/// 충돌 해결 (keep_local이면 다음 동기화 때 로컬 내용을 서버에 올리고, 아니면 서버 내용을 바로 반영)
+pub fn resolve_conflict(conflict_id: i64, keep_local: bool) -> Result<(), String> {
+    let conflict = db::get_caldav_conflicts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| c.id == conflict_id)
+        .ok_or("충돌 기록을 찾을 수 없습니다")?;
+    let schedule = db::get_schedule(conflict.schedule_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("일정을 찾을 수 없습니다")?;
+
+    if keep_local {
+        // ETag만 서버 최신으로 맞추고 해시는 그대로 두면 "로컬만 변경"으로 처리되어 If-Match로 올라감
+        let sync_hash = db::get_schedule_sync_states()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|s| s.schedule.id == schedule.id)
+            .and_then(|s| s.sync_hash);
+        db::set_schedule_sync(schedule.id, Some(&conflict.external_id), conflict.remote_etag.as_deref(), sync_hash.as_deref())
+            .map_err(|e| e.to_string())?;
+    } else {
+        let event = first_event(&conflict.remote_ics).ok_or("서버 일정 내용을 해석할 수 없습니다")?;
+        let schedule = apply_remote_event(Some(schedule), event)?;
+        db::set_schedule_sync(
+            schedule.id,
+            Some(&conflict.external_id),
+            conflict.remote_etag.as_deref(),
+            Some(&schedule_hash(&schedule)),
+        ).map_err(|e| e.to_string())?;
+    }
+
+    db::delete_caldav_conflict(conflict_id).map_err(|e| e.to_string())
+}
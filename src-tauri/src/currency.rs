@@ -0,0 +1,79 @@
+// ===== 통화 (ISO 4217) =====
+//
+// 거래 금액은 통화의 최소 단위 정수로 저장 (KRW 5000원 = 5000, USD $12.50 = 1250)
+// 환율은 "1 통화 = rate 기준 통화" (주 단위 기준, 예: USD → KRW 1380.5)
+
+/// 통화를 지정하지 않았을 때의 기본 통화
+pub const DEFAULT_CURRENCY: &str = "KRW";
+
+/// 소수점이 없는 통화
+const ZERO_DECIMAL: &[&str] = &[
+    "KRW", "JPY", "VND", "CLP", "ISK", "PYG", "UGX", "XAF", "XOF", "XPF", "KMF", "GNF", "RWF", "VUV", "BIF", "DJF",
+];
+
+/// 소수점 세 자리 통화
+const THREE_DECIMAL: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// 통화 기호/이름 → 코드
+const ALIASES: &[(&str, &str)] = &[
+    ("₩", "KRW"), ("원", "KRW"), ("WON", "KRW"),
+    ("$", "USD"), ("US$", "USD"), ("달러", "USD"), ("불", "USD"),
+    ("€", "EUR"), ("유로", "EUR"),
+    ("¥", "JPY"), ("円", "JPY"), ("엔", "JPY"),
+    ("元", "CNY"), ("위안", "CNY"), ("RMB", "CNY"),
+    ("£", "GBP"), ("파운드", "GBP"),
+];
+
+/// 최소 단위 자릿수 (KRW 0, USD 2, KWD 3, 그 외 2)
+pub fn exponent(code: &str) -> u32 {
+    if ZERO_DECIMAL.contains(&code) {
+        0
+    } else if THREE_DECIMAL.contains(&code) {
+        3
+    } else {
+        2
+    }
+}
+
+/// 통화 코드 정규화 ("usd", "$", "달러" → "USD")
+pub fn normalize(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let upper = value.to_uppercase();
+    if let Some((_, code)) = ALIASES.iter().find(|(alias, _)| *alias == upper) {
+        return Ok(code.to_string());
+    }
+    if upper.len() == 3 && upper.chars().all(|c| c.is_ascii_uppercase()) {
+        return Ok(upper);
+    }
+    Err(format!("알 수 없는 통화: {}", value))
+}
+
+/// 주 단위 금액 → 최소 단위 정수 (12.5 USD → 1250)
+pub fn to_minor(amount: f64, code: &str) -> i64 {
+    (amount * 10f64.powi(exponent(code) as i32)).round() as i64
+}
+
+/// 최소 단위 금액 변환
+/// rate_from, rate_to: 각 통화의 기준 통화 환율 (기준 통화 자신은 1.0)
+pub fn convert(amount: i64, from: &str, to: &str, rate_from: f64, rate_to: f64) -> i64 {
+    if from == to {
+        return amount;
+    }
+    let major = amount as f64 / 10f64.powi(exponent(from) as i32);
+    to_minor(major * rate_from / rate_to, to)
+}
+
+/// SQL에서 최소 단위를 기준 통화의 최소 단위로 맞추는 배율 (환율과 곱해서 사용)
+pub fn scale_sql(column: &str, base: &str) -> String {
+    let factor = |exp: u32| 10f64.powi(exponent(base) as i32 - exp as i32);
+    let list = |codes: &[&str]| codes.iter().map(|c| format!("'{}'", c)).collect::<Vec<_>>().join(", ");
+    format!(
+        "(CASE WHEN {col} IN ({zero}) THEN {f0:?} WHEN {col} IN ({three}) THEN {f3:?} ELSE {f2:?} END)",
+        col = column,
+        zero = list(ZERO_DECIMAL),
+        three = list(THREE_DECIMAL),
+        f0 = factor(0),
+        f3 = factor(3),
+        f2 = factor(2),
+    )
+}
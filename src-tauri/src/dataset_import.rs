@@ -0,0 +1,560 @@
+// ===== 데이터셋 가져오기 (XLSX / XLS / ODS / CSV / TSV) =====
+//
+// 형식은 확장자가 아니라 파일 앞부분(매직 바이트)으로 판단 (.xls로 저장된 CSV/HTML 등이 흔함)
+// - 엑셀/ODS: calamine의 형식별 리더로 시트를 읽음
+// - CSV/TSV: 앞부분으로 구분자(, ; 탭 |)와 인코딩(UTF-8, 아니면 CP949/EUC-KR)을 감지하고
+//   전체를 메모리에 올리지 않고 한 줄씩 읽어 BATCH_ROWS개씩 dataset_rows에 저장
+// 헤더는 맨 윗줄로 고정하지 않고 시트마다 위치(제목 줄 아래, 여러 줄 병합 헤더)를 추측하고
+// preview로 시트/헤더/컬럼 수를 보여준 뒤 사용자가 고친 설정(ImportOptions)으로 가져옴
+
+use crate::{bank_import, db};
+use calamine::{Data, Ods, Reader, Sheets, Xls, Xlsx};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// 형식/구분자/인코딩 감지에 쓰는 앞부분 크기
+pub const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// 한 번에 저장하는 행 수
+const BATCH_ROWS: usize = 5_000;
+
+/// 헤더를 찾을 때 보는 앞쪽 행 수 (미리보기 표본 행 수도 같음)
+const HEADER_SCAN_ROWS: usize = 20;
+
+/// 병합 헤더로 인정하는 최대 줄 수
+const MAX_HEADER_ROWS: usize = 3;
+
+/// 여러 시트를 합칠 때 시트 이름을 넣는 열
+const SHEET_COLUMN: &str = "sheet";
+
+/// 가져올 파일 형식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatasetFormat {
+    Xlsx,
+    Xls,
+    Ods,
+    Csv,
+}
+
+/// 파일 형식 감지 (ZIP이면 ODS/XLSX, OLE2면 XLS, 그 외는 텍스트로 보고 CSV)
+pub fn detect_format(head: &[u8]) -> DatasetFormat {
+    if head.starts_with(b"PK\x03\x04") {
+        // ODS는 압축하지 않은 mimetype 항목이 맨 앞에 있음
+        let prefix = &head[..head.len().min(256)];
+        if prefix.windows(12).any(|w| w == b"opendocument") {
+            return DatasetFormat::Ods;
+        }
+        return DatasetFormat::Xlsx;
+    }
+    if head.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return DatasetFormat::Xls;
+    }
+    DatasetFormat::Csv
+}
+
+/// 데이터셋 이름 (파일명에서 확장자 제거)
+pub fn dataset_name(file_name: &str) -> String {
+    Path::new(file_name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| file_name.to_string())
+}
+
+/// 셀 값 → 문자열 (소수점 없는 실수는 정수로)
+pub fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Float(f) => {
+            if f.fract() == 0.0 {
+                format!("{}", *f as i64)
+            } else {
+                format!("{}", f)
+            }
+        }
+        Data::Int(i) => format!("{}", i),
+        Data::Bool(b) => format!("{}", b),
+        Data::DateTime(dt) => excel_datetime(dt),
+        Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        Data::Error(e) => format!("Error: {:?}", e),
+    }
+}
+
+/// 엑셀 날짜 → "YYYY-MM-DD" (시간이 있으면 "YYYY-MM-DD HH:MM:SS"), 기간 값은 숫자 그대로
+fn excel_datetime(dt: &calamine::ExcelDateTime) -> String {
+    match dt.as_datetime().filter(|_| dt.is_datetime()) {
+        Some(dt) if dt.time() == chrono::NaiveTime::MIN => dt.format("%Y-%m-%d").to_string(),
+        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => format!("{}", dt.as_f64()),
+    }
+}
+
+/// 문자열로 바꾼 행들
+pub type Rows = Vec<Vec<String>>;
+
+/// 엑셀/ODS 파일의 모든 시트 읽기 → (시트 이름, 행들)
+pub fn read_workbook<RS: Read + Seek>(format: DatasetFormat, reader: RS) -> Result<Vec<(String, Rows)>, String> {
+    let mut workbook = match format {
+        DatasetFormat::Xlsx => Sheets::Xlsx(Xlsx::new(reader).map_err(|e| format!("엑셀 파일 읽기 실패: {}", e))?),
+        DatasetFormat::Xls => Sheets::Xls(Xls::new(reader).map_err(|e| format!("엑셀(XLS) 파일 읽기 실패: {}", e))?),
+        DatasetFormat::Ods => Sheets::Ods(Ods::new(reader).map_err(|e| format!("ODS 파일 읽기 실패: {}", e))?),
+        DatasetFormat::Csv => return Err("CSV는 시트가 없습니다".to_string()),
+    };
+
+    let sheet_names = workbook.sheet_names().to_vec();
+    if sheet_names.is_empty() {
+        return Err("엑셀 파일에 시트가 없습니다".to_string());
+    }
+
+    let mut sheets = Vec::new();
+    for name in sheet_names {
+        let Ok(range) = workbook.worksheet_range(&name) else {
+            continue;
+        };
+        let rows = range
+            .rows()
+            .map(|row| row.iter().map(cell_to_string).collect())
+            .collect();
+        sheets.push((name, rows));
+    }
+    Ok(sheets)
+}
+
+/// CSV 구분자와 인코딩
+#[derive(Debug, Clone, Copy)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    pub euc_kr: bool,
+}
+
+/// 앞부분(SAMPLE_BYTES)으로 구분자/인코딩 감지 (.tsv는 항상 탭)
+/// 앞부분이 UTF-8이어도 뒤쪽 셀이 UTF-8이 아니면 csv_rows에서 그 셀만 EUC-KR로 읽음
+pub fn detect_csv(file_name: &str, sample: &[u8]) -> CsvFormat {
+    // 앞부분만 잘라 봤으므로 끝에서 글자가 잘린 것(error_len = None)은 UTF-8로 봄
+    let euc_kr = std::str::from_utf8(sample).is_err_and(|e| e.error_len().is_some());
+    let delimiter = if file_name.to_lowercase().ends_with(".tsv") {
+        b'\t'
+    } else {
+        // 맨 위 제목 줄에는 구분자가 없을 수 있어 앞쪽 여러 줄 중 가장 많이 나오는 것 (같으면 쉼표)
+        let text = bank_import::decode(sample, Some(if euc_kr { "euc-kr" } else { "utf-8" }));
+        let lines: Vec<&str> = text.lines().take(HEADER_SCAN_ROWS).collect();
+        [b'|', b'\t', b';', b',']
+            .into_iter()
+            .max_by_key(|d| lines.iter().map(|l| l.matches(*d as char).count()).max().unwrap_or(0))
+            .unwrap_or(b',')
+    };
+    CsvFormat { delimiter, euc_kr }
+}
+
+/// CSV 행을 한 줄씩 읽기
+/// CP949의 두 번째 바이트에는 구분자/따옴표가 나오지 않으므로 바이트 단위로 나눈 뒤 셀마다 디코딩
+/// (UTF-8로 감지했어도 UTF-8이 아닌 셀은 EUC-KR로)
+pub fn csv_rows<R: Read>(reader: R, format: CsvFormat) -> impl Iterator<Item = Result<Vec<String>, String>> {
+    let mut first = true;
+    csv::ReaderBuilder::new()
+        .delimiter(format.delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader)
+        .into_byte_records()
+        .map(move |record| {
+            let record = record.map_err(|e| format!("CSV 읽기 실패: {}", e))?;
+            let mut row: Vec<String> = record
+                .iter()
+                .map(|field| {
+                    match std::str::from_utf8(field) {
+                        Ok(text) if !format.euc_kr => text.to_string(),
+                        _ => encoding_rs::EUC_KR.decode(field).0.into_owned(),
+                    }
+                })
+                .collect();
+            if std::mem::take(&mut first) {
+                if let Some(cell) = row.first_mut() {
+                    *cell = cell.trim_start_matches('\u{feff}').to_string();
+                }
+            }
+            Ok(row)
+        })
+}
+
+/// 헤더 위치 (header_row: 헤더가 시작하는 행, 0부터 / header_rows: 헤더 줄 수, 병합 헤더면 2 이상)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderLayout {
+    pub header_row: usize,
+    pub header_rows: usize,
+}
+
+fn is_blank(cell: &str) -> bool {
+    cell.trim().is_empty()
+}
+
+fn is_number(cell: &str) -> bool {
+    cell.trim().replace(',', "").parse::<f64>().is_ok()
+}
+
+fn cell(row: &[String], i: usize) -> &str {
+    row.get(i).map(String::as_str).unwrap_or("")
+}
+
+fn filled(row: &[String]) -> usize {
+    row.iter().filter(|c| !is_blank(c)).count()
+}
+
+/// 헤더 위치 추측 (앞쪽 HEADER_SCAN_ROWS행만 봄)
+/// - 헤더: 가장 넓은 행의 2/3 이상이 채워져 있고 대부분 숫자가 아닌 첫 행 (위의 제목/설명 줄은 건너뜀)
+/// - 병합 헤더: 바로 윗줄이 빈 칸(가로 병합)을 가진 글자 줄이고 헤더가 그 빈 칸을 채우면 윗줄까지 헤더
+pub fn detect_header(rows: &[Vec<String>]) -> HeaderLayout {
+    let rows = &rows[..rows.len().min(HEADER_SCAN_ROWS)];
+    let width = rows.iter().map(|r| filled(r)).max().unwrap_or(0);
+    let mut header_row = rows
+        .iter()
+        .position(|row| {
+            let count = filled(row);
+            let text = row.iter().filter(|c| !is_blank(c) && !is_number(c)).count();
+            count > 0 && count * 3 >= width * 2 && text * 2 > count
+        })
+        .unwrap_or(0);
+
+    let mut header_rows = 1;
+    while header_rows < MAX_HEADER_ROWS && header_row > 0 {
+        let upper = &rows[header_row - 1];
+        let lower = &rows[header_row];
+        let columns = upper.len().max(lower.len());
+        let fills_gap = (0..columns).any(|i| is_blank(cell(upper, i)) && !is_blank(cell(lower, i)));
+        let all_text = filled(upper) >= 2 && upper.iter().all(|c| is_blank(c) || !is_number(c));
+        // 병합 헤더의 아랫줄은 보통 윗줄이 세로로 병합된 칸이 비어 있거나 같은 이름이 반복됨
+        let under_merged = (0..columns).any(|i| !is_blank(cell(upper, i)) && is_blank(cell(lower, i)));
+        let repeated = lower
+            .iter()
+            .filter(|c| !is_blank(c))
+            .any(|c| lower.iter().filter(|o| o.trim() == c.trim()).count() > 1);
+        if !(fills_gap && all_text && (under_merged || repeated)) {
+            break;
+        }
+        header_row -= 1;
+        header_rows += 1;
+    }
+    HeaderLayout { header_row, header_rows }
+}
+
+/// 헤더 행들 → 컬럼명
+/// 여러 줄이면 위아래 이름을 이어 붙임 ("1분기 매출"), fill_merged면 윗줄의 빈 칸을 왼쪽 값으로 채움 (가로 병합 셀)
+/// 빈 이름은 ColumnN, 같은 이름은 이름_2, 이름_3...
+pub fn header_names(header: &[Vec<String>], fill_merged: bool) -> Vec<String> {
+    let width = header.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut lines: Vec<Vec<String>> = header
+        .iter()
+        .map(|row| (0..width).map(|i| cell(row, i).trim().to_string()).collect())
+        .collect();
+    if fill_merged {
+        let upper = lines.len().saturating_sub(1);
+        for line in lines.iter_mut().take(upper) {
+            for i in 1..width {
+                if line[i].is_empty() {
+                    line[i] = line[i - 1].clone();
+                }
+            }
+        }
+    }
+
+    let mut columns: Vec<String> = Vec::with_capacity(width);
+    for i in 0..width {
+        let mut parts: Vec<&str> = Vec::new();
+        for line in &lines {
+            if !line[i].is_empty() && !parts.contains(&line[i].as_str()) {
+                parts.push(&line[i]);
+            }
+        }
+        let name = if parts.is_empty() { format!("Column{}", i + 1) } else { parts.join(" ") };
+        columns.push(unique_name(&columns, name));
+    }
+    columns
+}
+
+fn unique_name(existing: &[String], name: String) -> String {
+    if !existing.contains(&name) {
+        return name;
+    }
+    (2..)
+        .map(|n| format!("{}_{}", name, n))
+        .find(|candidate| !existing.contains(candidate))
+        .unwrap_or(name)
+}
+
+/// 헤더 앞의 행을 버리고 헤더 행을 읽어 컬럼명 반환 (rows는 데이터 행부터 남음)
+fn take_header(
+    rows: &mut impl Iterator<Item = Result<Vec<String>, String>>,
+    layout: HeaderLayout,
+    fill_merged: bool,
+) -> Result<Vec<String>, String> {
+    let mut header = Vec::with_capacity(layout.header_rows);
+    for row in rows.by_ref().skip(layout.header_row).take(layout.header_rows.max(1)) {
+        header.push(row?);
+    }
+    let columns = header_names(&header, fill_merged);
+    if columns.is_empty() {
+        return Err("컬럼 정보를 찾을 수 없습니다".to_string());
+    }
+    Ok(columns)
+}
+
+/// 미리보기의 시트 하나 (CSV는 시트 하나로 취급)
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetPreview {
+    pub name: String,
+    pub header: HeaderLayout,        // 감지한 헤더 위치
+    pub columns: Vec<String>,        // 감지한 헤더로 만든 컬럼명
+    pub column_count: usize,
+    pub row_count: i64,              // 헤더 아래 데이터 행 수 (빈 행 제외)
+    pub sample_rows: Rows,           // 파일 앞부분 그대로 (헤더 위치를 고를 수 있도록)
+}
+
+/// 가져오기 미리보기
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub file_name: String,
+    pub format: String,              // xlsx, xls, ods, csv
+    pub sheets: Vec<SheetPreview>,
+}
+
+/// 시트별 가져오기 설정 (header가 없으면 감지한 위치)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheetImportOptions {
+    pub name: String,
+    #[serde(default)]
+    pub header: Option<HeaderLayout>,
+}
+
+/// 가져오기 설정
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub name: Option<String>,                // 데이터셋 이름 (없으면 파일명, 시트별로 나누면 "이름 - 시트")
+    #[serde(default)]
+    pub sheets: Vec<SheetImportOptions>,     // 가져올 시트 (비어 있으면 전부)
+    #[serde(default)]
+    pub union: bool,                         // 여러 시트를 sheet 열을 붙여 하나의 데이터셋으로
+    #[serde(default = "default_true")]
+    pub fill_merged: bool,                   // 병합 헤더의 빈 칸을 왼쪽 값으로 채움
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self { name: None, sheets: Vec::new(), union: false, fill_merged: true }
+    }
+}
+
+/// 만들어진 데이터셋
+#[derive(Debug, Clone)]
+pub struct ImportedDataset {
+    pub dataset_id: i64,
+    pub name: String,
+    pub columns: Vec<String>,
+    pub row_count: i64,
+}
+
+fn format_label(format: DatasetFormat) -> &'static str {
+    match format {
+        DatasetFormat::Xlsx => "xlsx",
+        DatasetFormat::Xls => "xls",
+        DatasetFormat::Ods => "ods",
+        DatasetFormat::Csv => "csv",
+    }
+}
+
+fn sheet_preview(name: String, mut rows: impl Iterator<Item = Result<Vec<String>, String>>) -> Result<SheetPreview, String> {
+    let mut sample = Vec::new();
+    for row in rows.by_ref().take(HEADER_SCAN_ROWS) {
+        sample.push(row?);
+    }
+    let header = detect_header(&sample);
+    let columns = header_names(
+        &sample[header.header_row.min(sample.len())..(header.header_row + header.header_rows).min(sample.len())],
+        true,
+    );
+    let sampled_data = sample
+        .iter()
+        .skip(header.header_row + header.header_rows)
+        .filter(|r| filled(r) > 0)
+        .count() as i64;
+    let rest = rows.filter(|r| r.as_ref().map(|r| filled(r) > 0).unwrap_or(true)).count() as i64;
+    Ok(SheetPreview {
+        name,
+        header,
+        column_count: columns.len(),
+        columns,
+        row_count: sampled_data + rest,
+        sample_rows: sample,
+    })
+}
+
+/// 파일 미리보기 (시트 목록, 감지한 헤더, 컬럼 수, 행 수)
+pub fn preview<R: Read + Seek>(file_name: &str, head: &[u8], reader: R) -> Result<ImportPreview, String> {
+    let format = detect_format(head);
+    let sheets = match format {
+        DatasetFormat::Csv => {
+            let csv = detect_csv(file_name, head);
+            vec![sheet_preview(dataset_name(file_name), csv_rows(reader, csv))?]
+        }
+        format => read_workbook(format, reader)?
+            .into_iter()
+            .map(|(name, rows)| sheet_preview(name, rows.into_iter().map(Ok)))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(ImportPreview { file_name: file_name.to_string(), format: format_label(format).to_string(), sheets })
+}
+
+/// 파일 가져오기
+/// - CSV: 데이터셋 하나 (한 줄씩 읽어 저장)
+/// - 엑셀/ODS: 고른 시트마다 데이터셋 하나, union이면 컬럼 이름으로 맞춰 sheet 열을 붙인 데이터셋 하나
+///
+/// progress: (데이터셋 이름, 지금까지 저장한 행 수)
+pub fn import<R: Read + Seek>(
+    file_name: &str,
+    head: &[u8],
+    reader: R,
+    options: &ImportOptions,
+    mut progress: impl FnMut(&str, i64),
+) -> Result<Vec<ImportedDataset>, String> {
+    let base_name = options
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| dataset_name(file_name));
+
+    let format = detect_format(head);
+    if format == DatasetFormat::Csv {
+        let csv = detect_csv(file_name, head);
+        let mut rows = csv_rows(reader, csv);
+        let mut sample = Vec::new();
+        for row in rows.by_ref().take(HEADER_SCAN_ROWS) {
+            sample.push(row?);
+        }
+        let layout = options
+            .sheets
+            .first()
+            .and_then(|s| s.header)
+            .unwrap_or_else(|| detect_header(&sample));
+        let mut rows = sample.into_iter().map(Ok).chain(rows);
+        let columns = take_header(&mut rows, layout, options.fill_merged)?;
+        let dataset = save_rows(&base_name, columns, rows, |n| progress(&base_name, n))?;
+        return Ok(vec![dataset]);
+    }
+
+    let mut sheets = read_workbook(format, reader)?;
+    if !options.sheets.is_empty() {
+        sheets.retain(|(name, _)| options.sheets.iter().any(|s| &s.name == name));
+    }
+    // 시트마다 (시트 이름, 컬럼명, 데이터 행)
+    let mut tables = Vec::new();
+    for (name, rows) in sheets {
+        if rows.iter().all(|r| filled(r) == 0) {
+            continue;
+        }
+        let layout = options
+            .sheets
+            .iter()
+            .find(|s| s.name == name)
+            .and_then(|s| s.header)
+            .unwrap_or_else(|| detect_header(&rows));
+        let mut rows = rows.into_iter().map(Ok);
+        let columns = take_header(&mut rows, layout, options.fill_merged)?;
+        let data: Rows = rows.filter_map(Result::ok).filter(|r| filled(r) > 0).collect();
+        tables.push((name, columns, data));
+    }
+    if tables.is_empty() {
+        return Err("가져올 데이터가 있는 시트가 없습니다".to_string());
+    }
+
+    if options.union && tables.len() > 1 {
+        let mut columns = vec![SHEET_COLUMN.to_string()];
+        let mut mappings = Vec::new();
+        for (_, sheet_columns, _) in &tables {
+            let mapping: Vec<usize> = sheet_columns
+                .iter()
+                .map(|c| {
+                    // 시트 자체의 sheet 열은 시트 이름 열과 겹치지 않게 따로
+                    let key = if c == SHEET_COLUMN { format!("{}_2", c) } else { c.clone() };
+                    columns.iter().position(|u| *u == key).unwrap_or_else(|| {
+                        columns.push(key);
+                        columns.len() - 1
+                    })
+                })
+                .collect();
+            mappings.push(mapping);
+        }
+        let width = columns.len();
+        let rows = tables.into_iter().zip(mappings).flat_map(|((sheet, _, data), mapping)| {
+            data.into_iter().map(move |row| {
+                let mut out = vec![String::new(); width];
+                out[0] = sheet.clone();
+                for (value, &index) in row.into_iter().zip(&mapping) {
+                    out[index] = value;
+                }
+                Ok(out)
+            })
+        });
+        let dataset = save_rows(&base_name, columns, rows, |n| progress(&base_name, n))?;
+        return Ok(vec![dataset]);
+    }
+
+    let separate = tables.len() > 1;
+    let mut datasets = Vec::new();
+    for (sheet, columns, data) in tables {
+        let name = if separate { format!("{} - {}", base_name, sheet) } else { base_name.clone() };
+        datasets.push(save_rows(&name, columns, data.into_iter().map(Ok), |n| progress(&name, n))?);
+    }
+    Ok(datasets)
+}
+
+/// 데이터셋을 만들고 행을 나눠 저장한 뒤 컬럼 타입 추측 (progress: 지금까지 저장한 행 수)
+/// 중간에 실패하면 만들던 데이터셋을 지움
+fn save_rows(
+    name: &str,
+    columns: Vec<String>,
+    rows: impl Iterator<Item = Result<Vec<String>, String>>,
+    mut progress: impl FnMut(i64),
+) -> Result<ImportedDataset, String> {
+    let dataset_id = db::save_dataset(name, "", &columns).map_err(|e| format!("데이터셋 저장 실패: {}", e))?;
+    let rows = rows.filter(|r| r.as_ref().map(|r| filled(r) > 0).unwrap_or(true));
+    let saved = save_batches(dataset_id, rows, &mut progress)
+        .and_then(|row_count| db::refresh_dataset_types(dataset_id).map(|_| row_count).map_err(|e| e.to_string()));
+    match saved {
+        Ok(row_count) => Ok(ImportedDataset { dataset_id, name: name.to_string(), columns, row_count }),
+        Err(e) => {
+            db::delete_dataset(dataset_id).ok();
+            Err(e)
+        }
+    }
+}
+
+fn save_batches(
+    dataset_id: i64,
+    rows: impl Iterator<Item = Result<Vec<String>, String>>,
+    progress: &mut impl FnMut(i64),
+) -> Result<i64, String> {
+    let mut batch = Vec::with_capacity(BATCH_ROWS);
+    let mut saved = 0i64;
+    for row in rows {
+        batch.push(row?);
+        if batch.len() == BATCH_ROWS {
+            db::append_dataset_rows(dataset_id, saved, &batch).map_err(|e| format!("데이터 저장 실패: {}", e))?;
+            saved += batch.len() as i64;
+            batch.clear();
+            progress(saved);
+        }
+    }
+    if !batch.is_empty() {
+        db::append_dataset_rows(dataset_id, saved, &batch).map_err(|e| format!("데이터 저장 실패: {}", e))?;
+        saved += batch.len() as i64;
+        progress(saved);
+    }
+    db::update_dataset_row_count(dataset_id, saved).map_err(|e| e.to_string())?;
+    Ok(saved)
+}
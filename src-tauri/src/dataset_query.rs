@@ -0,0 +1,379 @@
+// ===== 데이터셋 질의 (필터 / 정렬 / 그룹 / 집계 / 페이지) =====
+//
+// AI 없이 SQLite에서 바로 실행하는 구조화된 질의
+// 값은 가져올 때 타입에 맞게 변환해 둔 dataset_rows.typed_json을 json_extract로 꺼내 비교하므로
+// 숫자/날짜 비교와 합계가 정확하고, 컬럼 이름과 값은 모두 바인딩 파라미터로 넘겨 SQL에 직접 들어가지 않음
+
+use crate::dataset_schema::{self, CellValue, ColumnType};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+/// 한 번에 돌려주는 최대 행 수
+pub const MAX_LIMIT: i64 = 10_000;
+const DEFAULT_LIMIT: i64 = 100;
+
+/// 필터 연산자
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    NotContains,
+    StartsWith,
+    EndsWith,
+    In,          // value: 배열
+    Between,     // value: [시작, 끝] (양 끝 포함)
+    IsNull,
+    NotNull,
+}
+
+/// 컬럼 필터 (여러 개면 모두 만족하는 행)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryFilter {
+    pub column: String,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// 정렬 키 (그룹 질의에서는 그룹 컬럼이나 집계 이름)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortKey {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// 집계 함수
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateFn {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// 집계 (count는 column이 없으면 행 수)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub function: AggregateFn,
+    #[serde(default)]
+    pub column: Option<String>,
+    #[serde(default)]
+    pub alias: Option<String>,     // 결과 컬럼 이름 (없으면 "sum(매출)" 형태)
+}
+
+/// 데이터셋 질의
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetQuery {
+    #[serde(default)]
+    pub select: Vec<String>,       // 보여줄 컬럼 (비어 있으면 전부, 그룹 질의에서는 무시)
+    #[serde(default)]
+    pub filters: Vec<QueryFilter>,
+    #[serde(default)]
+    pub group_by: Vec<String>,
+    #[serde(default)]
+    pub aggregates: Vec<Aggregate>,
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default)]
+    pub limit: Option<i64>,        // 없으면 100, 최대 MAX_LIMIT
+}
+
+/// 질의 결과 (total: 페이지와 상관없이 조건에 맞는 행/그룹 수)
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetQueryResult {
+    pub columns: Vec<String>,
+    pub column_types: Vec<ColumnType>,
+    pub rows: Vec<Vec<CellValue>>,
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+/// 실행할 SQL (db::run_dataset_query에서 실행)
+#[derive(Debug, Clone)]
+pub struct BuiltQuery {
+    pub sql: String,               // 페이지 적용
+    pub count_sql: String,         // 전체 개수
+    pub params: Vec<Value>,        // 두 SQL 공통 (?1 = dataset_id)
+    pub columns: Vec<String>,
+    pub column_types: Vec<ColumnType>,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+/// 결과 컬럼 하나 (SQL 식, 이름, 타입)
+struct Output {
+    expr: String,
+    name: String,
+    column_type: ColumnType,
+}
+
+fn column_expr(index: usize) -> String {
+    format!("json_extract(r.typed_json, '$[{}]')", index)
+}
+
+fn aggregate_name(function: AggregateFn) -> &'static str {
+    match function {
+        AggregateFn::Count => "count",
+        AggregateFn::Sum => "sum",
+        AggregateFn::Avg => "avg",
+        AggregateFn::Min => "min",
+        AggregateFn::Max => "max",
+    }
+}
+
+fn is_numeric(column_type: ColumnType) -> bool {
+    matches!(column_type, ColumnType::Integer | ColumnType::Float | ColumnType::Currency)
+}
+
+/// 필터 값 → 바인딩 값 (문자열은 컬럼 타입으로 변환해 "2024/1/5" 같은 입력도 같은 형식으로 비교)
+/// 숫자 컬럼에 숫자로 읽을 수 없는 값이 오면 오류 (문자열로 비교하면 모든 숫자보다 커져 결과가 틀어짐)
+fn bind_value(value: &serde_json::Value, column_type: ColumnType) -> Result<Value, String> {
+    let value = match value {
+        serde_json::Value::Null => Value::Null,
+        // 텍스트 컬럼은 json_extract가 TEXT를 돌려주므로 숫자/불린도 문자열로 비교 (INTEGER 5와 TEXT "5"는 같지 않음)
+        _ if column_type == ColumnType::Text => Value::Text(value_text(value)),
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map(Value::Real).unwrap_or(Value::Null),
+        },
+        serde_json::Value::String(s) => match dataset_schema::parse_cell(s, column_type) {
+            CellValue::Integer(i) => Value::Integer(i),
+            CellValue::Float(f) => Value::Real(f),
+            CellValue::Boolean(b) => Value::Integer(b as i64),
+            CellValue::Date(d) => Value::Text(d),
+            CellValue::Text(t) => Value::Text(t),
+            CellValue::Null if is_numeric(column_type) => match dataset_schema::parse_float(s) {
+                Some(f) => Value::Real(f),
+                None => return Err(format!("숫자 컬럼에는 숫자 값으로만 필터할 수 있습니다: {}", s)),
+            },
+            CellValue::Null => Value::Text(s.clone()),
+        },
+        other => Value::Text(other.to_string()),
+    };
+    Ok(value)
+}
+
+/// 바인딩 값 추가 → 자리 표시자 (?1은 dataset_id라 ?2부터)
+fn bind(params: &mut Vec<Value>, value: Value) -> String {
+    params.push(value);
+    format!("?{}", params.len() + 1)
+}
+
+/// LIKE 패턴용 이스케이프 (\ 를 ESCAPE 문자로)
+fn like_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// 질의 → SQL (컬럼 이름이 없으면 오류)
+pub fn build(columns: &[String], types: &[ColumnType], query: &DatasetQuery) -> Result<BuiltQuery, String> {
+    let find = |name: &str| -> Result<usize, String> {
+        columns
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| format!("컬럼을 찾을 수 없습니다: {}", name))
+    };
+    let type_of = |index: usize| types.get(index).copied().unwrap_or_default();
+
+    let mut params: Vec<Value> = Vec::new();
+
+    // WHERE
+    let mut conditions = vec!["r.dataset_id = ?1".to_string()];
+    for filter in &query.filters {
+        let index = find(&filter.column)?;
+        let column_type = type_of(index);
+        let expr = column_expr(index);
+        let condition = match filter.op {
+            FilterOp::IsNull => format!("{} IS NULL", expr),
+            FilterOp::NotNull => format!("{} IS NOT NULL", expr),
+            FilterOp::Eq | FilterOp::Ne | FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte => {
+                let op = match filter.op {
+                    FilterOp::Eq => "=",
+                    FilterOp::Ne => "<>",
+                    FilterOp::Gt => ">",
+                    FilterOp::Gte => ">=",
+                    FilterOp::Lt => "<",
+                    _ => "<=",
+                };
+                let placeholder = bind(&mut params, bind_value(&filter.value, column_type)?);
+                if filter.op == FilterOp::Ne {
+                    // 값이 없는 행도 "같지 않음"에 포함
+                    format!("({} IS NULL OR {} {} {})", expr, expr, op, placeholder)
+                } else {
+                    format!("{} {} {}", expr, op, placeholder)
+                }
+            }
+            FilterOp::Contains | FilterOp::NotContains | FilterOp::StartsWith | FilterOp::EndsWith => {
+                let text = like_escape(&value_text(&filter.value));
+                let pattern = match filter.op {
+                    FilterOp::StartsWith => format!("{}%", text),
+                    FilterOp::EndsWith => format!("%{}", text),
+                    _ => format!("%{}%", text),
+                };
+                let placeholder = bind(&mut params, Value::Text(pattern));
+                if filter.op == FilterOp::NotContains {
+                    format!("({} IS NULL OR {} NOT LIKE {} ESCAPE '\\')", expr, expr, placeholder)
+                } else {
+                    format!("{} LIKE {} ESCAPE '\\'", expr, placeholder)
+                }
+            }
+            FilterOp::In => {
+                let values = filter.value.as_array().cloned().unwrap_or_else(|| vec![filter.value.clone()]);
+                if values.is_empty() {
+                    "0".to_string()
+                } else {
+                    let placeholders = values
+                        .iter()
+                        .map(|v| Ok(bind(&mut params, bind_value(v, column_type)?)))
+                        .collect::<Result<Vec<String>, String>>()?;
+                    format!("{} IN ({})", expr, placeholders.join(", "))
+                }
+            }
+            FilterOp::Between => {
+                let range = filter.value.as_array().filter(|a| a.len() == 2).ok_or_else(|| {
+                    format!("between 필터는 [시작, 끝] 값이 필요합니다: {}", filter.column)
+                })?;
+                let start = bind(&mut params, bind_value(&range[0], column_type)?);
+                let end = bind(&mut params, bind_value(&range[1], column_type)?);
+                format!("{} BETWEEN {} AND {}", expr, start, end)
+            }
+        };
+        conditions.push(condition);
+    }
+    let where_clause = conditions.join(" AND ");
+
+    // SELECT
+    let grouped = !query.group_by.is_empty() || !query.aggregates.is_empty();
+    let mut outputs: Vec<Output> = Vec::new();
+    if grouped {
+        for name in &query.group_by {
+            let index = find(name)?;
+            outputs.push(Output { expr: column_expr(index), name: name.clone(), column_type: type_of(index) });
+        }
+        for aggregate in &query.aggregates {
+            let function = aggregate_name(aggregate.function);
+            let (expr, column_type, label) = match &aggregate.column {
+                None if aggregate.function == AggregateFn::Count => ("COUNT(*)".to_string(), ColumnType::Integer, "count".to_string()),
+                None => return Err(format!("{} 집계에는 컬럼이 필요합니다", function)),
+                Some(name) => {
+                    let index = find(name)?;
+                    let source_type = type_of(index);
+                    if matches!(aggregate.function, AggregateFn::Sum | AggregateFn::Avg) && !is_numeric(source_type) {
+                        return Err(format!("{}는 숫자 컬럼에서만 계산할 수 있습니다: {}", function, name));
+                    }
+                    let column_type = match aggregate.function {
+                        AggregateFn::Count => ColumnType::Integer,
+                        AggregateFn::Avg => ColumnType::Float,
+                        _ => source_type,
+                    };
+                    (
+                        format!("{}({})", function.to_uppercase(), column_expr(index)),
+                        column_type,
+                        format!("{}({})", function, name),
+                    )
+                }
+            };
+            let name = aggregate.alias.clone().filter(|a| !a.trim().is_empty()).unwrap_or(label);
+            outputs.push(Output { expr, name, column_type });
+        }
+    } else {
+        let selected: Vec<usize> = if query.select.is_empty() {
+            (0..columns.len()).collect()
+        } else {
+            query.select.iter().map(|name| find(name)).collect::<Result<_, _>>()?
+        };
+        for index in selected {
+            outputs.push(Output { expr: column_expr(index), name: columns[index].clone(), column_type: type_of(index) });
+        }
+    }
+    if outputs.is_empty() {
+        return Err("보여줄 컬럼이 없습니다".to_string());
+    }
+    let select_clause = outputs
+        .iter()
+        .enumerate()
+        .map(|(i, o)| format!("{} AS c{}", o.expr, i))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // ORDER BY (값이 없는 행은 항상 뒤로)
+    let mut order = Vec::new();
+    for key in &query.sort {
+        let expr = if grouped {
+            let position = outputs
+                .iter()
+                .position(|o| o.name == key.column)
+                .ok_or_else(|| format!("그룹 질의는 그룹 컬럼이나 집계로만 정렬할 수 있습니다: {}", key.column))?;
+            format!("c{}", position)
+        } else {
+            column_expr(find(&key.column)?)
+        };
+        order.push(format!("{} IS NULL", expr));
+        order.push(format!("{}{}", expr, if key.descending { " DESC" } else { "" }));
+    }
+    if !grouped {
+        order.push("r.row_index".to_string());
+    } else if order.is_empty() && !query.group_by.is_empty() {
+        order.extend((0..query.group_by.len()).map(|i| format!("c{}", i)));
+    }
+
+    let group_clause = if query.group_by.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " GROUP BY {}",
+            (0..query.group_by.len()).map(|i| format!("c{}", i)).collect::<Vec<_>>().join(", ")
+        )
+    };
+    let order_clause = if order.is_empty() { String::new() } else { format!(" ORDER BY {}", order.join(", ")) };
+
+    let base = format!(
+        "SELECT {} FROM dataset_rows r WHERE {}{}",
+        select_clause, where_clause, group_clause
+    );
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let offset = query.offset.max(0);
+    Ok(BuiltQuery {
+        sql: format!("{}{} LIMIT {} OFFSET {}", base, order_clause, limit, offset),
+        count_sql: format!("SELECT COUNT(*) FROM ({})", base),
+        params,
+        columns: outputs.iter().map(|o| o.name.clone()).collect(),
+        column_types: outputs.iter().map(|o| o.column_type).collect(),
+        offset,
+        limit,
+    })
+}
+
+/// SQLite 값 → 셀 값 (컬럼 타입으로 날짜/불리언 구분)
+pub fn cell_from_sql(value: Value, column_type: ColumnType) -> CellValue {
+    match value {
+        Value::Null => CellValue::Null,
+        Value::Integer(i) if column_type == ColumnType::Boolean => CellValue::Boolean(i != 0),
+        Value::Integer(i) if matches!(column_type, ColumnType::Float | ColumnType::Currency) => CellValue::Float(i as f64),
+        Value::Integer(i) => CellValue::Integer(i),
+        Value::Real(f) => CellValue::Float(f),
+        Value::Text(t) if column_type == ColumnType::Date => CellValue::Date(t),
+        Value::Text(t) => CellValue::Text(t),
+        Value::Blob(_) => CellValue::Null,
+    }
+}
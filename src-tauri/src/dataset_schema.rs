@@ -0,0 +1,216 @@
+// ===== 데이터셋 컬럼 타입 =====
+//
+// 가져온 값은 모두 문자열(dataset_rows.data_json)이라 숫자/날짜로 정렬·필터·집계할 수 없음
+// 가져올 때 전체 행을 보고 컬럼 타입(integer, float, date, boolean, text, currency)을 추측해 datasets에 저장하고
+// 행마다 타입에 맞게 한 번 변환한 값(dataset_rows.typed_json)을 함께 저장
+// - 숫자/통화 → JSON 숫자, 날짜 → "YYYY-MM-DD"(시간이 있으면 "YYYY-MM-DDTHH:MM"), 불리언 → true/false
+// - 비었거나 타입에 맞지 않는 값 → null
+// 사용자가 컬럼 타입을 바꾸면(override) typed_json만 다시 만듦
+
+use crate::bank_import;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 컬럼 타입
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Date,
+    Boolean,
+    #[default]
+    Text,
+    Currency,
+}
+
+/// 타입에 맞게 변환한 셀 값 (JSON으로는 숫자/문자열/불리언/null)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum CellValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Date(String),
+    Text(String),
+}
+
+impl CellValue {
+    /// 저장된 JSON 값 → 셀 값 (날짜와 텍스트는 컬럼 타입으로 구분)
+    pub fn from_json(value: &serde_json::Value, column_type: ColumnType) -> CellValue {
+        match value {
+            serde_json::Value::Bool(b) => CellValue::Boolean(*b),
+            serde_json::Value::Number(n) => match (column_type, n.as_i64()) {
+                (ColumnType::Integer, Some(v)) => CellValue::Integer(v),
+                _ => n.as_f64().map(CellValue::Float).unwrap_or(CellValue::Null),
+            },
+            serde_json::Value::String(s) if column_type == ColumnType::Date => CellValue::Date(s.clone()),
+            serde_json::Value::String(s) => CellValue::Text(s.clone()),
+            _ => CellValue::Null,
+        }
+    }
+}
+
+/// 빈 값으로 취급하는 표시
+const NULL_MARKERS: &[&str] = &["-", "n/a", "na", "null", "none", "#n/a", "없음"];
+
+static INTEGER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[-+]?\d{1,3}(,\d{3})+$|^[-+]?\d+$").unwrap());
+static FLOAT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[-+]?(\d{1,3}(,\d{3})+|\d+)?(\.\d+)?([eE][-+]?\d+)?$").unwrap());
+static CURRENCY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\(?[-+]?\s*(?:[₩$€¥£]|KRW|USD|EUR|JPY|CNY|GBP)?\s*[-+]?[\d,]+(?:\.\d+)?\s*(?:원|KRW|USD|EUR|JPY|CNY|GBP)?\)?$").unwrap()
+});
+static CURRENCY_MARKER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[₩$€¥£원]|KRW|USD|EUR|JPY|CNY|GBP").unwrap());
+
+const TRUE_VALUES: &[&str] = &["true", "yes", "y", "예", "참"];
+const FALSE_VALUES: &[&str] = &["false", "no", "n", "아니오", "거짓"];
+
+fn is_null(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty() || NULL_MARKERS.contains(&text.to_lowercase().as_str())
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    let lower = text.trim().to_lowercase();
+    if TRUE_VALUES.contains(&lower.as_str()) {
+        Some(true)
+    } else if FALSE_VALUES.contains(&lower.as_str()) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// 0으로 시작하는 여러 자리 숫자 (우편번호, 전화번호, 코드 등 - 숫자가 아니라 텍스트로 봄)
+fn has_leading_zero(text: &str) -> bool {
+    let digits = text.trim_start_matches(['-', '+']);
+    digits.len() > 1 && digits.starts_with('0') && !digits[1..].starts_with('.')
+}
+
+/// 정수 ("1,234", "-12")
+fn parse_integer(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if has_leading_zero(text) || !INTEGER_RE.is_match(text) {
+        return None;
+    }
+    text.replace(',', "").parse().ok()
+}
+
+/// 실수 ("1,234.5", "-0.25", "1e-3")
+pub fn parse_float(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if has_leading_zero(text) || !text.chars().any(|c| c.is_ascii_digit()) || !FLOAT_RE.is_match(text) {
+        return None;
+    }
+    text.replace(',', "").parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// 통화 기호/코드가 붙은 금액 ("₩12,500", "12,500원", "$12.50", "USD 12.50", "(1,000)")
+fn parse_currency(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if !CURRENCY_MARKER_RE.is_match(text) || !CURRENCY_RE.is_match(text) {
+        return None;
+    }
+    bank_import::parse_amount(&CURRENCY_MARKER_RE.replace_all(text, ""))
+}
+
+/// 문자열 → 컬럼 타입에 맞는 값 (맞지 않으면 Null, 텍스트는 그대로)
+pub fn parse_cell(text: &str, column_type: ColumnType) -> CellValue {
+    if column_type == ColumnType::Text {
+        return if text.is_empty() { CellValue::Null } else { CellValue::Text(text.to_string()) };
+    }
+    if is_null(text) {
+        return CellValue::Null;
+    }
+    let value = match column_type {
+        ColumnType::Integer => parse_integer(text).map(CellValue::Integer),
+        ColumnType::Float => parse_float(text).map(CellValue::Float),
+        ColumnType::Currency => parse_currency(text)
+            .or_else(|| parse_float(text))
+            .map(CellValue::Float),
+        ColumnType::Date => bank_import::parse_date(text, None).map(CellValue::Date),
+        ColumnType::Boolean => parse_bool(text).map(CellValue::Boolean),
+        ColumnType::Text => None,
+    };
+    value.unwrap_or(CellValue::Null)
+}
+
+/// 한 행을 저장용 JSON 배열로 (typed_json)
+pub fn typed_json(row: &[String], types: &[ColumnType]) -> String {
+    let values: Vec<CellValue> = types
+        .iter()
+        .enumerate()
+        .map(|(i, t)| parse_cell(row.get(i).map(String::as_str).unwrap_or(""), *t))
+        .collect();
+    serde_json::to_string(&values).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 컬럼 하나에서 본 값들 (비지 않은 값 중 각 타입으로 읽히는 개수)
+#[derive(Debug, Clone, Default)]
+struct ColumnStats {
+    values: usize,
+    integers: usize,
+    floats: usize,
+    currencies: usize,
+    currency_marked: usize,
+    dates: usize,
+    booleans: usize,
+}
+
+/// 행을 하나씩 넣어 컬럼 타입 추측 (모든 값이 읽혀야 그 타입, 아니면 text)
+#[derive(Debug, Clone)]
+pub struct TypeInference {
+    columns: Vec<ColumnStats>,
+}
+
+impl TypeInference {
+    pub fn new(column_count: usize) -> Self {
+        Self { columns: vec![ColumnStats::default(); column_count] }
+    }
+
+    pub fn observe(&mut self, row: &[String]) {
+        for (stats, text) in self.columns.iter_mut().zip(row) {
+            if is_null(text) {
+                continue;
+            }
+            stats.values += 1;
+            let integer = parse_integer(text).is_some();
+            let float = integer || parse_float(text).is_some();
+            let currency = parse_currency(text).is_some();
+            stats.integers += integer as usize;
+            stats.floats += float as usize;
+            stats.currency_marked += currency as usize;
+            stats.currencies += (currency || float) as usize;
+            if !float {
+                stats.booleans += parse_bool(text).is_some() as usize;
+                stats.dates += bank_import::parse_date(text, None).is_some() as usize;
+            }
+        }
+    }
+
+    pub fn finish(&self) -> Vec<ColumnType> {
+        self.columns
+            .iter()
+            .map(|s| match s.values {
+                0 => ColumnType::Text,
+                n if s.booleans == n => ColumnType::Boolean,
+                n if s.integers == n => ColumnType::Integer,
+                n if s.floats == n => ColumnType::Float,
+                n if s.currencies == n && s.currency_marked > 0 => ColumnType::Currency,
+                n if s.dates == n => ColumnType::Date,
+                _ => ColumnType::Text,
+            })
+            .collect()
+    }
+}
+
+/// 추측한 타입에 사용자 지정(override)을 덮어쓴 실제 타입
+pub fn effective_types(inferred: &[ColumnType], overrides: &[Option<ColumnType>]) -> Vec<ColumnType> {
+    inferred
+        .iter()
+        .enumerate()
+        .map(|(i, t)| overrides.get(i).copied().flatten().unwrap_or(*t))
+        .collect()
+}
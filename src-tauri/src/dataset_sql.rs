@@ -0,0 +1,449 @@
+// ===== 데이터셋 SQL 샌드박스 =====
+//
+// AI 질문 답변은 모델이 만든 읽기 전용 SQL을 전체 행에 대해 로컬에서 실행한 결과로 답함
+// 앱 DB가 아니라 데이터셋 하나만 담은 메모리 SQLite(테이블 data)를 따로 열어 실행하므로
+// 메모/설정/API 키 같은 다른 데이터에는 닿을 수 없음
+// - 문장 허용 목록: SELECT / WITH 한 문장만 (주석, 끝의 ;는 무시)
+// - authorizer: data 테이블 읽기, 함수, 재귀 CTE만 허용 (ATTACH, PRAGMA, 쓰기 등은 준비 단계에서 거부)
+// - 실행 시간 제한(progress handler)과 결과 행 수 제한
+
+use crate::dataset_query;
+use crate::dataset_schema::{CellValue, ColumnType};
+use crate::db;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// 샌드박스 테이블 이름
+pub const TABLE_NAME: &str = "data";
+/// 결과로 돌려주는 최대 행 수
+pub const MAX_RESULT_ROWS: usize = 1_000;
+/// 쿼리 하나의 최대 실행 시간
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// 데이터셋을 샌드박스로 옮길 때 한 번에 읽는 행 수
+const LOAD_CHUNK: i64 = 5_000;
+
+/// SQL 실행 결과 표
+#[derive(Debug, Clone, Serialize)]
+pub struct SqlResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<CellValue>>,
+    pub truncated: bool,   // MAX_RESULT_ROWS에서 잘렸는지
+}
+
+/// 컬럼 하나의 전체 행 통계 (AI 분석용)
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub non_null: i64,
+    pub distinct: i64,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub avg: Option<f64>,
+    pub sum: Option<f64>,
+    pub top_values: Vec<(String, i64)>,   // 텍스트 컬럼의 많이 나온 값
+}
+
+/// 데이터셋 하나를 담은 메모리 SQLite
+pub struct Sandbox {
+    conn: Connection,
+    columns: Vec<String>,
+    types: Vec<ColumnType>,
+    row_count: i64,
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn sql_type(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Integer | ColumnType::Boolean => "INTEGER",
+        ColumnType::Float | ColumnType::Currency => "REAL",
+        ColumnType::Date | ColumnType::Text => "TEXT",
+    }
+}
+
+fn json_to_sql(value: Option<&serde_json::Value>) -> Value {
+    match value {
+        Some(serde_json::Value::Bool(b)) => Value::Integer(*b as i64),
+        Some(serde_json::Value::Number(n)) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map(Value::Real).unwrap_or(Value::Null),
+        },
+        Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+        _ => Value::Null,
+    }
+}
+
+fn value_to_string(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => String::new(),
+        CellValue::Integer(i) => i.to_string(),
+        CellValue::Float(f) => f.to_string(),
+        CellValue::Boolean(b) => b.to_string(),
+        CellValue::Date(s) | CellValue::Text(s) => s.clone(),
+    }
+}
+
+/// 중복/빈 컬럼 이름을 샌드박스용으로 정리 (이전 버전에서 가져온 데이터셋 대비)
+fn table_columns(columns: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        let base = match column.trim() {
+            "" => format!("Column{}", i + 1),
+            name => name.to_string(),
+        };
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.iter().any(|existing| existing.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// 모델이 만든 SQL 검사 → 실행할 한 문장 (SELECT / WITH만)
+pub fn validate(sql: &str) -> Result<String, String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut cleaned = String::with_capacity(sql.len());
+    let mut statement_end = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            cleaned.push(' ');
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            cleaned.push(' ');
+            continue;
+        }
+        if statement_end {
+            if !c.is_whitespace() && c != ';' {
+                return Err("SQL은 한 문장만 실행할 수 있습니다".to_string());
+            }
+            i += 1;
+            continue;
+        }
+        if c == ';' {
+            statement_end = true;
+            i += 1;
+            continue;
+        }
+        if let Some(close) = match c {
+            '\'' | '"' | '`' => Some(c),
+            '[' => Some(']'),
+            _ => None,
+        } {
+            // 문자열/따옴표 식별자는 그대로 (안의 ;와 --는 구분자가 아님)
+            cleaned.push(c);
+            i += 1;
+            while i < chars.len() {
+                cleaned.push(chars[i]);
+                if chars[i] == close {
+                    if chars.get(i + 1) == Some(&close) && close != ']' {
+                        cleaned.push(close);
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        cleaned.push(c);
+        i += 1;
+    }
+
+    let cleaned = cleaned.trim().to_string();
+    let keyword: String = cleaned
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase();
+    match keyword.as_str() {
+        "SELECT" | "WITH" => Ok(cleaned),
+        "" => Err("빈 SQL입니다".to_string()),
+        other => Err(format!("SELECT 문만 실행할 수 있습니다: {}", other)),
+    }
+}
+
+// 준비 단계에서 허용할 동작 (data 테이블과 CTE 읽기, 함수, 재귀 CTE만)
+// CTE 읽기는 database_name이 없음 (실제 테이블은 main/temp)
+fn authorize(ctx: AuthContext<'_>) -> Authorization {
+    match ctx.action {
+        AuthAction::Select | AuthAction::Recursive => Authorization::Allow,
+        AuthAction::Read { table_name, .. } if table_name == TABLE_NAME || ctx.database_name.is_none() => {
+            Authorization::Allow
+        }
+        AuthAction::Function { function_name }
+            if !function_name.eq_ignore_ascii_case("load_extension") =>
+        {
+            Authorization::Allow
+        }
+        _ => Authorization::Deny,
+    }
+}
+
+impl Sandbox {
+    /// 데이터셋 전체 행(typed_json)을 메모리 SQLite로 옮김
+    pub fn load(dataset_id: i64) -> Result<Self, String> {
+        db::ensure_dataset_types(dataset_id).map_err(|e| e.to_string())?;
+        let dataset = db::get_dataset(dataset_id).map_err(|e| e.to_string())?;
+        let columns = table_columns(&dataset.columns);
+        let mut types = dataset.column_types.clone();
+        types.resize(columns.len(), ColumnType::Text);
+
+        let mut conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        let definitions: Vec<String> = columns
+            .iter()
+            .zip(&types)
+            .map(|(name, t)| format!("{} {}", quote_ident(name), sql_type(*t)))
+            .collect();
+        conn.execute(&format!("CREATE TABLE {} ({})", TABLE_NAME, definitions.join(", ")), [])
+            .map_err(|e| e.to_string())?;
+
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let insert_sql = format!("INSERT INTO {} VALUES ({})", TABLE_NAME, placeholders);
+        let mut row_count = 0;
+        let mut after_id = 0;
+        loop {
+            let chunk = db::dataset_typed_chunk(dataset_id, after_id, LOAD_CHUNK).map_err(|e| e.to_string())?;
+            let Some((last_id, _)) = chunk.last() else {
+                break;
+            };
+            after_id = *last_id;
+
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            {
+                let mut stmt = tx.prepare(&insert_sql).map_err(|e| e.to_string())?;
+                for (_, values) in &chunk {
+                    let row = (0..columns.len()).map(|i| json_to_sql(values.get(i)));
+                    stmt.execute(params_from_iter(row)).map_err(|e| e.to_string())?;
+                }
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+            row_count += chunk.len() as i64;
+        }
+
+        // 여기부터는 읽기만
+        conn.pragma_update(None, "query_only", true).map_err(|e| e.to_string())?;
+        conn.authorizer(Some(authorize));
+
+        Ok(Self { conn, columns, types, row_count })
+    }
+
+    pub fn row_count(&self) -> i64 {
+        self.row_count
+    }
+
+    /// 모델에 보여줄 테이블 정의 (CREATE TABLE + 컬럼별 값 형식)
+    pub fn schema(&self) -> String {
+        let last = self.columns.len().saturating_sub(1);
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&self.types)
+            .enumerate()
+            .map(|(i, (name, t))| {
+                let note = match t {
+                    ColumnType::Integer => "정수",
+                    ColumnType::Float => "실수",
+                    ColumnType::Currency => "금액 (숫자)",
+                    ColumnType::Date => "날짜 'YYYY-MM-DD' 문자열 (시간이 있으면 'YYYY-MM-DDTHH:MM')",
+                    ColumnType::Boolean => "불리언 (1 = 참, 0 = 거짓)",
+                    ColumnType::Text => "텍스트",
+                };
+                let comma = if i == last { "" } else { "," };
+                format!("  {} {}{}  -- {}", quote_ident(name), sql_type(*t), comma, note)
+            })
+            .collect();
+        format!("CREATE TABLE {} (\n{}\n);", TABLE_NAME, columns.join("\n"))
+    }
+
+    /// 모델에 보여줄 테이블 설명 (정의 + 전체 행 수 + 예시 행)
+    pub fn describe(&self, sample_rows: usize) -> Result<String, String> {
+        let sample = self.execute(&format!("SELECT * FROM {} LIMIT {}", TABLE_NAME, sample_rows))?;
+        Ok(format!(
+            "{}\n\n전체 {}행, 예시 {}행:\n{}",
+            self.schema(),
+            self.row_count,
+            sample.rows.len(),
+            result_to_text(&sample, sample_rows)
+        ))
+    }
+
+    /// 검사한 SELECT 실행 (MAX_RESULT_ROWS까지)
+    pub fn execute(&self, sql: &str) -> Result<SqlResult, String> {
+        let sql = validate(sql)?;
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        self.conn.progress_handler(10_000, Some(move || Instant::now() > deadline));
+        let result = self.run(&sql);
+        self.conn.progress_handler(0, None::<fn() -> bool>);
+        result.map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::OperationInterrupted => {
+                format!("쿼리 실행 시간이 {}초를 넘었습니다", QUERY_TIMEOUT.as_secs())
+            }
+            rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::AuthorizationForStatementDenied => {
+                "data 테이블을 읽는 SELECT 문만 실행할 수 있습니다".to_string()
+            }
+            rusqlite::Error::InvalidQuery => "읽기 전용 SELECT 문만 실행할 수 있습니다".to_string(),
+            e => e.to_string(),
+        })
+    }
+
+    fn run(&self, sql: &str) -> rusqlite::Result<SqlResult> {
+        let mut stmt = self.conn.prepare(sql)?;
+        if !stmt.readonly() || stmt.column_count() == 0 {
+            return Err(rusqlite::Error::InvalidQuery);
+        }
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut query = stmt.query([])?;
+        while let Some(row) = query.next()? {
+            if rows.len() == MAX_RESULT_ROWS {
+                truncated = true;
+                break;
+            }
+            let values = (0..columns.len())
+                .map(|i| Ok(dataset_query::cell_from_sql(row.get(i)?, ColumnType::Text)))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.push(values);
+        }
+        Ok(SqlResult { columns, rows, truncated })
+    }
+
+    /// 전체 행 기준 컬럼별 통계
+    pub fn profile(&self) -> Result<Vec<ColumnProfile>, String> {
+        self.columns
+            .iter()
+            .zip(&self.types)
+            .map(|(name, t)| {
+                let column = quote_ident(name);
+                let numeric = matches!(t, ColumnType::Integer | ColumnType::Float | ColumnType::Currency);
+                let summary = self.execute(&format!(
+                    "SELECT COUNT({c}), COUNT(DISTINCT {c}), MIN({c}), MAX({c}), {avg}, {sum} FROM {table}",
+                    c = column,
+                    avg = if numeric { format!("AVG({})", column) } else { "NULL".to_string() },
+                    sum = if numeric { format!("SUM({})", column) } else { "NULL".to_string() },
+                    table = TABLE_NAME,
+                ))?;
+                let row = summary.rows.first().cloned().unwrap_or_default();
+                let count = |i: usize| match row.get(i) {
+                    Some(CellValue::Integer(v)) => *v,
+                    _ => 0,
+                };
+                let number = |i: usize| match row.get(i) {
+                    Some(CellValue::Integer(v)) => Some(*v as f64),
+                    Some(CellValue::Float(v)) => Some(*v),
+                    _ => None,
+                };
+                let text = |i: usize| row.get(i).filter(|v| **v != CellValue::Null).map(value_to_string);
+
+                let top_values = if *t == ColumnType::Text {
+                    self.execute(&format!(
+                        "SELECT {c}, COUNT(*) AS n FROM {table} WHERE {c} IS NOT NULL GROUP BY {c} ORDER BY n DESC LIMIT 5",
+                        c = column,
+                        table = TABLE_NAME,
+                    ))?
+                    .rows
+                    .iter()
+                    .map(|r| {
+                        let n = match r.get(1) {
+                            Some(CellValue::Integer(n)) => *n,
+                            _ => 0,
+                        };
+                        (r.first().map(value_to_string).unwrap_or_default(), n)
+                    })
+                    .collect()
+                } else {
+                    Vec::new()
+                };
+
+                Ok(ColumnProfile {
+                    name: name.clone(),
+                    column_type: *t,
+                    non_null: count(0),
+                    distinct: count(1),
+                    min: if *t == ColumnType::Text { None } else { text(2) },
+                    max: if *t == ColumnType::Text { None } else { text(3) },
+                    avg: number(4),
+                    sum: number(5),
+                    top_values,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 결과 표를 문자열 행으로 (화면 표시 / 프롬프트용)
+pub fn rows_as_strings(result: &SqlResult) -> Vec<Vec<String>> {
+    result
+        .rows
+        .iter()
+        .map(|row| row.iter().map(value_to_string).collect())
+        .collect()
+}
+
+/// 결과 표를 프롬프트용 텍스트로 (최대 max_rows행)
+pub fn result_to_text(result: &SqlResult, max_rows: usize) -> String {
+    let mut lines = vec![result.columns.join(" | ")];
+    for row in rows_as_strings(result).iter().take(max_rows) {
+        lines.push(row.join(" | "));
+    }
+    if result.rows.len() > max_rows || result.truncated {
+        lines.push(format!(
+            "... ({}행 중 {}행만 표시{})",
+            result.rows.len(),
+            max_rows.min(result.rows.len()),
+            if result.truncated { format!(", 결과는 {}행에서 잘림", MAX_RESULT_ROWS) } else { String::new() }
+        ));
+    }
+    lines.join("\n")
+}
+
+/// 컬럼 통계를 프롬프트용 텍스트로
+pub fn profile_to_text(profile: &[ColumnProfile]) -> String {
+    profile
+        .iter()
+        .map(|p| {
+            let mut parts = vec![
+                format!("값 {}개", p.non_null),
+                format!("고유값 {}개", p.distinct),
+            ];
+            if let (Some(min), Some(max)) = (&p.min, &p.max) {
+                parts.push(format!("범위 {} ~ {}", min, max));
+            }
+            if let Some(avg) = p.avg {
+                parts.push(format!("평균 {:.2}", avg));
+            }
+            if let Some(sum) = p.sum {
+                parts.push(format!("합계 {}", sum));
+            }
+            if !p.top_values.is_empty() {
+                let top: Vec<String> = p.top_values.iter().map(|(v, n)| format!("{}({})", v, n)).collect();
+                parts.push(format!("많은 값 {}", top.join(", ")));
+            }
+            format!("- {} [{}]: {}", p.name, sql_type(p.column_type), parts.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,6 +1,8 @@
 use rusqlite::{Connection, Result, params};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use crate::dataset_query;
+use crate::dataset_schema::{self, CellValue, ColumnType};
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
 
@@ -31,6 +33,38 @@ pub struct Schedule {
     pub description: Option<String>,
     pub google_event_id: Option<String>,
     pub created_at: String,
+    #[serde(default)]
+    pub recurrence: Option<String>,  // RFC 5545 RRULE (예: FREQ=WEEKLY;BYDAY=TU), 없으면 단일 일정
+    #[serde(default)]
+    pub exdates: Option<String>,     // 제외할 발생 시각 (쉼표 구분, start_time과 같은 형식)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleOccurrence {
+    pub schedule: Schedule,
+    pub occurrence_start: String,
+    pub occurrence_end: Option<String>,
+}
+
+// CalDAV 동기화 상태 (external_id = 서버의 리소스 href, schedules.google_event_id 컬럼에 저장)
+#[derive(Debug, Clone)]
+pub struct ScheduleSyncState {
+    pub schedule: Schedule,
+    pub external_id: Option<String>,
+    pub etag: Option<String>,
+    pub sync_hash: Option<String>,
+    pub updated_at: String,  // UTC, 수정된 적 없으면 created_at
+    pub ical_uid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalDavConflict {
+    pub id: i64,
+    pub schedule_id: i64,
+    pub external_id: String,
+    pub remote_etag: Option<String>,
+    pub remote_ics: String,
+    pub detected_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,18 +76,197 @@ pub struct Todo {
     pub priority: Option<String>,  // high, medium, low
     pub due_date: Option<String>,
     pub created_at: String,
+    #[serde(default)]
+    pub parent_id: Option<i64>,        // 상위 할일 (하위 할일이면)
+    #[serde(default)]
+    pub project: Option<String>,       // 프로젝트/목록 이름
+    #[serde(default)]
+    pub sort_order: Option<i64>,       // 수동 정렬 순서 (없으면 마감일 순)
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,    // RFC 5545 RRULE (반복 할일), 반복 아니면 None
+    #[serde(default)]
+    pub series_id: Option<i64>,        // 같은 반복 할일 묶음 (처음 할일의 id)
+}
+
+// 반복 할일 완료 기록
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TodoCompletion {
+    pub id: i64,
+    pub series_id: i64,
+    pub todo_id: i64,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub on_time: bool,                 // 마감일 안에 완료했는지 (마감일 없으면 true)
+    pub completed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TodoProject {
+    pub name: String,
+    pub total: i64,
+    pub open: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transaction {
     pub id: i64,
     pub memo_id: Option<i64>,
-    pub tx_type: String,  // "income" or "expense"
-    pub amount: i64,
+    pub tx_type: String,  // "income", "expense" or "transfer"
+    pub amount: i64,      // 통화의 최소 단위 (KRW 원, USD 센트)
     pub description: String,
     pub category: Option<String>,
     pub tx_date: Option<String>,
     pub created_at: String,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub account_id: Option<i64>,       // 결제/입금 계좌 (이체면 보내는 계좌)
+    #[serde(default)]
+    pub to_account_id: Option<i64>,    // 이체 받는 계좌
+    #[serde(default)]
+    pub to_amount: Option<i64>,        // 받는 계좌 통화로 환산한 금액 (통화가 다를 때)
+}
+
+fn default_currency() -> String {
+    crate::currency::DEFAULT_CURRENCY.to_string()
+}
+
+// 계좌 (현금, 카드, 은행 등)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Account {
+    pub id: i64,
+    pub name: String,
+    pub account_type: String,          // cash, card, bank, other
+    pub currency: String,
+    pub opening_balance: i64,
+    pub created_at: String,
+}
+
+// 계좌별 통화별 입출금 합계 (이체 포함, 부호 있음)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountFlow {
+    pub account_id: i64,
+    pub currency: String,
+    pub amount: i64,
+    pub count: i64,
+}
+
+// 거래 자동 분류 규칙 (pattern이 적요에 포함되면 category로)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryRule {
+    pub id: i64,
+    pub pattern: String,
+    pub category: String,
+    pub hits: i64,                     // 같은 분류로 고친 횟수
+    pub source: String,                // learned (거래 수정에서 학습), manual
+    pub updated_at: String,
+}
+
+// 은행별 CSV 열 매핑 프로필 (mapping: CsvMapping JSON)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BankImportProfile {
+    pub id: i64,
+    pub name: String,
+    pub mapping: String,
+    pub updated_at: String,
+}
+
+// 첨부파일 본문 추출 결과
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttachmentText {
+    pub attachment_id: i64,
+    pub content: String,
+    pub file_modified: Option<i64>,    // 추출 당시 파일 수정 시각 (unix 초)
+    pub file_size: Option<i64>,
+    pub error: Option<String>,         // 추출 실패 사유
+    pub extracted_at: String,
+}
+
+// 첨부파일 미리보기 (썸네일은 앱 데이터 디렉토리/thumbnails, thumbnail_url은 asset 프로토콜 주소)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttachmentPreview {
+    pub attachment_id: i64,
+    pub sha256: Option<String>,
+    pub thumbnail_path: Option<String>,  // 만들 수 없는 형식이면 None
+    pub thumbnail_url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub taken_at: Option<String>,        // EXIF 촬영 일시
+    pub camera: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+// 영수증에서 만든 거래의 원본 첨부파일과 품목 (amount: 통화 최소 단위)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionReceipt {
+    pub transaction_id: i64,
+    pub attachment_id: Option<i64>,    // 첨부파일이 삭제되면 None
+    pub merchant: Option<String>,
+    pub items: Vec<ReceiptItem>,
+    pub backend: String,               // vision, command, rules
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReceiptItem {
+    pub name: String,
+    pub quantity: Option<f64>,
+    pub amount: i64,
+}
+
+// 환율 (1 currency = rate base_currency, rate_date부터 적용)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExchangeRate {
+    pub id: i64,
+    pub currency: String,
+    pub base_currency: String,
+    pub rate: f64,
+    pub rate_date: String,
+    pub updated_at: String,
+}
+
+// 거래 카테고리 (등록한 카테고리 + 거래에 쓰인 카테고리)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionCategory {
+    pub name: String,
+    pub tx_type: Option<String>,     // 등록한 카테고리의 기본 유형 (income/expense), 등록 안 됐으면 None
+    pub color: Option<String>,
+    pub registered: bool,
+    pub transaction_count: i64,
+    pub total_amount: i64,           // 기준 통화로 환산한 합계
+}
+
+// 카테고리별 예산 (category '*' = 전체 지출)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionBudget {
+    pub id: i64,
+    pub category: String,
+    pub period: String,              // monthly, weekly
+    pub limit_amount: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionAggregate {
+    pub bucket: String,      // 기간 시작일 (day/week: YYYY-MM-DD, month: YYYY-MM), 기간 구분 없으면 빈 문자열
+    pub group_key: String,   // 카테고리 또는 유형, 구분 없으면 빈 문자열
+    pub income: i64,         // 기준 통화로 환산한 금액
+    pub expense: i64,
+    pub net: i64,
+    pub count: i64,
+    pub unconverted: i64,    // 환율이 없어 합계에서 빠진 거래 수
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalancePoint {
+    pub bucket: String,
+    pub income: i64,
+    pub expense: i64,
+    pub net: i64,
+    pub balance: i64,        // 기간 끝 시점의 누적 잔액 (조회 시작일 이전 거래 포함)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -66,6 +279,8 @@ pub struct Attachment {
     pub is_copy: bool,           // 파일 복사 여부
     pub file_size: i64,
     pub created_at: String,
+    #[serde(default)]
+    pub sha256: Option<String>,  // 첨부할 때 파일 내용 해시 (기존 첨부파일은 검사할 때 채움)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -76,6 +291,18 @@ pub struct Dataset {
     pub columns: Vec<String>,
     pub row_count: i64,
     pub created_at: String,
+    #[serde(default)]
+    pub column_types: Vec<ColumnType>,                   // 실제 타입 (추측한 타입 + 사용자 지정)
+    #[serde(default)]
+    pub column_type_overrides: Vec<Option<ColumnType>>,  // 사용자가 지정한 타입 (없으면 추측한 타입)
+}
+
+/// 타입에 맞게 변환한 데이터셋 행
+#[derive(Debug, Serialize, Clone)]
+pub struct TypedDatasetRow {
+    pub id: i64,
+    pub row_index: i64,
+    pub values: Vec<CellValue>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -99,6 +326,89 @@ pub struct Postit {
     pub updated_at: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AiCacheEntry {
+    pub cache_key: String,
+    pub provider: String,
+    pub model: String,
+    pub operation: String,
+    pub prompt_hash: String,
+    pub config_hash: String,
+    pub response_text: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(default)]
+    pub cost_usd: Option<f64>,  // 원래 호출 비용 (캐시 적중 시 절감액, 이전 항목은 없음)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiBudget {
+    pub id: i64,
+    pub operation: String,   // '*' = 전체
+    pub period: String,      // daily, monthly
+    pub limit_usd: f64,
+    pub action: String,      // block (거부), confirm (사용자 확인 후 진행)
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelPrice {
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default)]
+    pub provider: String,
+    pub model: String,                    // '*' = 기본 가격
+    #[serde(default)]
+    pub effective_from: String,           // UTC, 이 시각 이후 호출에 적용
+    pub input_per_m: f64,                 // USD per 1M tokens
+    pub output_per_m: f64,
+    #[serde(default)]
+    pub cached_input_per_m: Option<f64>,  // 없으면 입력 가격 적용
+    #[serde(default)]
+    pub reasoning_per_m: Option<f64>,     // 없으면 출력 가격 적용
+    #[serde(default)]
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AiOutputFailure {
+    pub id: i64,
+    pub operation: String,
+    pub model: String,
+    pub error: String,
+    pub raw_response: String,
+    pub attempt: i64,      // 0 = 최초 응답, 1 이상 = 재요청 응답
+    pub retried: bool,     // 이 실패 후 재요청 했는지
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsageAggregate {
+    pub bucket: String,      // 기간 시작일 (day/week: YYYY-MM-DD, month: YYYY-MM), 기간 구분 없으면 빈 문자열
+    pub group_key: String,   // operation 또는 model 값, 구분 없으면 빈 문자열
+    pub request_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub cache_hits: i64,
+    pub saved_usd: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiUsageRecord {
+    pub id: i64,
+    pub timestamp: String,   // 로컬 시간
+    pub operation: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub cache_hit: bool,
+    pub saved_usd: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Alarm {
     pub id: i64,
@@ -107,6 +417,36 @@ pub struct Alarm {
     pub enabled: bool,
     pub days: String,        // JSON 배열 형식 (빈 배열이면 매일)
     pub created_at: String,
+    #[serde(default)]
+    pub date: Option<String>,          // YYYY-MM-DD, 있으면 그날 한 번만 울림 (days 무시)
+    #[serde(default)]
+    pub timezone: Option<String>,      // IANA 타임존 (예: America/New_York), 없으면 로컬 시간
+    #[serde(default)]
+    pub schedule_id: Option<i64>,      // 있으면 이 일정 시작 offset_minutes분 전에 울림 (time/days/date 무시)
+    #[serde(default)]
+    pub offset_minutes: i64,
+    #[serde(default = "default_snooze_minutes")]
+    pub snooze_minutes: i64,           // 다시 알림 간격
+    #[serde(default)]
+    pub next_fire_at: Option<String>,  // 다음에 울릴 로컬 시각 (스케줄러가 계산해서 저장)
+}
+
+fn default_snooze_minutes() -> i64 {
+    10
+}
+
+// 리마인더 상태 (key = 종류:원본ID@발생 시각, 같은 알림을 두 번 울리지 않도록 기록)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reminder {
+    pub key: String,
+    pub kind: String,            // alarm, schedule, todo
+    pub source_id: i64,
+    pub title: String,
+    pub message: String,
+    pub trigger_at: String,      // 원래 알림 시각 (로컬)
+    pub status: String,          // fired, snoozed, dismissed
+    pub snooze_until: Option<String>,
+    pub fired_at: Option<String>,
 }
 
 pub fn init_db(app_dir: PathBuf) -> Result<()> {
@@ -191,6 +531,78 @@ pub fn init_db(app_dir: PathBuf) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_transactions_type ON transactions(tx_type);
         CREATE INDEX IF NOT EXISTS idx_transactions_date ON transactions(tx_date);
 
+        CREATE TABLE IF NOT EXISTS transaction_categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            tx_type TEXT NOT NULL DEFAULT 'expense',
+            color TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        -- 가계부 예산 (category '*' = 전체 지출, period: monthly / weekly)
+        CREATE TABLE IF NOT EXISTS transaction_budgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            category TEXT NOT NULL DEFAULT '*',
+            period TEXT NOT NULL,
+            limit_amount INTEGER NOT NULL,
+            created_at TEXT DEFAULT (datetime('now')),
+            UNIQUE(category, period)
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('transaction_budget_warning_thresholds', '[0.8,1.0]');
+
+        CREATE TABLE IF NOT EXISTS accounts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            account_type TEXT NOT NULL DEFAULT 'cash',
+            currency TEXT NOT NULL DEFAULT 'KRW',
+            opening_balance INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS exchange_rates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            currency TEXT NOT NULL,
+            base_currency TEXT NOT NULL,
+            rate REAL NOT NULL,
+            rate_date TEXT NOT NULL,
+            updated_at TEXT DEFAULT (datetime('now')),
+            UNIQUE(currency, base_currency, rate_date)
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('base_currency', 'KRW');
+
+        CREATE TABLE IF NOT EXISTS category_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pattern TEXT NOT NULL UNIQUE,
+            category TEXT NOT NULL,
+            hits INTEGER NOT NULL DEFAULT 1,
+            source TEXT NOT NULL DEFAULT 'learned',
+            updated_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS bank_import_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            mapping TEXT NOT NULL,
+            updated_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS transaction_receipts (
+            transaction_id INTEGER PRIMARY KEY,
+            attachment_id INTEGER,
+            merchant TEXT,
+            items TEXT NOT NULL DEFAULT '[]',
+            backend TEXT NOT NULL,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_transaction_receipts_attachment ON transaction_receipts(attachment_id);
+
+        -- 영수증 인식 방식: vision (Gemini 이미지 인식) / command (로컬 OCR 명령, {file} = 이미지 경로)
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('receipt_ocr_backend', 'vision');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('receipt_ocr_command', 'tesseract {file} stdout -l kor+eng');
+
         CREATE TABLE IF NOT EXISTS attachments (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             memo_id INTEGER NOT NULL,
@@ -204,12 +616,41 @@ pub fn init_db(app_dir: PathBuf) -> Result<()> {
         );
 
         CREATE INDEX IF NOT EXISTS idx_attachments_memo ON attachments(memo_id);
+
+        -- 첨부파일 본문 (file_modified/file_size: 추출 당시 파일 상태, 바뀌면 다시 추출)
+        CREATE TABLE IF NOT EXISTS attachment_texts (
+            attachment_id INTEGER PRIMARY KEY REFERENCES attachments(id) ON DELETE CASCADE,
+            content TEXT NOT NULL DEFAULT '',
+            file_modified INTEGER,
+            file_size INTEGER,
+            error TEXT,
+            extracted_at TEXT DEFAULT (datetime('now'))
+        );
+
+        -- 본문 검색 인덱스 (rowid = attachment_id, trigram이라 한글 부분 일치도 검색됨)
+        CREATE VIRTUAL TABLE IF NOT EXISTS attachment_text_fts USING fts5(content, tokenize = 'trigram');
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('attachment_text_refresh_minutes', '10');
+
+        -- 첨부파일 미리보기 (sha256: 만들 때의 파일 내용 해시, 바뀌면 다시 생성)
+        CREATE TABLE IF NOT EXISTS attachment_previews (
+            attachment_id INTEGER PRIMARY KEY REFERENCES attachments(id) ON DELETE CASCADE,
+            sha256 TEXT,
+            thumbnail_path TEXT,
+            width INTEGER,
+            height INTEGER,
+            taken_at TEXT,
+            camera TEXT,
+            error TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
         CREATE INDEX IF NOT EXISTS idx_attachments_name ON attachments(file_name);
 
         INSERT OR IGNORE INTO settings (key, value) VALUES ('language', 'ko');
         INSERT OR IGNORE INTO settings (key, value) VALUES ('gemini_api_key', '');
         INSERT OR IGNORE INTO settings (key, value) VALUES ('attachment_copy_mode', 'link');
         INSERT OR IGNORE INTO settings (key, value) VALUES ('attachment_storage_path', '');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('todo_parent_completion', 'block');
 
         -- 엑셀 데이터셋 테이블
         CREATE TABLE IF NOT EXISTS datasets (
@@ -275,8 +716,172 @@ pub fn init_db(app_dir: PathBuf) -> Result<()> {
 
         CREATE INDEX IF NOT EXISTS idx_alarms_time ON alarms(time);
         CREATE INDEX IF NOT EXISTS idx_alarms_enabled ON alarms(enabled);
+
+        -- 리마인더 상태 (알람/일정/할일 알림의 울림, 다시 알림, 닫기)
+        CREATE TABLE IF NOT EXISTS reminder_states (
+            key TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            source_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            message TEXT NOT NULL,
+            trigger_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'fired',
+            snooze_until TEXT,
+            fired_at TEXT,
+            updated_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_reminder_states_status ON reminder_states(status, snooze_until);
+
+        -- 반복 할일 완료 기록 (회차별 완료 시각과 마감 준수 여부, 연속 기록 통계용)
+        CREATE TABLE IF NOT EXISTS todo_completions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            series_id INTEGER NOT NULL,
+            todo_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            due_date TEXT,
+            on_time INTEGER NOT NULL DEFAULT 1,
+            completed_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_todo_completions_series ON todo_completions(series_id, completed_at);
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminders_enabled', 'true');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminder_schedule_lead_minutes', '10');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminder_day_time', '09:00');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminder_catchup_hours', '24');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminder_default_snooze_minutes', '10');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('reminder_os_notification', 'true');
+
+        -- AI 응답 캐시 테이블 (provider + model + 프롬프트 해시 + 생성 설정 기준)
+        CREATE TABLE IF NOT EXISTS ai_cache (
+            cache_key TEXT PRIMARY KEY,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            prompt_hash TEXT NOT NULL,
+            config_hash TEXT NOT NULL,
+            response_text TEXT NOT NULL,
+            input_tokens INTEGER DEFAULT 0,
+            output_tokens INTEGER DEFAULT 0,
+            created_at TEXT DEFAULT (datetime('now')),
+            expires_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_ai_cache_operation ON ai_cache(operation);
+        CREATE INDEX IF NOT EXISTS idx_ai_cache_expires ON ai_cache(expires_at);
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('ai_cache_enabled', 'true');
+
+        -- API 사용 예산 테이블 (operation '*' = 전체)
+        CREATE TABLE IF NOT EXISTS api_budgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            operation TEXT NOT NULL DEFAULT '*',
+            period TEXT NOT NULL,
+            limit_usd REAL NOT NULL,
+            action TEXT NOT NULL DEFAULT 'block',
+            created_at TEXT DEFAULT (datetime('now')),
+            UNIQUE(operation, period)
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('budget_warning_thresholds', '[0.5,0.8,1.0]');
+
+        -- AI 구조화 응답 검증 실패 기록 (디버깅용 원본 응답 포함)
+        CREATE TABLE IF NOT EXISTS ai_output_failures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            operation TEXT NOT NULL,
+            model TEXT NOT NULL,
+            error TEXT NOT NULL,
+            raw_response TEXT NOT NULL,
+            attempt INTEGER DEFAULT 0,
+            retried INTEGER DEFAULT 0,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_ai_output_failures_created ON ai_output_failures(created_at);
+
+        -- CalDAV 동기화: 로컬에서 삭제된 원격 일정 (다음 동기화 때 서버에서도 삭제)
+        CREATE TABLE IF NOT EXISTS caldav_tombstones (
+            external_id TEXT PRIMARY KEY,
+            etag TEXT,
+            deleted_at TEXT DEFAULT (datetime('now'))
+        );
+
+        -- CalDAV 동기화: 양쪽이 모두 바뀌어 사용자 선택을 기다리는 일정 (conflict_policy = prompt)
+        CREATE TABLE IF NOT EXISTS caldav_conflicts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            schedule_id INTEGER NOT NULL UNIQUE,
+            external_id TEXT NOT NULL,
+            remote_etag TEXT,
+            remote_ics TEXT NOT NULL,
+            detected_at TEXT DEFAULT (datetime('now'))
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('caldav_url', '');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('caldav_username', '');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('caldav_password', '');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('caldav_conflict_policy', 'last_writer_wins');
     "#)?;
 
+    // 기존 DB 마이그레이션 (컬럼 추가)
+    add_column_if_missing(&conn, "api_usage", "cache_hit", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(&conn, "api_usage", "saved_usd", "REAL DEFAULT 0")?;
+    add_column_if_missing(&conn, "ai_cache", "cost_usd", "REAL")?;
+    add_column_if_missing(&conn, "schedules", "recurrence", "TEXT")?;
+    add_column_if_missing(&conn, "schedules", "exdates", "TEXT")?;
+    add_column_if_missing(&conn, "schedules", "ical_uid", "TEXT")?;
+    add_column_if_missing(&conn, "todos", "ical_uid", "TEXT")?;
+    add_column_if_missing(&conn, "schedules", "external_etag", "TEXT")?;
+    add_column_if_missing(&conn, "schedules", "sync_hash", "TEXT")?;
+    add_column_if_missing(&conn, "schedules", "updated_at", "TEXT")?;
+    add_column_if_missing(&conn, "alarms", "date", "TEXT")?;
+    add_column_if_missing(&conn, "alarms", "timezone", "TEXT")?;
+    add_column_if_missing(&conn, "alarms", "schedule_id", "INTEGER")?;
+    add_column_if_missing(&conn, "alarms", "offset_minutes", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(&conn, "alarms", "snooze_minutes", "INTEGER DEFAULT 10")?;
+    add_column_if_missing(&conn, "alarms", "next_fire_at", "TEXT")?;
+    add_column_if_missing(&conn, "todos", "parent_id", "INTEGER")?;
+    add_column_if_missing(&conn, "todos", "project", "TEXT")?;
+    add_column_if_missing(&conn, "todos", "sort_order", "INTEGER")?;
+    add_column_if_missing(&conn, "todos", "completed_at", "TEXT")?;
+    add_column_if_missing(&conn, "todos", "recurrence", "TEXT")?;
+    add_column_if_missing(&conn, "todos", "series_id", "INTEGER")?;
+    add_column_if_missing(&conn, "transactions", "currency", "TEXT NOT NULL DEFAULT 'KRW'")?;
+    add_column_if_missing(&conn, "transactions", "account_id", "INTEGER")?;
+    add_column_if_missing(&conn, "transactions", "to_account_id", "INTEGER")?;
+    add_column_if_missing(&conn, "transactions", "to_amount", "INTEGER")?;
+    add_column_if_missing(&conn, "transactions", "fingerprint", "TEXT")?;
+    add_column_if_missing(&conn, "attachments", "sha256", "TEXT")?;
+    add_column_if_missing(&conn, "datasets", "column_types_json", "TEXT")?;
+    add_column_if_missing(&conn, "datasets", "column_type_overrides_json", "TEXT")?;
+    add_column_if_missing(&conn, "dataset_rows", "typed_json", "TEXT")?;
+    // CalDAV href는 원래 있던 google_event_id 컬럼에 저장 (잠시 쓰던 external_id 컬럼은 옮기고 삭제)
+    let has_external_id = conn
+        .prepare("SELECT 1 FROM pragma_table_info('schedules') WHERE name = 'external_id'")?
+        .exists([])?;
+    if has_external_id {
+        conn.execute_batch(
+            "UPDATE schedules SET google_event_id = external_id WHERE external_id IS NOT NULL;
+             DROP INDEX IF EXISTS idx_schedules_external_id;
+             ALTER TABLE schedules DROP COLUMN external_id;",
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_schedules_ical_uid ON schedules(ical_uid);
+         CREATE INDEX IF NOT EXISTS idx_todos_ical_uid ON todos(ical_uid);
+         CREATE INDEX IF NOT EXISTS idx_schedules_google_event_id ON schedules(google_event_id);
+         CREATE INDEX IF NOT EXISTS idx_alarms_next_fire ON alarms(enabled, next_fire_at);
+         CREATE INDEX IF NOT EXISTS idx_todos_parent ON todos(parent_id);
+         CREATE INDEX IF NOT EXISTS idx_todos_project ON todos(project);
+         CREATE INDEX IF NOT EXISTS idx_todos_series ON todos(series_id);
+         CREATE INDEX IF NOT EXISTS idx_transactions_category ON transactions(category);
+         CREATE INDEX IF NOT EXISTS idx_transactions_account ON transactions(account_id);
+         CREATE INDEX IF NOT EXISTS idx_transactions_fingerprint ON transactions(fingerprint);
+         CREATE INDEX IF NOT EXISTS idx_attachments_sha256 ON attachments(sha256);",
+    )?;
+    backfill_transaction_fingerprints(&conn)?;
+    init_model_prices(&conn)?;
+
     DB.set(Mutex::new(conn)).ok();
     Ok(())
 }
@@ -285,6 +890,79 @@ pub fn get_db() -> &'static Mutex<Connection> {
     DB.get().expect("Database not initialized")
 }
 
+// 컬럼이 없으면 추가 (CREATE TABLE IF NOT EXISTS는 기존 테이블을 바꾸지 않으므로)
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists(params![column])?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// 모델 가격 테이블 (USD per 1M tokens, effective_from 이후 호출에 적용, UTC)
+// model = '*' 는 등록되지 않은 모델의 기본 가격
+const MODEL_PRICES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS model_prices (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider TEXT NOT NULL DEFAULT 'gemini',
+        model TEXT NOT NULL,
+        effective_from TEXT NOT NULL,
+        input_per_m REAL NOT NULL,
+        output_per_m REAL NOT NULL,
+        cached_input_per_m REAL,
+        reasoning_per_m REAL,
+        created_at TEXT DEFAULT (datetime('now')),
+        UNIQUE(provider, model, effective_from)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_model_prices_model ON model_prices(model, effective_from);
+";
+
+// 기본 모델 가격
+const MODEL_PRICES_SEED: &str = "
+    INSERT OR IGNORE INTO model_prices (model, effective_from, input_per_m, output_per_m, cached_input_per_m) VALUES
+        ('*', '2025-01-01 00:00:00', 0.10, 0.40, 0.025),
+        ('gemini-2.0-flash-lite', '2025-01-01 00:00:00', 0.075, 0.30, 0.01875),
+        ('gemini-2.0-flash', '2025-01-01 00:00:00', 0.10, 0.40, 0.025),
+        ('gemini-2.5-flash-lite', '2025-01-01 00:00:00', 0.10, 0.40, 0.025),
+        ('gemini-2.5-flash', '2025-01-01 00:00:00', 0.30, 2.50, 0.075),
+        ('gemini-2.5-pro', '2025-01-01 00:00:00', 1.25, 10.00, 0.31),
+        ('gemini-3-flash-preview', '2025-01-01 00:00:00', 0.50, 3.00, 0.05),
+        ('gemini-3-pro-preview', '2025-01-01 00:00:00', 2.00, 12.00, 0.20);
+";
+
+// 모델 가격 테이블 생성 + 기본 가격은 처음 한 번만 (사용자가 지운 가격이 다시 생기지 않도록)
+// 이전 버전 테이블(UNIQUE(model, effective_from))은 provider를 포함한 UNIQUE로 다시 만듦
+fn init_model_prices(conn: &Connection) -> Result<()> {
+    let old_unique = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'model_prices' AND sql LIKE '%UNIQUE(model, effective_from)%'")?
+        .exists([])?;
+    if old_unique {
+        conn.execute_batch("DROP INDEX IF EXISTS idx_model_prices_model; ALTER TABLE model_prices RENAME TO model_prices_old;")?;
+        conn.execute_batch(MODEL_PRICES_SCHEMA)?;
+        conn.execute_batch(
+            "INSERT INTO model_prices (id, provider, model, effective_from, input_per_m, output_per_m, cached_input_per_m, reasoning_per_m, created_at)
+             SELECT id, provider, model, effective_from, input_per_m, output_per_m, cached_input_per_m, reasoning_per_m, created_at
+             FROM model_prices_old;
+             DROP TABLE model_prices_old;",
+        )?;
+    } else {
+        conn.execute_batch(MODEL_PRICES_SCHEMA)?;
+    }
+
+    let seeded = conn.prepare("SELECT 1 FROM settings WHERE key = 'model_prices_seeded'")?.exists([])?;
+    if !seeded {
+        let empty = !conn.prepare("SELECT 1 FROM model_prices")?.exists([])?;
+        if empty {
+            conn.execute_batch(MODEL_PRICES_SEED)?;
+        }
+        conn.execute("INSERT INTO settings (key, value) VALUES ('model_prices_seeded', 'true')", [])?;
+    }
+    Ok(())
+}
+
 // 메모 저장
 pub fn save_memo(memo: &Memo) -> Result<i64> {
     let conn = get_db().lock();
@@ -370,451 +1048,2387 @@ pub fn log_api_usage(operation: &str, model: &str, input_tokens: i64, output_tok
     Ok(())
 }
 
-// 오늘 사용량 조회
+// 캐시 적중 기록 (비용 0, 원래 들었을 비용은 saved_usd에 기록)
+pub fn log_api_cache_hit(operation: &str, model: &str, input_tokens: i64, output_tokens: i64, saved_usd: f64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO api_usage (operation, model, input_tokens, output_tokens, cost_usd, cache_hit, saved_usd)
+         VALUES (?1, ?2, ?3, ?4, 0, 1, ?5)",
+        params![operation, model, input_tokens, output_tokens, saved_usd],
+    )?;
+    Ok(())
+}
+
+// 오늘 사용량 조회 (로컬 날짜 기준, 캐시 적중분 토큰은 실제 소비가 아니므로 제외)
 pub fn get_today_usage() -> Result<(i64, i64, f64)> {
     let conn = get_db().lock();
     let result = conn.query_row(
         "SELECT COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0), COALESCE(SUM(cost_usd), 0)
-         FROM api_usage WHERE date(timestamp) = date('now')",
+         FROM api_usage WHERE date(timestamp, 'localtime') = date('now', 'localtime') AND cache_hit = 0",
         [],
         |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
     ).unwrap_or((0, 0, 0.0));
     Ok(result)
 }
 
-// 전체 메모 삭제
-pub fn delete_all_memos() -> Result<usize> {
+// 오늘 캐시 적중 횟수 및 절감액 조회
+pub fn get_today_cache_savings() -> Result<(i64, f64)> {
     let conn = get_db().lock();
-    let count = conn.execute("DELETE FROM memos", [])?;
-    Ok(count)
+    let result = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(saved_usd), 0)
+         FROM api_usage WHERE date(timestamp, 'localtime') = date('now', 'localtime') AND cache_hit = 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap_or((0, 0.0));
+    Ok(result)
 }
 
-// 메모 전체 업데이트 (편집용)
-pub fn update_memo_full(id: i64, title: &str, formatted_content: &str, category: &str, tags: &str, content: Option<&str>) -> Result<()> {
-    let conn = get_db().lock();
-    if let Some(original) = content {
-        conn.execute(
-            "UPDATE memos SET title = ?1, formatted_content = ?2, category = ?3, tags = ?4, content = ?5, updated_at = datetime('now') WHERE id = ?6",
-            params![title, formatted_content, category, tags, original, id],
-        )?;
-    } else {
-        conn.execute(
-            "UPDATE memos SET title = ?1, formatted_content = ?2, category = ?3, tags = ?4, updated_at = datetime('now') WHERE id = ?5",
-            params![title, formatted_content, category, tags, id],
-        )?;
+// ===== API 사용량 통계 =====
+// timestamp는 UTC(datetime('now'))로 저장되므로 모든 날짜 비교/구간 나누기는 'localtime'으로 변환 후 수행
+
+// 기간 단위별 SQL 표현식 (week는 월요일 시작)
+fn usage_bucket_expr(period: &str) -> &'static str {
+    match period {
+        "week" => "date(timestamp, 'localtime', 'weekday 0', '-6 days')",
+        "month" => "strftime('%Y-%m', timestamp, 'localtime')",
+        "day" => "date(timestamp, 'localtime')",
+        _ => "''",
     }
-    Ok(())
 }
 
-// 메모 삭제
-pub fn delete_memo(id: i64) -> Result<()> {
+// 그룹 기준별 SQL 표현식
+fn usage_group_expr(group_by: &str) -> &'static str {
+    match group_by {
+        "operation" => "operation",
+        "model" => "model",
+        _ => "''",
+    }
+}
+
+// 기간별/작업별 사용량 집계 (start_date, end_date: 로컬 날짜 YYYY-MM-DD, 양 끝 포함)
+// period: day / week / month / 그 외는 기간 구분 없음, group_by: operation / model / 그 외는 구분 없음
+pub fn get_usage_aggregates(start_date: &str, end_date: &str, period: &str, group_by: &str) -> Result<Vec<UsageAggregate>> {
     let conn = get_db().lock();
-    conn.execute("DELETE FROM memos WHERE id = ?1", params![id])?;
-    Ok(())
+    let sql = format!(
+        "SELECT {bucket} AS bucket, {group} AS group_key, COUNT(*),
+                COALESCE(SUM(CASE WHEN cache_hit = 0 THEN input_tokens ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN cache_hit = 0 THEN output_tokens ELSE 0 END), 0),
+                COALESCE(SUM(cost_usd), 0),
+                COALESCE(SUM(cache_hit), 0),
+                COALESCE(SUM(saved_usd), 0)
+         FROM api_usage
+         WHERE date(timestamp, 'localtime') BETWEEN ?1 AND ?2
+         GROUP BY bucket, group_key
+         ORDER BY bucket, group_key",
+        bucket = usage_bucket_expr(period),
+        group = usage_group_expr(group_by),
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(UsageAggregate {
+            bucket: row.get(0)?,
+            group_key: row.get(1)?,
+            request_count: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            cost_usd: row.get(5)?,
+            cache_hits: row.get(6)?,
+            saved_usd: row.get(7)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(rows)
 }
 
-// ===== 일정 관련 함수 =====
+// 비용 상위 작업 조회
+pub fn get_top_cost_operations(start_date: &str, end_date: &str, limit: i64) -> Result<Vec<UsageAggregate>> {
+    let mut rows = get_usage_aggregates(start_date, end_date, "", "operation")?;
+    rows.sort_by(|a, b| b.cost_usd.partial_cmp(&a.cost_usd).unwrap_or(std::cmp::Ordering::Equal));
+    rows.truncate(limit.max(0) as usize);
+    Ok(rows)
+}
 
-// 일정 저장
+// 기간 내 사용 기록 원본 조회 (CSV 내보내기용)
+pub fn get_api_usage_records(start_date: &str, end_date: &str) -> Result<Vec<ApiUsageRecord>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, datetime(timestamp, 'localtime'), operation, model, input_tokens, output_tokens, cost_usd, cache_hit, saved_usd
+         FROM api_usage
+         WHERE date(timestamp, 'localtime') BETWEEN ?1 AND ?2
+         ORDER BY timestamp, id"
+    )?;
+
+    let records = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(ApiUsageRecord {
+            id: row.get(0)?,
+            timestamp: row.get(1)?,
+            operation: row.get(2)?,
+            model: row.get(3)?,
+            input_tokens: row.get(4)?,
+            output_tokens: row.get(5)?,
+            cost_usd: row.get(6)?,
+            cache_hit: row.get::<_, i64>(7)? != 0,
+            saved_usd: row.get(8)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(records)
+}
+
+// ===== API 예산 관련 함수 =====
+
+// 예산 저장 (같은 operation + period가 있으면 교체)
+pub fn save_api_budget(operation: &str, period: &str, limit_usd: f64, action: &str) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO api_budgets (operation, period, limit_usd, action) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(operation, period) DO UPDATE SET limit_usd = excluded.limit_usd, action = excluded.action",
+        params![operation, period, limit_usd, action],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM api_budgets WHERE operation = ?1 AND period = ?2",
+        params![operation, period],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+// 모든 예산 조회
+pub fn get_all_api_budgets() -> Result<Vec<ApiBudget>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, operation, period, limit_usd, action, created_at FROM api_budgets ORDER BY operation, period"
+    )?;
+
+    let budgets = stmt.query_map([], |row| {
+        Ok(ApiBudget {
+            id: row.get(0)?,
+            operation: row.get(1)?,
+            period: row.get(2)?,
+            limit_usd: row.get(3)?,
+            action: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(budgets)
+}
+
+// 예산 삭제
+pub fn delete_api_budget(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM api_budgets WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 기간 내 사용 금액 조회 (period: daily = 오늘, monthly = 이번 달, 로컬 시간 기준)
+// operation이 '*'이면 전체 합계
+pub fn get_spent_usd(operation: &str, period: &str) -> Result<f64> {
+    let conn = get_db().lock();
+    let period_filter = if period == "monthly" {
+        "strftime('%Y-%m', timestamp, 'localtime') = strftime('%Y-%m', 'now', 'localtime')"
+    } else {
+        "date(timestamp, 'localtime') = date('now', 'localtime')"
+    };
+    let sql = format!(
+        "SELECT COALESCE(SUM(cost_usd), 0) FROM api_usage WHERE {} AND (?1 = '*' OR operation = ?1)",
+        period_filter
+    );
+    let spent: f64 = conn.query_row(&sql, params![operation], |row| row.get(0))?;
+    Ok(spent)
+}
+
+// ===== AI 응답 검증 실패 기록 =====
+
+// 검증 실패 기록 (최근 500건만 유지)
+pub fn log_ai_output_failure(operation: &str, model: &str, error: &str, raw_response: &str, attempt: usize, retried: bool) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO ai_output_failures (operation, model, error, raw_response, attempt, retried) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![operation, model, error, raw_response, attempt as i64, retried as i64],
+    )?;
+    conn.execute(
+        "DELETE FROM ai_output_failures WHERE id NOT IN (SELECT id FROM ai_output_failures ORDER BY id DESC LIMIT 500)",
+        [],
+    )?;
+    Ok(())
+}
+
+// 최근 검증 실패 조회
+pub fn get_ai_output_failures(limit: i64) -> Result<Vec<AiOutputFailure>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, operation, model, error, raw_response, attempt, retried, created_at
+         FROM ai_output_failures ORDER BY id DESC LIMIT ?1"
+    )?;
+
+    let failures = stmt.query_map(params![limit], |row| {
+        Ok(AiOutputFailure {
+            id: row.get(0)?,
+            operation: row.get(1)?,
+            model: row.get(2)?,
+            error: row.get(3)?,
+            raw_response: row.get(4)?,
+            attempt: row.get(5)?,
+            retried: row.get::<_, i64>(6)? != 0,
+            created_at: row.get(7)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(failures)
+}
+
+// 검증 실패 기록 삭제
+pub fn clear_ai_output_failures() -> Result<usize> {
+    let conn = get_db().lock();
+    let count = conn.execute("DELETE FROM ai_output_failures", [])?;
+    Ok(count)
+}
+
+// ===== 모델 가격 관련 함수 =====
+
+fn row_to_model_price(row: &rusqlite::Row) -> Result<ModelPrice> {
+    Ok(ModelPrice {
+        id: row.get(0)?,
+        provider: row.get(1)?,
+        model: row.get(2)?,
+        effective_from: row.get(3)?,
+        input_per_m: row.get(4)?,
+        output_per_m: row.get(5)?,
+        cached_input_per_m: row.get(6)?,
+        reasoning_per_m: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+// 현재 적용 중인 모델 가격 조회 (모델 가격이 없으면 '*' 기본 가격, 제공자 가격이 없으면 '*' 제공자 가격)
+pub fn get_model_price(provider: &str, model: &str) -> Result<Option<ModelPrice>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        "SELECT id, provider, model, effective_from, input_per_m, output_per_m, cached_input_per_m, reasoning_per_m, created_at
+         FROM model_prices
+         WHERE provider IN (?1, '*') AND model IN (?2, '*') AND effective_from <= datetime('now')
+         ORDER BY model = ?2 DESC, provider = ?1 DESC, effective_from DESC
+         LIMIT 1",
+        params![provider, model],
+        row_to_model_price,
+    );
+
+    match result {
+        Ok(price) => Ok(Some(price)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 모든 모델 가격 조회 (이력 포함)
+pub fn get_all_model_prices() -> Result<Vec<ModelPrice>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, provider, model, effective_from, input_per_m, output_per_m, cached_input_per_m, reasoning_per_m, created_at
+         FROM model_prices ORDER BY model, effective_from DESC"
+    )?;
+    let prices = stmt.query_map([], row_to_model_price)?.collect::<Result<Vec<_>>>()?;
+    Ok(prices)
+}
+
+// 모델 가격 저장 (같은 provider + 모델 + 적용 시각이면 교체, effective_from이 비어 있으면 지금부터 적용)
+// 기존 가격을 덮어쓰지 않고 새 적용 시각으로 추가하면 과거 api_usage 비용과 일관성이 유지됨
+pub fn save_model_price(price: &ModelPrice) -> Result<i64> {
+    let conn = get_db().lock();
+    let effective_from: String = if price.effective_from.is_empty() {
+        conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?
+    } else {
+        price.effective_from.clone()
+    };
+    conn.execute(
+        "INSERT INTO model_prices (provider, model, effective_from, input_per_m, output_per_m, cached_input_per_m, reasoning_per_m)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(provider, model, effective_from) DO UPDATE SET
+            input_per_m = excluded.input_per_m,
+            output_per_m = excluded.output_per_m,
+            cached_input_per_m = excluded.cached_input_per_m,
+            reasoning_per_m = excluded.reasoning_per_m",
+        params![
+            price.provider,
+            price.model,
+            effective_from,
+            price.input_per_m,
+            price.output_per_m,
+            price.cached_input_per_m,
+            price.reasoning_per_m,
+        ],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM model_prices WHERE provider = ?1 AND model = ?2 AND effective_from = ?3",
+        params![price.provider, price.model, effective_from],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+// 모델 가격 삭제
+pub fn delete_model_price(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM model_prices WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 전체 메모 삭제
+pub fn delete_all_memos() -> Result<usize> {
+    let conn = get_db().lock();
+    let count = conn.execute("DELETE FROM memos", [])?;
+    Ok(count)
+}
+
+// 메모 전체 업데이트 (편집용)
+pub fn update_memo_full(id: i64, title: &str, formatted_content: &str, category: &str, tags: &str, content: Option<&str>) -> Result<()> {
+    let conn = get_db().lock();
+    if let Some(original) = content {
+        conn.execute(
+            "UPDATE memos SET title = ?1, formatted_content = ?2, category = ?3, tags = ?4, content = ?5, updated_at = datetime('now') WHERE id = ?6",
+            params![title, formatted_content, category, tags, original, id],
+        )?;
+    } else {
+        conn.execute(
+            "UPDATE memos SET title = ?1, formatted_content = ?2, category = ?3, tags = ?4, updated_at = datetime('now') WHERE id = ?5",
+            params![title, formatted_content, category, tags, id],
+        )?;
+    }
+    Ok(())
+}
+
+// 메모 삭제
+pub fn delete_memo(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM memos WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// ===== 일정 관련 함수 =====
+
+// 일정 저장
 pub fn save_schedule(schedule: &Schedule) -> Result<i64> {
     let conn = get_db().lock();
     conn.execute(
-        "INSERT INTO schedules (memo_id, title, start_time, end_time, location, description, google_event_id)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![
-            schedule.memo_id,
-            schedule.title,
-            schedule.start_time,
-            schedule.end_time,
-            schedule.location,
-            schedule.description,
-            schedule.google_event_id
-        ],
+        "INSERT INTO schedules (memo_id, title, start_time, end_time, location, description, google_event_id, recurrence, exdates)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            schedule.memo_id,
+            schedule.title,
+            schedule.start_time,
+            schedule.end_time,
+            schedule.location,
+            schedule.description,
+            schedule.google_event_id,
+            schedule.recurrence,
+            schedule.exdates
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+const SCHEDULE_COLUMNS: &str =
+    "id, memo_id, title, start_time, end_time, location, description, google_event_id, created_at, recurrence, exdates";
+
+fn row_to_schedule(row: &rusqlite::Row) -> Result<Schedule> {
+    Ok(Schedule {
+        id: row.get(0)?,
+        memo_id: row.get(1)?,
+        title: row.get(2)?,
+        start_time: row.get(3)?,
+        end_time: row.get(4)?,
+        location: row.get(5)?,
+        description: row.get(6)?,
+        google_event_id: row.get(7)?,
+        created_at: row.get(8)?,
+        recurrence: row.get(9)?,
+        exdates: row.get(10)?,
+    })
+}
+
+// 모든 일정 조회
+pub fn get_all_schedules() -> Result<Vec<Schedule>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM schedules ORDER BY start_time ASC",
+        SCHEDULE_COLUMNS
+    ))?;
+
+    let schedules = stmt.query_map([], row_to_schedule)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(schedules)
+}
+
+// 일정 조회
+pub fn get_schedule(id: i64) -> Result<Option<Schedule>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM schedules WHERE id = ?1", SCHEDULE_COLUMNS),
+        params![id],
+        row_to_schedule,
+    );
+
+    match result {
+        Ok(schedule) => Ok(Some(schedule)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 반복 규칙 / 제외 날짜 변경
+pub fn update_schedule_recurrence(id: i64, recurrence: Option<&str>, exdates: Option<&str>) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE schedules SET recurrence = ?1, exdates = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![recurrence, exdates, id],
+    )?;
+    Ok(())
+}
+
+// 기간 내 일정 발생 목록 (반복 일정은 전개, window_start 이상 window_end 미만과 겹치는 것)
+// 시각은 모두 로컬 시간 "YYYY-MM-DDTHH:MM" 또는 "YYYY-MM-DD"
+pub fn get_schedule_occurrences(window_start: &str, window_end: &str) -> Result<Vec<ScheduleOccurrence>> {
+    use crate::recurrence;
+
+    let (start, end) = match (recurrence::parse_datetime(window_start), recurrence::parse_datetime(window_end)) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Ok(Vec::new()),
+    };
+
+    // 반복 일정은 시작 시각이 구간 끝 이전인 것 전부, 단일 일정은 구간과 겹치는 것만 후보
+    let candidates = {
+        let conn = get_db().lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM schedules
+             WHERE start_time IS NOT NULL AND start_time < ?2
+               AND ((recurrence IS NOT NULL AND recurrence != '') OR COALESCE(end_time, start_time) >= ?1)
+             ORDER BY start_time ASC",
+            SCHEDULE_COLUMNS
+        ))?;
+        let rows = stmt
+            .query_map(params![start.format("%Y-%m-%d").to_string(), window_end], row_to_schedule)?
+            .collect::<Result<Vec<_>>>()?;
+        rows
+    };
+
+    let mut occurrences = Vec::new();
+    for schedule in candidates {
+        let start_text = schedule.start_time.clone().unwrap_or_default();
+        let dtstart = match recurrence::parse_datetime(&start_text) {
+            Some(dt) => dt,
+            None => continue,
+        };
+        let dtend = schedule.end_time.as_deref().and_then(recurrence::parse_datetime);
+        let duration = dtend.map(|e| e - dtstart).filter(|d| *d > chrono::Duration::zero()).unwrap_or_else(chrono::Duration::zero);
+
+        let rule = schedule
+            .recurrence
+            .as_deref()
+            .filter(|r| !r.trim().is_empty())
+            .and_then(|r| recurrence::parse_rrule(r).ok());
+
+        let starts = match &rule {
+            Some(rule) => {
+                let exdates: Vec<String> = schedule
+                    .exdates
+                    .as_deref()
+                    .unwrap_or("")
+                    .split(',')
+                    .map(|e| e.trim().to_string())
+                    .filter(|e| !e.is_empty())
+                    .collect();
+                recurrence::expand(dtstart, rule, &exdates, duration, start, end)
+            }
+            None if dtstart < end && (dtstart >= start || dtstart + duration > start) => vec![dtstart],
+            None => Vec::new(),
+        };
+
+        for occurrence in starts {
+            let end_template = schedule.end_time.as_deref().unwrap_or(&start_text);
+            occurrences.push(ScheduleOccurrence {
+                occurrence_start: recurrence::format_like(&occurrence, &start_text),
+                occurrence_end: dtend.map(|_| recurrence::format_like(&(occurrence + duration), end_template)),
+                schedule: schedule.clone(),
+            });
+        }
+    }
+
+    occurrences.sort_by(|a, b| a.occurrence_start.cmp(&b.occurrence_start));
+    Ok(occurrences)
+}
+
+// 일정 내용 수정 (iCalendar 가져오기 시 UID가 같은 일정 갱신)
+pub fn update_schedule(schedule: &Schedule) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE schedules SET memo_id = ?1, title = ?2, start_time = ?3, end_time = ?4, location = ?5,
+                description = ?6, recurrence = ?7, exdates = ?8, updated_at = datetime('now')
+         WHERE id = ?9",
+        params![
+            schedule.memo_id,
+            schedule.title,
+            schedule.start_time,
+            schedule.end_time,
+            schedule.location,
+            schedule.description,
+            schedule.recurrence,
+            schedule.exdates,
+            schedule.id
+        ],
+    )?;
+    Ok(())
+}
+
+// 일정의 memo_id 조회
+pub fn get_schedule_memo_id(id: i64) -> Result<Option<i64>> {
+    let conn = get_db().lock();
+    match conn.query_row(
+        "SELECT memo_id FROM schedules WHERE id = ?1",
+        params![id],
+        |row| row.get::<_, Option<i64>>(0),
+    ) {
+        Ok(memo_id) => Ok(memo_id),
+        Err(_) => Ok(None),
+    }
+}
+
+// 일정 삭제 (CalDAV와 동기화된 일정이면 서버에서도 지우도록 기록)
+pub fn delete_schedule(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT OR REPLACE INTO caldav_tombstones (external_id, etag)
+         SELECT google_event_id, external_etag FROM schedules WHERE id = ?1 AND google_event_id IS NOT NULL",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM caldav_conflicts WHERE schedule_id = ?1", params![id])?;
+    conn.execute("DELETE FROM alarms WHERE schedule_id = ?1", params![id])?;
+    conn.execute("DELETE FROM schedules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// ===== 할일 관련 함수 =====
+
+// 할일 저장
+pub fn save_todo(todo: &Todo) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO todos (memo_id, title, completed, priority, due_date, parent_id, project, sort_order,
+                            recurrence, series_id, completed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CASE WHEN ?3 THEN datetime('now') END)",
+        params![
+            todo.memo_id,
+            todo.title,
+            todo.completed as i32,
+            todo.priority,
+            todo.due_date,
+            todo.parent_id,
+            todo.project,
+            todo.sort_order,
+            todo.recurrence,
+            todo.series_id
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+const TODO_COLUMNS: &str =
+    "id, memo_id, title, completed, priority, due_date, created_at, parent_id, project, sort_order, completed_at, \
+     recurrence, series_id";
+
+fn row_to_todo(row: &rusqlite::Row) -> Result<Todo> {
+    Ok(Todo {
+        id: row.get(0)?,
+        memo_id: row.get(1)?,
+        title: row.get(2)?,
+        completed: row.get::<_, i32>(3)? != 0,
+        priority: row.get(4)?,
+        due_date: row.get(5)?,
+        created_at: row.get(6)?,
+        parent_id: row.get(7)?,
+        project: row.get(8)?,
+        sort_order: row.get(9)?,
+        completed_at: row.get(10)?,
+        recurrence: row.get(11)?,
+        series_id: row.get(12)?,
+    })
+}
+
+// 모든 할일 조회 (수동 정렬한 할일이 먼저, 나머지는 마감일 순)
+pub fn get_all_todos() -> Result<Vec<Todo>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM todos
+         ORDER BY completed ASC, sort_order IS NULL, sort_order ASC, due_date ASC, created_at DESC",
+        TODO_COLUMNS
+    ))?;
+
+    let todos = stmt.query_map([], row_to_todo)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(todos)
+}
+
+// 할일 조회
+pub fn get_todo(id: i64) -> Result<Option<Todo>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM todos WHERE id = ?1", TODO_COLUMNS),
+        params![id],
+        row_to_todo,
+    );
+
+    match result {
+        Ok(todo) => Ok(Some(todo)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 하위 할일 전체 (손자 이하 포함)
+pub fn get_todo_descendants(id: i64) -> Result<Vec<Todo>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "WITH RECURSIVE tree(id) AS (
+            SELECT id FROM todos WHERE parent_id = ?1
+            UNION
+            SELECT t.id FROM todos t JOIN tree ON t.parent_id = tree.id
+         )
+         SELECT {} FROM todos WHERE id IN (SELECT id FROM tree)",
+        TODO_COLUMNS
+    ))?;
+
+    let todos = stmt.query_map(params![id], row_to_todo)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(todos)
+}
+
+// 상위 할일 전체 (부모, 조부모, ...)
+pub fn get_todo_ancestors(id: i64) -> Result<Vec<Todo>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "WITH RECURSIVE chain(id) AS (
+            SELECT parent_id FROM todos WHERE id = ?1 AND parent_id IS NOT NULL
+            UNION
+            SELECT t.parent_id FROM todos t JOIN chain ON t.id = chain.id WHERE t.parent_id IS NOT NULL
+         )
+         SELECT {} FROM todos WHERE id IN (SELECT id FROM chain)",
+        TODO_COLUMNS
+    ))?;
+
+    let todos = stmt.query_map(params![id], row_to_todo)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(todos)
+}
+
+// 할일 완료 토글
+pub fn toggle_todo(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE todos SET completed = NOT completed,
+                completed_at = CASE WHEN completed THEN NULL ELSE datetime('now') END
+         WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+// 여러 할일 완료/미완료 처리 (이미 그 상태인 할일의 completed_at은 유지)
+pub fn set_todos_completed(ids: &[i64], completed: bool) -> Result<()> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "UPDATE todos SET completed = ?1,
+                completed_at = CASE WHEN ?1 THEN COALESCE(completed_at, datetime('now')) END
+         WHERE id = ?2",
+    )?;
+    for id in ids {
+        stmt.execute(params![completed as i32, id])?;
+    }
+    Ok(())
+}
+
+// 할일 내용 수정 (iCalendar 가져오기 시 UID가 같은 할일 갱신)
+pub fn update_todo(todo: &Todo) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE todos SET memo_id = ?1, title = ?2, completed = ?3, priority = ?4, due_date = ?5,
+                parent_id = ?6, project = ?7,
+                completed_at = CASE WHEN ?3 THEN COALESCE(completed_at, datetime('now')) END
+         WHERE id = ?8",
+        params![
+            todo.memo_id,
+            todo.title,
+            todo.completed as i32,
+            todo.priority,
+            todo.due_date,
+            todo.parent_id,
+            todo.project,
+            todo.id
+        ],
+    )?;
+    Ok(())
+}
+
+// 상위 할일 변경 (None이면 최상위로)
+pub fn set_todo_parent(id: i64, parent_id: Option<i64>) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE todos SET parent_id = ?1, sort_order = NULL WHERE id = ?2",
+        params![parent_id, id],
+    )?;
+    Ok(())
+}
+
+// 프로젝트 지정 (하위 할일도 같은 프로젝트로)
+pub fn set_todo_project(ids: &[i64], project: Option<&str>) -> Result<()> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare("UPDATE todos SET project = ?1 WHERE id = ?2")?;
+    for id in ids {
+        stmt.execute(params![project, id])?;
+    }
+    Ok(())
+}
+
+// 수동 정렬 (ids 순서대로 0, 1, 2, ...)
+pub fn reorder_todos(ids: &[i64]) -> Result<()> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare("UPDATE todos SET sort_order = ?1 WHERE id = ?2")?;
+    for (index, id) in ids.iter().enumerate() {
+        stmt.execute(params![index as i64, id])?;
+    }
+    Ok(())
+}
+
+// 프로젝트 목록 (할일 수, 미완료 수)
+pub fn get_todo_projects() -> Result<Vec<TodoProject>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT project, COUNT(*), SUM(CASE WHEN completed = 0 THEN 1 ELSE 0 END)
+         FROM todos
+         WHERE project IS NOT NULL AND project != ''
+         GROUP BY project
+         ORDER BY project ASC"
+    )?;
+
+    let projects = stmt.query_map([], |row| {
+        Ok(TodoProject {
+            name: row.get(0)?,
+            total: row.get(1)?,
+            open: row.get(2)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(projects)
+}
+
+// 반복 규칙 지정 (None이면 반복 해제, 완료 기록은 남김)
+pub fn set_todo_recurrence(id: i64, recurrence: Option<&str>) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE todos SET recurrence = ?1, series_id = COALESCE(series_id, id) WHERE id = ?2",
+        params![recurrence, id],
+    )?;
+    Ok(())
+}
+
+// 반복 할일 완료 기록
+pub fn save_todo_completion(series_id: i64, todo: &Todo, on_time: bool) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO todo_completions (series_id, todo_id, title, due_date, on_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![series_id, todo.id, todo.title, todo.due_date, on_time as i32],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// 완료 취소 시 기록도 삭제
+pub fn delete_todo_completion(todo_id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM todo_completions WHERE todo_id = ?1", params![todo_id])?;
+    Ok(())
+}
+
+// 반복 할일 완료 기록 (최근 순, limit 없으면 전체)
+pub fn get_todo_completions(series_id: i64, limit: Option<i64>) -> Result<Vec<TodoCompletion>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, series_id, todo_id, title, due_date, on_time, completed_at
+         FROM todo_completions
+         WHERE series_id = ?1
+         ORDER BY completed_at DESC, id DESC
+         LIMIT ?2"
+    )?;
+
+    let completions = stmt.query_map(params![series_id, limit.unwrap_or(-1)], |row| {
+        Ok(TodoCompletion {
+            id: row.get(0)?,
+            series_id: row.get(1)?,
+            todo_id: row.get(2)?,
+            title: row.get(3)?,
+            due_date: row.get(4)?,
+            on_time: row.get::<_, i32>(5)? != 0,
+            completed_at: row.get(6)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(completions)
+}
+
+// 반복 할일 묶음 ID 목록 (진행 중인 반복 할일 + 완료 기록이 있는 묶음)
+pub fn get_todo_series_ids() -> Result<Vec<i64>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT series_id FROM todos WHERE recurrence IS NOT NULL AND series_id IS NOT NULL
+         UNION
+         SELECT series_id FROM todo_completions
+         ORDER BY 1"
+    )?;
+
+    let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+
+    Ok(ids)
+}
+
+// 반복 할일 묶음의 할일 (미완료 먼저, 최신 순)
+pub fn get_todos_in_series(series_id: i64) -> Result<Vec<Todo>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM todos WHERE series_id = ?1 ORDER BY completed ASC, id DESC",
+        TODO_COLUMNS
+    ))?;
+
+    let todos = stmt.query_map(params![series_id], row_to_todo)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(todos)
+}
+
+// 할일 삭제 (하위 할일도 함께)
+pub fn delete_todo(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "WITH RECURSIVE tree(id) AS (
+            SELECT ?1
+            UNION
+            SELECT t.id FROM todos t JOIN tree ON t.parent_id = tree.id
+         )
+         DELETE FROM todos WHERE id IN (SELECT id FROM tree)",
+        params![id],
+    )?;
+    Ok(())
+}
+
+// ===== iCalendar UID =====
+// table은 "schedules" 또는 "todos"
+
+// UID 조회, 없으면 새로 발급해서 저장 (한 번 발급된 UID는 내보낼 때마다 동일)
+pub fn ensure_ical_uid(table: &str, id: i64) -> Result<String> {
+    let conn = get_db().lock();
+    let existing: Option<String> = conn.query_row(
+        &format!("SELECT ical_uid FROM {} WHERE id = ?1", table),
+        params![id],
+        |row| row.get(0),
+    )?;
+    if let Some(uid) = existing.filter(|u| !u.is_empty()) {
+        return Ok(uid);
+    }
+
+    let uid = format!("{}@jolajoamemo", uuid::Uuid::new_v4());
+    conn.execute(
+        &format!("UPDATE {} SET ical_uid = ?1 WHERE id = ?2", table),
+        params![uid, id],
+    )?;
+    Ok(uid)
+}
+
+// UID 지정 (가져온 항목은 원래 캘린더의 UID 유지)
+pub fn set_ical_uid(table: &str, id: i64, uid: &str) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        &format!("UPDATE {} SET ical_uid = ?1 WHERE id = ?2", table),
+        params![uid, id],
+    )?;
+    Ok(())
+}
+
+// UID로 가져온 항목에 연결된 가져오기 메모 ID 조회
+// 제목 접두어와 태그가 가져오기 메모와 같은 것만 (사용자가 쓴 메모에서 추출된 항목이면 None)
+pub fn find_import_memo_by_ical_uid(table: &str, uid: &str, title_prefix: &str, tags: &str) -> Result<Option<i64>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!(
+            "SELECT t.memo_id FROM {} t JOIN memos m ON m.id = t.memo_id
+             WHERE t.ical_uid = ?1 AND substr(m.title, 1, length(?2)) = ?2 AND m.tags = ?3
+             ORDER BY t.id LIMIT 1",
+            table
+        ),
+        params![uid, title_prefix, tags],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// UID로 항목 ID 조회
+pub fn find_by_ical_uid(table: &str, uid: &str) -> Result<Option<i64>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT id FROM {} WHERE ical_uid = ?1 ORDER BY id LIMIT 1", table),
+        params![uid],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// ===== CalDAV 동기화 =====
+
+// 모든 일정과 동기화 상태
+pub fn get_schedule_sync_states() -> Result<Vec<ScheduleSyncState>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, external_etag, sync_hash, COALESCE(updated_at, created_at), ical_uid
+         FROM schedules ORDER BY id ASC",
+        SCHEDULE_COLUMNS
+    ))?;
+
+    let states = stmt.query_map([], |row| {
+        let schedule = row_to_schedule(row)?;
+        Ok(ScheduleSyncState {
+            external_id: schedule.google_event_id.clone(),
+            schedule,
+            etag: row.get(11)?,
+            sync_hash: row.get(12)?,
+            updated_at: row.get(13)?,
+            ical_uid: row.get(14)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(states)
+}
+
+// 동기화 결과 기록 (서버 href, ETag, 동기화 시점의 내용 해시)
+pub fn set_schedule_sync(id: i64, external_id: Option<&str>, etag: Option<&str>, sync_hash: Option<&str>) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE schedules SET google_event_id = ?1, external_etag = ?2, sync_hash = ?3 WHERE id = ?4",
+        params![external_id, etag, sync_hash, id],
+    )?;
+    Ok(())
+}
+
+// 서버에서 삭제된 일정 지우기 (삭제 기록을 남기지 않음)
+pub fn delete_synced_schedule(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM caldav_conflicts WHERE schedule_id = ?1", params![id])?;
+    conn.execute("DELETE FROM alarms WHERE schedule_id = ?1", params![id])?;
+    conn.execute("DELETE FROM schedules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 서버에서 지워야 할 일정 목록 (href, ETag)
+pub fn get_caldav_tombstones() -> Result<Vec<(String, Option<String>)>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare("SELECT external_id, etag FROM caldav_tombstones ORDER BY deleted_at ASC")?;
+    let tombstones = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(tombstones)
+}
+
+pub fn remove_caldav_tombstone(external_id: &str) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM caldav_tombstones WHERE external_id = ?1", params![external_id])?;
+    Ok(())
+}
+
+// 충돌 기록 (같은 일정의 이전 충돌은 최신 서버 내용으로 교체)
+pub fn save_caldav_conflict(schedule_id: i64, external_id: &str, remote_etag: Option<&str>, remote_ics: &str) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO caldav_conflicts (schedule_id, external_id, remote_etag, remote_ics)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(schedule_id) DO UPDATE SET
+            external_id = excluded.external_id,
+            remote_etag = excluded.remote_etag,
+            remote_ics = excluded.remote_ics,
+            detected_at = datetime('now')",
+        params![schedule_id, external_id, remote_etag, remote_ics],
+    )?;
+    Ok(())
+}
+
+pub fn get_caldav_conflicts() -> Result<Vec<CalDavConflict>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, schedule_id, external_id, remote_etag, remote_ics, detected_at
+         FROM caldav_conflicts ORDER BY detected_at DESC"
+    )?;
+
+    let conflicts = stmt.query_map([], |row| {
+        Ok(CalDavConflict {
+            id: row.get(0)?,
+            schedule_id: row.get(1)?,
+            external_id: row.get(2)?,
+            remote_etag: row.get(3)?,
+            remote_ics: row.get(4)?,
+            detected_at: row.get(5)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(conflicts)
+}
+
+pub fn delete_caldav_conflict(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM caldav_conflicts WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// ===== 페이징 관련 함수 =====
+
+// 메모 페이징 조회
+pub fn get_memos_paginated(offset: i64, limit: i64) -> Result<Vec<Memo>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content, formatted_content, summary, category, tags, embedding, created_at, updated_at
+         FROM memos ORDER BY updated_at DESC LIMIT ?1 OFFSET ?2"
+    )?;
+
+    let memos = stmt.query_map([limit, offset], |row| {
+        Ok(Memo {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            content: row.get(2)?,
+            formatted_content: row.get(3)?,
+            summary: row.get(4)?,
+            category: row.get(5)?,
+            tags: row.get(6)?,
+            embedding: row.get(7)?,
+            created_at: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(memos)
+}
+
+// 전체 메모 개수
+pub fn get_memo_count() -> Result<i64> {
+    let conn = get_db().lock();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM memos", [], |row| row.get(0))?;
+    Ok(count)
+}
+
+// 기존 카테고리 목록 조회 (중복 제거)
+pub fn get_all_categories() -> Result<Vec<String>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT category FROM memos WHERE category != '' ORDER BY category"
+    )?;
+
+    let categories = stmt.query_map([], |row| {
+        row.get::<_, String>(0)
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(categories)
+}
+
+// 카테고리 삭제 (해당 카테고리 메모들의 카테고리를 비움)
+pub fn delete_category(category: &str) -> Result<usize> {
+    let conn = get_db().lock();
+    let count = conn.execute(
+        "UPDATE memos SET category = '' WHERE category = ?1",
+        params![category],
+    )?;
+    Ok(count)
+}
+
+// 카테고리 이름 변경
+pub fn rename_category(old_name: &str, new_name: &str) -> Result<usize> {
+    let conn = get_db().lock();
+    let count = conn.execute(
+        "UPDATE memos SET category = ?1 WHERE category = ?2",
+        params![new_name, old_name],
+    )?;
+    Ok(count)
+}
+
+// ===== 가계부(거래) 관련 함수 =====
+
+// 중복 거래 판별용 지문 (날짜 + 유형 + 금액 + 통화 + 공백/대소문자 정리한 적요)
+pub fn transaction_fingerprint(tx_date: Option<&str>, tx_type: &str, amount: i64, currency: &str, description: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let date: String = tx_date.unwrap_or("").chars().take(10).collect();
+    let description = description.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ");
+    let fields = [date, tx_type.to_string(), amount.to_string(), currency.to_string(), description];
+    Sha256::digest(fields.join("\u{1f}").as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn fingerprint_of(transaction: &Transaction) -> String {
+    transaction_fingerprint(
+        transaction.tx_date.as_deref(),
+        &transaction.tx_type,
+        transaction.amount,
+        &transaction.currency,
+        &transaction.description,
+    )
+}
+
+// 지문이 없는 기존 거래에 지문 채우기
+fn backfill_transaction_fingerprints(conn: &Connection) -> Result<()> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, tx_date, tx_type, amount, currency, description FROM transactions WHERE fingerprint IS NULL"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let tx_date: Option<String> = row.get(1)?;
+            let tx_type: String = row.get(2)?;
+            let currency: String = row.get(4)?;
+            let description: String = row.get(5)?;
+            Ok((row.get(0)?, transaction_fingerprint(tx_date.as_deref(), &tx_type, row.get(3)?, &currency, &description)))
+        })?.collect::<Result<Vec<_>>>()?;
+        rows
+    };
+    let mut stmt = conn.prepare("UPDATE transactions SET fingerprint = ?1 WHERE id = ?2")?;
+    for (id, fingerprint) in rows {
+        stmt.execute(params![fingerprint, id])?;
+    }
+    Ok(())
+}
+
+// 지문별 거래 수 (가져오기 중복 판별용)
+pub fn get_transaction_fingerprint_counts() -> Result<Vec<(String, i64)>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT fingerprint, COUNT(*) FROM transactions WHERE fingerprint IS NOT NULL GROUP BY fingerprint"
+    )?;
+
+    let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<Vec<_>>>()?;
+
+    Ok(counts)
+}
+
+// 거래 저장
+pub fn save_transaction(transaction: &Transaction) -> Result<i64> {
+    let conn = get_db().lock();
+    insert_transaction(&conn, transaction)
+}
+
+// 여러 거래를 한 트랜잭션으로 저장 (중간에 실패하면 하나도 저장하지 않음)
+pub fn save_transactions(transactions: &[Transaction]) -> Result<()> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    for transaction in transactions {
+        insert_transaction(&tx, transaction)?;
+    }
+    tx.commit()
+}
+
+fn insert_transaction(conn: &Connection, transaction: &Transaction) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO transactions (memo_id, tx_type, amount, description, category, tx_date,
+                                   currency, account_id, to_account_id, to_amount, fingerprint)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            transaction.memo_id,
+            transaction.tx_type,
+            transaction.amount,
+            transaction.description,
+            transaction.category,
+            transaction.tx_date,
+            transaction.currency,
+            transaction.account_id,
+            transaction.to_account_id,
+            transaction.to_amount,
+            fingerprint_of(transaction)
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+const TRANSACTION_COLUMNS: &str =
+    "id, memo_id, tx_type, amount, description, category, tx_date, created_at, currency, account_id, to_account_id, to_amount";
+
+fn row_to_transaction(row: &rusqlite::Row) -> Result<Transaction> {
+    Ok(Transaction {
+        id: row.get(0)?,
+        memo_id: row.get(1)?,
+        tx_type: row.get(2)?,
+        amount: row.get(3)?,
+        description: row.get(4)?,
+        category: row.get(5)?,
+        tx_date: row.get(6)?,
+        created_at: row.get(7)?,
+        currency: row.get(8)?,
+        account_id: row.get(9)?,
+        to_account_id: row.get(10)?,
+        to_amount: row.get(11)?,
+    })
+}
+
+// 모든 거래 조회
+pub fn get_all_transactions() -> Result<Vec<Transaction>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions ORDER BY tx_date DESC, created_at DESC",
+        TRANSACTION_COLUMNS
+    ))?;
+
+    let transactions = stmt.query_map([], row_to_transaction)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(transactions)
+}
+
+// 거래 조회
+pub fn get_transaction(id: i64) -> Result<Option<Transaction>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM transactions WHERE id = ?1", TRANSACTION_COLUMNS),
+        params![id],
+        row_to_transaction,
+    );
+
+    match result {
+        Ok(transaction) => Ok(Some(transaction)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 거래의 memo_id 조회
+pub fn get_transaction_memo_id(id: i64) -> Result<Option<i64>> {
+    let conn = get_db().lock();
+    match conn.query_row(
+        "SELECT memo_id FROM transactions WHERE id = ?1",
+        params![id],
+        |row| row.get::<_, Option<i64>>(0),
+    ) {
+        Ok(memo_id) => Ok(memo_id),
+        Err(_) => Ok(None),
+    }
+}
+
+// 거래 삭제
+pub fn delete_transaction(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM transactions WHERE id = ?1", params![id])?;
+    conn.execute("DELETE FROM transaction_receipts WHERE transaction_id = ?1", params![id])?;
+    Ok(())
+}
+
+// 거래 수정
+pub fn update_transaction(transaction: &Transaction) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE transactions SET tx_type = ?1, amount = ?2, description = ?3, category = ?4, tx_date = ?5,
+                currency = ?6, account_id = ?7, to_account_id = ?8, to_amount = ?9, fingerprint = ?10
+         WHERE id = ?11",
+        params![
+            transaction.tx_type,
+            transaction.amount,
+            transaction.description,
+            transaction.category,
+            transaction.tx_date,
+            transaction.currency,
+            transaction.account_id,
+            transaction.to_account_id,
+            transaction.to_amount,
+            fingerprint_of(transaction),
+            transaction.id
+        ],
+    )?;
+    Ok(())
+}
+
+// ===== 계좌/환율 =====
+
+// 기준 통화 (리포트와 예산의 통화)
+pub fn get_base_currency() -> Result<String> {
+    let base = get_setting("base_currency")?.trim().to_uppercase();
+    // SQL에 직접 넣으므로 세 글자 영문 코드만 허용
+    if base.len() == 3 && base.chars().all(|c| c.is_ascii_uppercase()) {
+        Ok(base)
+    } else {
+        Ok(crate::currency::DEFAULT_CURRENCY.to_string())
+    }
+}
+
+// 기준 통화 변경: 새 통화로 바꾼 예산 한도와 함께 한 트랜잭션으로 반영
+// limits: (예산 ID, 새 한도)
+pub fn change_base_currency(code: &str, limits: &[(i64, i64)]) -> Result<()> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    for (id, limit_amount) in limits {
+        tx.execute(
+            "UPDATE transaction_budgets SET limit_amount = ?1 WHERE id = ?2",
+            params![limit_amount, id],
+        )?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES ('base_currency', ?1)",
+        params![code],
+    )?;
+    tx.commit()
+}
+
+// 계좌 저장 (id가 0이면 새로 추가) - 계좌 id 반환
+pub fn save_account(account: &Account) -> Result<i64> {
+    let conn = get_db().lock();
+    if account.id == 0 {
+        conn.execute(
+            "INSERT INTO accounts (name, account_type, currency, opening_balance) VALUES (?1, ?2, ?3, ?4)",
+            params![account.name, account.account_type, account.currency, account.opening_balance],
+        )?;
+        return Ok(conn.last_insert_rowid());
+    }
+    conn.execute(
+        "UPDATE accounts SET name = ?1, account_type = ?2, currency = ?3, opening_balance = ?4 WHERE id = ?5",
+        params![account.name, account.account_type, account.currency, account.opening_balance, account.id],
+    )?;
+    Ok(account.id)
+}
+
+const ACCOUNT_COLUMNS: &str = "id, name, account_type, currency, opening_balance, created_at";
+
+fn row_to_account(row: &rusqlite::Row) -> Result<Account> {
+    Ok(Account {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        account_type: row.get(2)?,
+        currency: row.get(3)?,
+        opening_balance: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+// 모든 계좌 조회
+pub fn get_all_accounts() -> Result<Vec<Account>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM accounts ORDER BY account_type, name", ACCOUNT_COLUMNS))?;
+
+    let accounts = stmt.query_map([], row_to_account)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(accounts)
+}
+
+// 계좌 조회
+pub fn get_account(id: i64) -> Result<Option<Account>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM accounts WHERE id = ?1", ACCOUNT_COLUMNS),
+        params![id],
+        row_to_account,
+    );
+
+    match result {
+        Ok(account) => Ok(Some(account)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 계좌 삭제 (거래는 남기고 계좌 연결만 해제)
+pub fn delete_account(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("UPDATE transactions SET account_id = NULL WHERE account_id = ?1", params![id])?;
+    conn.execute(
+        "UPDATE transactions SET to_account_id = NULL, to_amount = NULL WHERE to_account_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM accounts WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 계좌별 통화별 입출금 합계
+// 수입 +, 지출 -, 이체는 보내는 계좌 -amount / 받는 계좌 +to_amount (없으면 amount, 같은 통화)
+pub fn get_account_flows() -> Result<Vec<AccountFlow>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT account_id, currency, SUM(amount), COUNT(*) FROM (
+            SELECT account_id, currency,
+                   CASE WHEN tx_type = 'income' THEN amount ELSE -amount END AS amount
+            FROM transactions WHERE account_id IS NOT NULL
+            UNION ALL
+            SELECT t.to_account_id,
+                   CASE WHEN t.to_amount IS NOT NULL THEN COALESCE(a.currency, t.currency) ELSE t.currency END,
+                   COALESCE(t.to_amount, t.amount)
+            FROM transactions t LEFT JOIN accounts a ON a.id = t.to_account_id
+            WHERE t.tx_type = 'transfer' AND t.to_account_id IS NOT NULL
+         )
+         GROUP BY account_id, currency"
+    )?;
+
+    let flows = stmt.query_map([], |row| {
+        Ok(AccountFlow {
+            account_id: row.get(0)?,
+            currency: row.get(1)?,
+            amount: row.get(2)?,
+            count: row.get(3)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(flows)
+}
+
+// ===== 자동 분류 규칙 / 가져오기 프로필 =====
+
+// 거래 분류를 고쳤을 때 학습 (같은 패턴이면 분류 교체, 같은 분류면 횟수 증가, 직접 만든 규칙은 건드리지 않음)
+pub fn learn_category_rule(pattern: &str, category: &str) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO category_rules (pattern, category, source) VALUES (?1, ?2, 'learned')
+         ON CONFLICT(pattern) DO UPDATE SET
+            hits = CASE WHEN category = excluded.category THEN hits + 1 ELSE 1 END,
+            category = excluded.category,
+            updated_at = datetime('now')
+         WHERE category_rules.source = 'learned'",
+        params![pattern, category],
+    )?;
+    Ok(())
+}
+
+// 규칙 직접 추가/수정
+pub fn save_category_rule(pattern: &str, category: &str) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO category_rules (pattern, category, source) VALUES (?1, ?2, 'manual')
+         ON CONFLICT(pattern) DO UPDATE SET category = excluded.category, source = 'manual', updated_at = datetime('now')",
+        params![pattern, category],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM category_rules WHERE pattern = ?1",
+        params![pattern],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+// 모든 분류 규칙
+pub fn get_category_rules() -> Result<Vec<CategoryRule>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, pattern, category, hits, source, updated_at FROM category_rules ORDER BY category, pattern"
+    )?;
+
+    let rules = stmt.query_map([], |row| {
+        Ok(CategoryRule {
+            id: row.get(0)?,
+            pattern: row.get(1)?,
+            category: row.get(2)?,
+            hits: row.get(3)?,
+            source: row.get(4)?,
+            updated_at: row.get(5)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(rules)
+}
+
+// 분류 규칙 삭제
+pub fn delete_category_rule(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM category_rules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 가져오기 프로필 저장 (같은 이름이면 교체)
+pub fn save_bank_import_profile(name: &str, mapping: &str) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO bank_import_profiles (name, mapping) VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET mapping = excluded.mapping, updated_at = datetime('now')",
+        params![name, mapping],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM bank_import_profiles WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+fn row_to_bank_import_profile(row: &rusqlite::Row) -> Result<BankImportProfile> {
+    Ok(BankImportProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        mapping: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+// 모든 가져오기 프로필
+pub fn get_bank_import_profiles() -> Result<Vec<BankImportProfile>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare("SELECT id, name, mapping, updated_at FROM bank_import_profiles ORDER BY name")?;
+
+    let profiles = stmt.query_map([], row_to_bank_import_profile)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(profiles)
+}
+
+// 가져오기 프로필 조회
+pub fn get_bank_import_profile(id: i64) -> Result<Option<BankImportProfile>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        "SELECT id, name, mapping, updated_at FROM bank_import_profiles WHERE id = ?1",
+        params![id],
+        row_to_bank_import_profile,
+    );
+
+    match result {
+        Ok(profile) => Ok(Some(profile)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 가져오기 프로필 삭제
+pub fn delete_bank_import_profile(id: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM bank_import_profiles WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// 영수증 정보 저장 (거래당 하나, 다시 저장하면 교체)
+pub fn save_transaction_receipt(receipt: &TransactionReceipt) -> Result<()> {
+    let conn = get_db().lock();
+    let items = serde_json::to_string(&receipt.items).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO transaction_receipts (transaction_id, attachment_id, merchant, items, backend)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(transaction_id) DO UPDATE SET attachment_id = excluded.attachment_id,
+             merchant = excluded.merchant, items = excluded.items, backend = excluded.backend",
+        params![receipt.transaction_id, receipt.attachment_id, receipt.merchant, items, receipt.backend],
+    )?;
+    Ok(())
+}
+
+// 거래의 영수증 정보
+pub fn get_transaction_receipt(transaction_id: i64) -> Result<Option<TransactionReceipt>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        "SELECT transaction_id, attachment_id, merchant, items, backend, created_at
+         FROM transaction_receipts WHERE transaction_id = ?1",
+        params![transaction_id],
+        |row| {
+            let items: String = row.get(3)?;
+            Ok(TransactionReceipt {
+                transaction_id: row.get(0)?,
+                attachment_id: row.get(1)?,
+                merchant: row.get(2)?,
+                items: serde_json::from_str(&items).unwrap_or_default(),
+                backend: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        },
+    );
+
+    match result {
+        Ok(receipt) => Ok(Some(receipt)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 첨부파일로 이미 만든 거래 ID 목록
+pub fn get_receipt_transaction_ids(attachment_id: i64) -> Result<Vec<i64>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT transaction_id FROM transaction_receipts WHERE attachment_id = ?1 ORDER BY transaction_id"
+    )?;
+
+    let ids = stmt.query_map([attachment_id], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+
+    Ok(ids)
+}
+
+// 환율 저장 (같은 통화/기준 통화/날짜면 교체)
+pub fn save_exchange_rate(currency: &str, base_currency: &str, rate: f64, rate_date: &str) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO exchange_rates (currency, base_currency, rate, rate_date) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(currency, base_currency, rate_date) DO UPDATE SET rate = excluded.rate, updated_at = datetime('now')",
+        params![currency, base_currency, rate, rate_date],
     )?;
-    Ok(conn.last_insert_rowid())
+    let id: i64 = conn.query_row(
+        "SELECT id FROM exchange_rates WHERE currency = ?1 AND base_currency = ?2 AND rate_date = ?3",
+        params![currency, base_currency, rate_date],
+        |row| row.get(0),
+    )?;
+    Ok(id)
 }
 
-// 모든 일정 조회
-pub fn get_all_schedules() -> Result<Vec<Schedule>> {
+// 모든 환율 조회 (통화별 최신 순)
+pub fn get_exchange_rates() -> Result<Vec<ExchangeRate>> {
     let conn = get_db().lock();
     let mut stmt = conn.prepare(
-        "SELECT id, memo_id, title, start_time, end_time, location, description, google_event_id, created_at
-         FROM schedules ORDER BY start_time ASC"
+        "SELECT id, currency, base_currency, rate, rate_date, updated_at
+         FROM exchange_rates ORDER BY base_currency, currency, rate_date DESC"
     )?;
 
-    let schedules = stmt.query_map([], |row| {
-        Ok(Schedule {
+    let rates = stmt.query_map([], |row| {
+        Ok(ExchangeRate {
             id: row.get(0)?,
-            memo_id: row.get(1)?,
-            title: row.get(2)?,
-            start_time: row.get(3)?,
-            end_time: row.get(4)?,
-            location: row.get(5)?,
-            description: row.get(6)?,
-            google_event_id: row.get(7)?,
-            created_at: row.get(8)?,
+            currency: row.get(1)?,
+            base_currency: row.get(2)?,
+            rate: row.get(3)?,
+            rate_date: row.get(4)?,
+            updated_at: row.get(5)?,
         })
     })?.collect::<Result<Vec<_>>>()?;
 
-    Ok(schedules)
+    Ok(rates)
 }
 
-// 일정의 memo_id 조회
-pub fn get_schedule_memo_id(id: i64) -> Result<Option<i64>> {
+// 환율 삭제
+pub fn delete_exchange_rate(id: i64) -> Result<()> {
     let conn = get_db().lock();
-    match conn.query_row(
-        "SELECT memo_id FROM schedules WHERE id = ?1",
-        params![id],
-        |row| row.get::<_, Option<i64>>(0),
-    ) {
-        Ok(memo_id) => Ok(memo_id),
-        Err(_) => Ok(None),
-    }
+    conn.execute("DELETE FROM exchange_rates WHERE id = ?1", params![id])?;
+    Ok(())
 }
 
-// 일정 삭제
-pub fn delete_schedule(id: i64) -> Result<()> {
+// 기준 통화 대비 환율 (date 이전 가장 최근 값, 없으면 가장 오래된 값, 기준 통화 자신은 1.0)
+pub fn get_exchange_rate(currency: &str, base_currency: &str, date: &str) -> Result<Option<f64>> {
+    if currency == base_currency {
+        return Ok(Some(1.0));
+    }
     let conn = get_db().lock();
-    conn.execute("DELETE FROM schedules WHERE id = ?1", params![id])?;
-    Ok(())
+    let result = conn.query_row(
+        "SELECT rate FROM exchange_rates
+         WHERE currency = ?1 AND base_currency = ?2
+         ORDER BY rate_date <= ?3 DESC,
+                  CASE WHEN rate_date <= ?3 THEN rate_date END DESC,
+                  rate_date ASC
+         LIMIT 1",
+        params![currency, base_currency, date],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(rate) => Ok(Some(rate)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
 }
 
-// ===== 할일 관련 함수 =====
+// 기준 통화로 환산 (환율이 없으면 None)
+pub fn convert_to_base(amount: i64, currency: &str, date: &str) -> Result<Option<i64>> {
+    let base = get_base_currency()?;
+    let rate = get_exchange_rate(currency, &base, date)?;
+    Ok(rate.map(|rate| crate::currency::convert(amount, currency, &base, rate, 1.0)))
+}
 
-// 할일 저장
-pub fn save_todo(todo: &Todo) -> Result<i64> {
-    let conn = get_db().lock();
-    conn.execute(
-        "INSERT INTO todos (memo_id, title, completed, priority, due_date) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![
-            todo.memo_id,
-            todo.title,
-            todo.completed as i32,
-            todo.priority,
-            todo.due_date
-        ],
-    )?;
-    Ok(conn.last_insert_rowid())
+// ===== 가계부 카테고리/예산/리포트 =====
+
+// 거래 날짜 (tx_date가 없거나 잘못된 값이면 등록일, 로컬 날짜 YYYY-MM-DD)
+const TX_DATE: &str = "COALESCE(date(t.tx_date), date(t.created_at, 'localtime'))";
+
+// 카테고리 (비어 있으면 미분류)
+const TX_CATEGORY: &str = "COALESCE(NULLIF(TRIM(t.category), ''), '미분류')";
+
+// 지출로 보는 거래 (수입과 이체 외 전부)
+const IS_EXPENSE: &str = "tx_type NOT IN ('income', 'transfer')";
+
+// 리포트용 거래 목록 (WITH 절): 날짜/카테고리 정리 + 기준 통화 환산 금액 (환율이 없으면 base_amount NULL)
+// 환율은 거래일 이전 가장 최근 값, 없으면 가장 오래된 값
+fn ledger_cte(base: &str) -> String {
+    let rate_lookup = |order: &str, filter: &str| {
+        format!(
+            "(SELECT r.rate FROM exchange_rates r
+              WHERE r.currency = t.currency AND r.base_currency = '{base}' {filter}
+              ORDER BY r.rate_date {order} LIMIT 1)",
+            base = base,
+            filter = filter,
+            order = order,
+        )
+    };
+    format!(
+        "ledger AS (
+            SELECT t.id, t.tx_type, t.currency, {date} AS tx_day, {category} AS category,
+                   CAST(ROUND(t.amount * {scale} * CASE WHEN t.currency = '{base}' THEN 1.0
+                        ELSE COALESCE({before}, {after}) END) AS INTEGER) AS base_amount
+            FROM transactions t
+         )",
+        date = TX_DATE,
+        category = TX_CATEGORY,
+        scale = crate::currency::scale_sql("t.currency", base),
+        base = base,
+        before = rate_lookup("DESC", &format!("AND r.rate_date <= {}", TX_DATE)),
+        after = rate_lookup("ASC", ""),
+    )
 }
 
-// 모든 할일 조회
-pub fn get_all_todos() -> Result<Vec<Todo>> {
+// 기간 단위별 SQL 표현식 (주는 월요일 시작)
+fn tx_bucket_expr(period: &str) -> &'static str {
+    match period {
+        "week" => "date(tx_day, 'weekday 0', '-6 days')",
+        "month" => "strftime('%Y-%m', tx_day)",
+        "day" => "tx_day",
+        _ => "''",
+    }
+}
+
+// 그룹 기준별 SQL 표현식
+fn tx_group_expr(group_by: &str) -> &'static str {
+    match group_by {
+        "category" => "category",
+        "type" => "tx_type",
+        _ => "''",
+    }
+}
+
+// 기간별/카테고리별 수입·지출 집계 (기준 통화로 환산, start_date, end_date: YYYY-MM-DD, 양 끝 포함)
+// period: day / week / month / 그 외는 기간 구분 없음, group_by: category / type / 그 외는 구분 없음
+// 이체는 수입/지출이 아니므로 제외
+pub fn get_transaction_aggregates(start_date: &str, end_date: &str, period: &str, group_by: &str) -> Result<Vec<TransactionAggregate>> {
+    let base = get_base_currency()?;
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT id, memo_id, title, completed, priority, due_date, created_at
-         FROM todos ORDER BY completed ASC, due_date ASC, created_at DESC"
-    )?;
+    let sql = format!(
+        "WITH {ledger}
+         SELECT {bucket} AS bucket, {group} AS group_key,
+                COALESCE(SUM(CASE WHEN tx_type = 'income' THEN base_amount ELSE 0 END), 0) AS income,
+                COALESCE(SUM(CASE WHEN {expense} THEN base_amount ELSE 0 END), 0) AS expense,
+                COUNT(*),
+                SUM(CASE WHEN base_amount IS NULL THEN 1 ELSE 0 END)
+         FROM ledger
+         WHERE tx_type != 'transfer' AND tx_day BETWEEN ?1 AND ?2
+         GROUP BY bucket, group_key
+         ORDER BY bucket, expense DESC, group_key",
+        ledger = ledger_cte(&base),
+        bucket = tx_bucket_expr(period),
+        group = tx_group_expr(group_by),
+        expense = IS_EXPENSE,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        let income: i64 = row.get(2)?;
+        let expense: i64 = row.get(3)?;
+        Ok(TransactionAggregate {
+            bucket: row.get(0)?,
+            group_key: row.get(1)?,
+            income,
+            expense,
+            net: income - expense,
+            count: row.get(4)?,
+            unconverted: row.get(5)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
 
-    let todos = stmt.query_map([], |row| {
-        Ok(Todo {
-            id: row.get(0)?,
-            memo_id: row.get(1)?,
-            title: row.get(2)?,
-            completed: row.get::<_, i32>(3)? != 0,
-            priority: row.get(4)?,
-            due_date: row.get(5)?,
-            created_at: row.get(6)?,
+    Ok(rows)
+}
+
+// 기간별 누적 잔액 (기준 통화, 시작일 이전 거래의 합계를 기초 잔액으로, 이체 제외)
+pub fn get_transaction_balance(start_date: &str, end_date: &str, period: &str) -> Result<Vec<BalancePoint>> {
+    let base = get_base_currency()?;
+    let conn = get_db().lock();
+    let sql = format!(
+        "WITH {ledger},
+         opening AS (
+            SELECT COALESCE(SUM(CASE WHEN tx_type = 'income' THEN base_amount ELSE -base_amount END), 0) AS amount
+            FROM ledger
+            WHERE tx_type != 'transfer' AND tx_day < ?1
+         ),
+         buckets AS (
+            SELECT {bucket} AS bucket,
+                   COALESCE(SUM(CASE WHEN tx_type = 'income' THEN base_amount ELSE 0 END), 0) AS income,
+                   COALESCE(SUM(CASE WHEN {expense} THEN base_amount ELSE 0 END), 0) AS expense
+            FROM ledger
+            WHERE tx_type != 'transfer' AND tx_day BETWEEN ?1 AND ?2
+            GROUP BY bucket
+         )
+         SELECT bucket, income, expense, income - expense,
+                (SELECT amount FROM opening) + SUM(income - expense) OVER (ORDER BY bucket)
+         FROM buckets
+         ORDER BY bucket",
+        ledger = ledger_cte(&base),
+        bucket = tx_bucket_expr(period),
+        expense = IS_EXPENSE,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(BalancePoint {
+            bucket: row.get(0)?,
+            income: row.get(1)?,
+            expense: row.get(2)?,
+            net: row.get(3)?,
+            balance: row.get(4)?,
         })
     })?.collect::<Result<Vec<_>>>()?;
 
-    Ok(todos)
+    Ok(rows)
 }
 
-// 할일 완료 토글
-pub fn toggle_todo(id: i64) -> Result<()> {
+// 카테고리 등록/수정 (같은 이름이면 유형과 색상 갱신)
+pub fn save_transaction_category(name: &str, tx_type: &str, color: Option<&str>) -> Result<()> {
     let conn = get_db().lock();
     conn.execute(
-        "UPDATE todos SET completed = NOT completed WHERE id = ?1",
-        params![id],
+        "INSERT INTO transaction_categories (name, tx_type, color) VALUES (?1, ?2, ?3)
+         ON CONFLICT(name) DO UPDATE SET tx_type = excluded.tx_type, color = excluded.color",
+        params![name, tx_type, color],
     )?;
     Ok(())
 }
 
-// 할일의 memo_id 조회
-pub fn get_todo_memo_id(id: i64) -> Result<Option<i64>> {
+// 카테고리 목록 (등록한 카테고리 + 거래에 쓰인 카테고리, 거래 수/금액 포함)
+pub fn get_transaction_categories() -> Result<Vec<TransactionCategory>> {
+    let base = get_base_currency()?;
     let conn = get_db().lock();
-    match conn.query_row(
-        "SELECT memo_id FROM todos WHERE id = ?1",
-        params![id],
-        |row| row.get::<_, Option<i64>>(0),
-    ) {
-        Ok(memo_id) => Ok(memo_id),
-        Err(_) => Ok(None),
+    let sql = format!(
+        "WITH {ledger},
+         used AS (
+            SELECT category AS name, COUNT(*) AS cnt, SUM(base_amount) AS total
+            FROM ledger
+            WHERE tx_type != 'transfer'
+            GROUP BY name
+         ),
+         names AS (
+            SELECT name FROM transaction_categories
+            UNION
+            SELECT name FROM used
+         )
+         SELECT n.name, c.tx_type, c.color, c.id IS NOT NULL, COALESCE(u.cnt, 0), COALESCE(u.total, 0)
+         FROM names n
+         LEFT JOIN transaction_categories c ON c.name = n.name
+         LEFT JOIN used u ON u.name = n.name
+         ORDER BY COALESCE(u.cnt, 0) DESC, n.name ASC",
+        ledger = ledger_cte(&base),
+    );
+    let mut stmt = conn.prepare(&sql)?;
+
+    let categories = stmt.query_map([], |row| {
+        Ok(TransactionCategory {
+            name: row.get(0)?,
+            tx_type: row.get(1)?,
+            color: row.get(2)?,
+            registered: row.get(3)?,
+            transaction_count: row.get(4)?,
+            total_amount: row.get(5)?,
+        })
+    })?.collect::<Result<Vec<_>>>()?;
+
+    Ok(categories)
+}
+
+// 카테고리 이름 변경 (거래와 예산도 함께, 이미 있는 이름이면 합쳐짐) - 변경된 거래 수 반환
+pub fn rename_transaction_category(old_name: &str, new_name: &str) -> Result<usize> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    let count = tx.execute(
+        &format!("UPDATE transactions AS t SET category = ?2 WHERE {} = ?1", TX_CATEGORY),
+        params![old_name, new_name],
+    )?;
+    tx.execute(
+        "UPDATE OR REPLACE transaction_budgets SET category = ?2 WHERE category = ?1",
+        params![old_name, new_name],
+    )?;
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM transaction_categories WHERE name = ?1",
+        params![new_name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        tx.execute("DELETE FROM transaction_categories WHERE name = ?1", params![old_name])?;
+    } else {
+        tx.execute("UPDATE transaction_categories SET name = ?2 WHERE name = ?1", params![old_name, new_name])?;
     }
+    tx.commit()?;
+    Ok(count)
 }
 
-// 할일 삭제
-pub fn delete_todo(id: i64) -> Result<()> {
+// 카테고리 등록 해제 (거래의 카테고리는 그대로)
+pub fn delete_transaction_category(name: &str) -> Result<()> {
     let conn = get_db().lock();
-    conn.execute("DELETE FROM todos WHERE id = ?1", params![id])?;
+    conn.execute("DELETE FROM transaction_categories WHERE name = ?1", params![name])?;
     Ok(())
 }
 
-// ===== 페이징 관련 함수 =====
+// 예산 저장 (같은 카테고리 + 기간이 있으면 교체)
+pub fn save_transaction_budget(category: &str, period: &str, limit_amount: i64) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO transaction_budgets (category, period, limit_amount) VALUES (?1, ?2, ?3)
+         ON CONFLICT(category, period) DO UPDATE SET limit_amount = excluded.limit_amount",
+        params![category, period, limit_amount],
+    )?;
+    let id: i64 = conn.query_row(
+        "SELECT id FROM transaction_budgets WHERE category = ?1 AND period = ?2",
+        params![category, period],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
 
-// 메모 페이징 조회
-pub fn get_memos_paginated(offset: i64, limit: i64) -> Result<Vec<Memo>> {
+// 모든 가계부 예산 조회
+pub fn get_all_transaction_budgets() -> Result<Vec<TransactionBudget>> {
     let conn = get_db().lock();
     let mut stmt = conn.prepare(
-        "SELECT id, title, content, formatted_content, summary, category, tags, embedding, created_at, updated_at
-         FROM memos ORDER BY updated_at DESC LIMIT ?1 OFFSET ?2"
+        "SELECT id, category, period, limit_amount, created_at FROM transaction_budgets ORDER BY category = '*' DESC, category, period"
     )?;
 
-    let memos = stmt.query_map([limit, offset], |row| {
-        Ok(Memo {
+    let budgets = stmt.query_map([], |row| {
+        Ok(TransactionBudget {
             id: row.get(0)?,
-            title: row.get(1)?,
-            content: row.get(2)?,
-            formatted_content: row.get(3)?,
-            summary: row.get(4)?,
-            category: row.get(5)?,
-            tags: row.get(6)?,
-            embedding: row.get(7)?,
-            created_at: row.get(8)?,
-            updated_at: row.get(9)?,
+            category: row.get(1)?,
+            period: row.get(2)?,
+            limit_amount: row.get(3)?,
+            created_at: row.get(4)?,
         })
     })?.collect::<Result<Vec<_>>>()?;
 
-    Ok(memos)
+    Ok(budgets)
 }
 
-// 전체 메모 개수
-pub fn get_memo_count() -> Result<i64> {
+// 가계부 예산 삭제
+pub fn delete_transaction_budget(id: i64) -> Result<()> {
     let conn = get_db().lock();
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM memos", [], |row| row.get(0))?;
-    Ok(count)
+    conn.execute("DELETE FROM transaction_budgets WHERE id = ?1", params![id])?;
+    Ok(())
 }
 
-// 기존 카테고리 목록 조회 (중복 제거)
-pub fn get_all_categories() -> Result<Vec<String>> {
+// 예산 기간의 지출 합계(기준 통화)와 기간 시작일 (date가 속한 달/주, period: monthly / weekly)
+// category가 '*'이면 전체 지출
+pub fn get_transaction_spent(category: &str, period: &str, date: &str) -> Result<(i64, String)> {
+    let base = get_base_currency()?;
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT DISTINCT category FROM memos WHERE category != '' ORDER BY category"
-    )?;
+    let bucket = tx_bucket_expr(if period == "weekly" { "week" } else { "month" });
+    let target = if period == "weekly" {
+        "date(?2, 'weekday 0', '-6 days')"
+    } else {
+        "strftime('%Y-%m', ?2)"
+    };
+    let sql = format!(
+        "WITH {ledger}
+         SELECT COALESCE(SUM(base_amount), 0), {target}
+         FROM ledger
+         WHERE {expense} AND {bucket} = {target} AND (?1 = '*' OR category = ?1)",
+        ledger = ledger_cte(&base),
+        target = target,
+        bucket = bucket,
+        expense = IS_EXPENSE,
+    );
+    let (spent, start): (i64, Option<String>) = conn.query_row(&sql, params![category, date], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok((spent, start.unwrap_or_default()))
+}
 
-    let categories = stmt.query_map([], |row| {
-        row.get::<_, String>(0)
-    })?.collect::<Result<Vec<_>>>()?;
+// ===== 메모 연결 항목 삭제 (재분석용) =====
 
-    Ok(categories)
+// 메모에 연결된 일정 삭제
+pub fn delete_schedules_by_memo_id(memo_id: i64) -> Result<usize> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT OR REPLACE INTO caldav_tombstones (external_id, etag)
+         SELECT google_event_id, external_etag FROM schedules WHERE memo_id = ?1 AND google_event_id IS NOT NULL",
+        params![memo_id],
+    )?;
+    conn.execute(
+        "DELETE FROM caldav_conflicts WHERE schedule_id IN (SELECT id FROM schedules WHERE memo_id = ?1)",
+        params![memo_id],
+    )?;
+    conn.execute(
+        "DELETE FROM alarms WHERE schedule_id IN (SELECT id FROM schedules WHERE memo_id = ?1)",
+        params![memo_id],
+    )?;
+    let count = conn.execute("DELETE FROM schedules WHERE memo_id = ?1", params![memo_id])?;
+    Ok(count)
 }
 
-// 카테고리 삭제 (해당 카테고리 메모들의 카테고리를 비움)
-pub fn delete_category(category: &str) -> Result<usize> {
+// 메모에 연결된 할일 삭제
+pub fn delete_todos_by_memo_id(memo_id: i64) -> Result<usize> {
     let conn = get_db().lock();
-    let count = conn.execute(
-        "UPDATE memos SET category = '' WHERE category = ?1",
-        params![category],
-    )?;
+    let count = conn.execute("DELETE FROM todos WHERE memo_id = ?1", params![memo_id])?;
     Ok(count)
 }
 
-// 카테고리 이름 변경
-pub fn rename_category(old_name: &str, new_name: &str) -> Result<usize> {
+// 메모에 연결된 거래 삭제
+pub fn delete_transactions_by_memo_id(memo_id: i64) -> Result<usize> {
     let conn = get_db().lock();
-    let count = conn.execute(
-        "UPDATE memos SET category = ?1 WHERE category = ?2",
-        params![new_name, old_name],
+    conn.execute(
+        "DELETE FROM transaction_receipts WHERE transaction_id IN (SELECT id FROM transactions WHERE memo_id = ?1)",
+        params![memo_id],
     )?;
+    let count = conn.execute("DELETE FROM transactions WHERE memo_id = ?1", params![memo_id])?;
     Ok(count)
 }
 
-// ===== 가계부(거래) 관련 함수 =====
+// ===== 첨부파일 관련 함수 =====
 
-// 거래 저장
-pub fn save_transaction(transaction: &Transaction) -> Result<i64> {
+const ATTACHMENT_COLUMNS: &str = "id, memo_id, file_name, file_path, original_path, is_copy, file_size, created_at, sha256";
+
+fn row_to_attachment(row: &rusqlite::Row) -> Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        memo_id: row.get(1)?,
+        file_name: row.get(2)?,
+        file_path: row.get(3)?,
+        original_path: row.get(4)?,
+        is_copy: row.get::<_, i32>(5)? != 0,
+        file_size: row.get(6)?,
+        created_at: row.get(7)?,
+        sha256: row.get(8)?,
+    })
+}
+
+// 첨부파일 저장
+pub fn save_attachment(attachment: &Attachment) -> Result<i64> {
     let conn = get_db().lock();
     conn.execute(
-        "INSERT INTO transactions (memo_id, tx_type, amount, description, category, tx_date)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO attachments (memo_id, file_name, file_path, original_path, is_copy, file_size, sha256)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![
-            transaction.memo_id,
-            transaction.tx_type,
-            transaction.amount,
-            transaction.description,
-            transaction.category,
-            transaction.tx_date
+            attachment.memo_id,
+            attachment.file_name,
+            attachment.file_path,
+            attachment.original_path,
+            attachment.is_copy as i32,
+            attachment.file_size,
+            attachment.sha256
         ],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
-// 모든 거래 조회
-pub fn get_all_transactions() -> Result<Vec<Transaction>> {
+// 첨부파일 조회
+pub fn get_attachment(id: i64) -> Result<Option<Attachment>> {
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT id, memo_id, tx_type, amount, description, category, tx_date, created_at
-         FROM transactions ORDER BY tx_date DESC, created_at DESC"
-    )?;
+    let result = conn.query_row(
+        &format!("SELECT {} FROM attachments WHERE id = ?1", ATTACHMENT_COLUMNS),
+        params![id],
+        row_to_attachment,
+    );
 
-    let transactions = stmt.query_map([], |row| {
-        Ok(Transaction {
-            id: row.get(0)?,
-            memo_id: row.get(1)?,
-            tx_type: row.get(2)?,
-            amount: row.get(3)?,
-            description: row.get(4)?,
-            category: row.get(5)?,
-            tx_date: row.get(6)?,
-            created_at: row.get(7)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    match result {
+        Ok(attachment) => Ok(Some(attachment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
 
-    Ok(transactions)
+// 메모별 첨부파일 조회
+pub fn get_attachments_by_memo(memo_id: i64) -> Result<Vec<Attachment>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments WHERE memo_id = ?1 ORDER BY created_at DESC",
+        ATTACHMENT_COLUMNS
+    ))?;
+
+    let attachments = stmt.query_map([memo_id], row_to_attachment)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(attachments)
 }
 
-// 거래의 memo_id 조회
-pub fn get_transaction_memo_id(id: i64) -> Result<Option<i64>> {
+// 첨부파일 삭제 (영수증으로 만든 거래는 남기고 연결만 해제)
+pub fn delete_attachment(id: i64) -> Result<Attachment> {
     let conn = get_db().lock();
-    match conn.query_row(
-        "SELECT memo_id FROM transactions WHERE id = ?1",
+    // 먼저 첨부파일 정보 조회 (파일 삭제용)
+    let attachment = conn.query_row(
+        &format!("SELECT {} FROM attachments WHERE id = ?1", ATTACHMENT_COLUMNS),
         params![id],
-        |row| row.get::<_, Option<i64>>(0),
-    ) {
-        Ok(memo_id) => Ok(memo_id),
-        Err(_) => Ok(None),
-    }
+        row_to_attachment,
+    )?;
+    conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])?;
+    conn.execute("DELETE FROM attachment_texts WHERE attachment_id = ?1", params![id])?;
+    conn.execute("DELETE FROM attachment_text_fts WHERE rowid = ?1", params![id])?;
+    conn.execute("DELETE FROM attachment_previews WHERE attachment_id = ?1", params![id])?;
+    conn.execute("UPDATE transaction_receipts SET attachment_id = NULL WHERE attachment_id = ?1", params![id])?;
+    Ok(attachment)
 }
 
-// 거래 삭제
-pub fn delete_transaction(id: i64) -> Result<()> {
+// 첨부파일 검색 (파일명으로)
+pub fn search_attachments(query: &str) -> Result<Vec<Attachment>> {
     let conn = get_db().lock();
-    conn.execute("DELETE FROM transactions WHERE id = ?1", params![id])?;
+    let search_pattern = format!("%{}%", query);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments WHERE file_name LIKE ?1 ORDER BY created_at DESC",
+        ATTACHMENT_COLUMNS
+    ))?;
+
+    let attachments = stmt.query_map([&search_pattern], row_to_attachment)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(attachments)
+}
+
+// 첨부파일 위치/내용 정보 갱신 (옮겨진 파일 다시 연결, 바뀐 내용 승인, 해시 채우기)
+pub fn update_attachment_file(attachment: &Attachment) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE attachments SET file_path = ?1, original_path = ?2, file_size = ?3, sha256 = ?4 WHERE id = ?5",
+        params![attachment.file_path, attachment.original_path, attachment.file_size, attachment.sha256, attachment.id],
+    )?;
     Ok(())
 }
 
-// 거래 수정
-pub fn update_transaction(
-    id: i64,
-    tx_type: &str,
-    amount: i64,
-    description: &str,
-    category: Option<&str>,
-    tx_date: Option<&str>,
-) -> Result<()> {
+// 같은 저장 파일을 쓰는 첨부파일 수 (복사 모드는 같은 내용을 한 파일로 공유)
+pub fn count_attachments_by_path(file_path: &str) -> Result<i64> {
+    let conn = get_db().lock();
+    conn.query_row(
+        "SELECT COUNT(*) FROM attachments WHERE file_path = ?1",
+        params![file_path],
+        |row| row.get(0),
+    )
+}
+
+// 첨부파일 미리보기 저장
+pub fn save_attachment_preview(preview: &AttachmentPreview) -> Result<()> {
     let conn = get_db().lock();
     conn.execute(
-        "UPDATE transactions SET tx_type = ?1, amount = ?2, description = ?3, category = ?4, tx_date = ?5 WHERE id = ?6",
-        params![tx_type, amount, description, category, tx_date, id],
+        "INSERT INTO attachment_previews (attachment_id, sha256, thumbnail_path, width, height, taken_at, camera, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))
+         ON CONFLICT(attachment_id) DO UPDATE SET sha256 = excluded.sha256, thumbnail_path = excluded.thumbnail_path,
+             width = excluded.width, height = excluded.height, taken_at = excluded.taken_at, camera = excluded.camera,
+             error = excluded.error, created_at = excluded.created_at",
+        params![
+            preview.attachment_id,
+            preview.sha256,
+            preview.thumbnail_path,
+            preview.width,
+            preview.height,
+            preview.taken_at,
+            preview.camera,
+            preview.error
+        ],
     )?;
     Ok(())
 }
 
-// ===== 메모 연결 항목 삭제 (재분석용) =====
+const ATTACHMENT_PREVIEW_COLUMNS: &str =
+    "p.attachment_id, p.sha256, p.thumbnail_path, p.width, p.height, p.taken_at, p.camera, p.error, p.created_at";
+
+fn row_to_attachment_preview(row: &rusqlite::Row) -> Result<AttachmentPreview> {
+    let thumbnail_path: Option<String> = row.get(2)?;
+    Ok(AttachmentPreview {
+        attachment_id: row.get(0)?,
+        sha256: row.get(1)?,
+        thumbnail_url: thumbnail_path.as_deref().map(crate::thumbnail::asset_url),
+        thumbnail_path,
+        width: row.get(3)?,
+        height: row.get(4)?,
+        taken_at: row.get(5)?,
+        camera: row.get(6)?,
+        error: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
 
-// 메모에 연결된 일정 삭제
-pub fn delete_schedules_by_memo_id(memo_id: i64) -> Result<usize> {
+// 첨부파일 미리보기 조회
+pub fn get_attachment_preview(attachment_id: i64) -> Result<Option<AttachmentPreview>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM attachment_previews p WHERE p.attachment_id = ?1", ATTACHMENT_PREVIEW_COLUMNS),
+        params![attachment_id],
+        row_to_attachment_preview,
+    );
+
+    match result {
+        Ok(preview) => Ok(Some(preview)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 미리보기가 쓰는 썸네일 파일 경로 (저장소 정리용)
+pub fn get_thumbnail_paths() -> Result<Vec<String>> {
     let conn = get_db().lock();
-    let count = conn.execute("DELETE FROM schedules WHERE memo_id = ?1", params![memo_id])?;
-    Ok(count)
+    let mut stmt = conn.prepare("SELECT DISTINCT thumbnail_path FROM attachment_previews WHERE thumbnail_path IS NOT NULL")?;
+
+    let paths = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+
+    Ok(paths)
 }
 
-// 메모에 연결된 할일 삭제
-pub fn delete_todos_by_memo_id(memo_id: i64) -> Result<usize> {
+// 메모가 없는 첨부파일 (외래 키 제약을 켜지 않아 CASCADE가 동작하지 않으므로, 첨부파일을 함께 지우기 전에 삭제된 메모의 행)
+pub fn get_orphan_attachments() -> Result<Vec<Attachment>> {
     let conn = get_db().lock();
-    let count = conn.execute("DELETE FROM todos WHERE memo_id = ?1", params![memo_id])?;
-    Ok(count)
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments WHERE memo_id NOT IN (SELECT id FROM memos) ORDER BY id",
+        ATTACHMENT_COLUMNS
+    ))?;
+
+    let attachments = stmt.query_map([], row_to_attachment)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(attachments)
 }
 
-// 메모에 연결된 거래 삭제
-pub fn delete_transactions_by_memo_id(memo_id: i64) -> Result<usize> {
+// 없는 첨부파일을 가리키는 본문/검색 인덱스/미리보기/영수증 연결 정리 (정리한 행 수 반환)
+// dry_run이면 개수만 셈
+pub fn cleanup_attachment_references(dry_run: bool) -> Result<usize> {
+    const MISSING: &str = "NOT IN (SELECT id FROM attachments)";
     let conn = get_db().lock();
-    let count = conn.execute("DELETE FROM transactions WHERE memo_id = ?1", params![memo_id])?;
+    if dry_run {
+        let count: i64 = conn.query_row(
+            &format!(
+                "SELECT (SELECT COUNT(*) FROM attachment_texts WHERE attachment_id {m})
+                      + (SELECT COUNT(*) FROM attachment_text_fts WHERE rowid {m})
+                      + (SELECT COUNT(*) FROM attachment_previews WHERE attachment_id {m})
+                      + (SELECT COUNT(*) FROM transaction_receipts WHERE attachment_id {m})",
+                m = MISSING
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        return Ok(count as usize);
+    }
+    let mut count = conn.execute(&format!("DELETE FROM attachment_texts WHERE attachment_id {}", MISSING), [])?;
+    count += conn.execute(&format!("DELETE FROM attachment_text_fts WHERE rowid {}", MISSING), [])?;
+    count += conn.execute(&format!("DELETE FROM attachment_previews WHERE attachment_id {}", MISSING), [])?;
+    count += conn.execute(
+        &format!("UPDATE transaction_receipts SET attachment_id = NULL WHERE attachment_id {}", MISSING),
+        [],
+    )?;
     Ok(count)
 }
 
-// ===== 첨부파일 관련 함수 =====
-
-// 첨부파일 저장
-pub fn save_attachment(attachment: &Attachment) -> Result<i64> {
-    let conn = get_db().lock();
-    conn.execute(
-        "INSERT INTO attachments (memo_id, file_name, file_path, original_path, is_copy, file_size)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![
-            attachment.memo_id,
-            attachment.file_name,
-            attachment.file_path,
-            attachment.original_path,
-            attachment.is_copy as i32,
-            attachment.file_size
-        ],
+// 저장 폴더 이동: 첨부파일 경로 변경과 저장 폴더 설정을 한 트랜잭션으로 반영
+// changes: (첨부파일 ID, 새 경로, 해시)
+pub fn move_attachment_storage(changes: &[(i64, String, String)], storage_path: &str) -> Result<()> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    for (id, file_path, sha256) in changes {
+        tx.execute(
+            "UPDATE attachments SET file_path = ?1, sha256 = ?2 WHERE id = ?3",
+            params![file_path, sha256, id],
+        )?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES ('attachment_storage_path', ?1)",
+        params![storage_path],
     )?;
-    Ok(conn.last_insert_rowid())
+    tx.commit()
 }
 
-// 메모별 첨부파일 조회
-pub fn get_attachments_by_memo(memo_id: i64) -> Result<Vec<Attachment>> {
+// 전체 첨부파일
+pub fn get_all_attachments() -> Result<Vec<Attachment>> {
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT id, memo_id, file_name, file_path, original_path, is_copy, file_size, created_at
-         FROM attachments WHERE memo_id = ?1 ORDER BY created_at DESC"
-    )?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM attachments ORDER BY id", ATTACHMENT_COLUMNS))?;
 
-    let attachments = stmt.query_map([memo_id], |row| {
-        Ok(Attachment {
-            id: row.get(0)?,
-            memo_id: row.get(1)?,
-            file_name: row.get(2)?,
-            file_path: row.get(3)?,
-            original_path: row.get(4)?,
-            is_copy: row.get::<_, i32>(5)? != 0,
-            file_size: row.get(6)?,
-            created_at: row.get(7)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    let attachments = stmt.query_map([], row_to_attachment)?.collect::<Result<Vec<_>>>()?;
 
     Ok(attachments)
 }
 
-// 첨부파일 삭제
-pub fn delete_attachment(id: i64) -> Result<Attachment> {
-    let conn = get_db().lock();
-    // 먼저 첨부파일 정보 조회 (파일 삭제용)
-    let attachment = conn.query_row(
-        "SELECT id, memo_id, file_name, file_path, original_path, is_copy, file_size, created_at
-         FROM attachments WHERE id = ?1",
-        params![id],
-        |row| {
-            Ok(Attachment {
-                id: row.get(0)?,
-                memo_id: row.get(1)?,
-                file_name: row.get(2)?,
-                file_path: row.get(3)?,
-                original_path: row.get(4)?,
-                is_copy: row.get::<_, i32>(5)? != 0,
-                file_size: row.get(6)?,
-                created_at: row.get(7)?,
-            })
-        },
+// 첨부파일 본문 저장 (검색 인덱스도 교체)
+pub fn save_attachment_text(text: &AttachmentText) -> Result<()> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO attachment_texts (attachment_id, content, file_modified, file_size, error, extracted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))
+         ON CONFLICT(attachment_id) DO UPDATE SET content = excluded.content, file_modified = excluded.file_modified,
+             file_size = excluded.file_size, error = excluded.error, extracted_at = excluded.extracted_at",
+        params![text.attachment_id, text.content, text.file_modified, text.file_size, text.error],
     )?;
-    conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])?;
-    Ok(attachment)
+    tx.execute("DELETE FROM attachment_text_fts WHERE rowid = ?1", params![text.attachment_id])?;
+    if !text.content.is_empty() {
+        tx.execute(
+            "INSERT INTO attachment_text_fts (rowid, content) VALUES (?1, ?2)",
+            params![text.attachment_id, text.content],
+        )?;
+    }
+    tx.commit()
 }
 
-// 첨부파일 검색 (파일명으로)
-pub fn search_attachments(query: &str) -> Result<Vec<Attachment>> {
+fn row_to_attachment_text(row: &rusqlite::Row) -> Result<AttachmentText> {
+    Ok(AttachmentText {
+        attachment_id: row.get(0)?,
+        content: row.get(1)?,
+        file_modified: row.get(2)?,
+        file_size: row.get(3)?,
+        error: row.get(4)?,
+        extracted_at: row.get(5)?,
+    })
+}
+
+// 첨부파일 본문 조회
+pub fn get_attachment_text(attachment_id: i64) -> Result<Option<AttachmentText>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        "SELECT attachment_id, content, file_modified, file_size, error, extracted_at
+         FROM attachment_texts WHERE attachment_id = ?1",
+        params![attachment_id],
+        row_to_attachment_text,
+    );
+
+    match result {
+        Ok(text) => Ok(Some(text)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 추출 당시 파일 상태 (attachment_id → (file_modified, file_size))
+pub fn get_attachment_text_stamps() -> Result<std::collections::HashMap<i64, (i64, i64)>> {
     let conn = get_db().lock();
-    let search_pattern = format!("%{}%", query);
     let mut stmt = conn.prepare(
-        "SELECT id, memo_id, file_name, file_path, original_path, is_copy, file_size, created_at
-         FROM attachments WHERE file_name LIKE ?1 ORDER BY created_at DESC"
+        "SELECT attachment_id, file_modified, file_size FROM attachment_texts
+         WHERE file_modified IS NOT NULL AND file_size IS NOT NULL"
     )?;
 
-    let attachments = stmt.query_map([&search_pattern], |row| {
-        Ok(Attachment {
-            id: row.get(0)?,
-            memo_id: row.get(1)?,
-            file_name: row.get(2)?,
-            file_path: row.get(3)?,
-            original_path: row.get(4)?,
-            is_copy: row.get::<_, i32>(5)? != 0,
-            file_size: row.get(6)?,
-            created_at: row.get(7)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    let stamps = stmt
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<Result<std::collections::HashMap<_, _>>>()?;
 
-    Ok(attachments)
+    Ok(stamps)
+}
+
+// 본문 검색: 검색어를 3글자 조각으로 나눠 trigram 인덱스에서 찾고 관련도순 정렬
+// 3글자 미만 검색어만 있으면 LIKE로 찾음 (인덱스 없이 전체 검색)
+// 반환: (첨부파일, 본문)
+pub fn search_attachment_texts(query: &str, limit: i64) -> Result<Vec<(Attachment, String)>> {
+    let terms: Vec<Vec<char>> = query
+        .split_whitespace()
+        .map(|t| t.chars().filter(|c| *c != '"').collect::<Vec<_>>())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut grams: Vec<String> = terms
+        .iter()
+        .flat_map(|t| t.windows(3).map(|w| w.iter().collect::<String>()))
+        .collect();
+    grams.sort();
+    grams.dedup();
+
+    let columns = ATTACHMENT_COLUMNS
+        .split(", ")
+        .map(|c| format!("a.{}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let conn = get_db().lock();
+    let map_row = |row: &rusqlite::Row| Ok((row_to_attachment(row)?, row.get::<_, String>(9)?));
+
+    if !grams.is_empty() {
+        let match_query = grams.iter().map(|g| format!("\"{}\"", g)).collect::<Vec<_>>().join(" OR ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, f.content FROM attachment_text_fts f JOIN attachments a ON a.id = f.rowid
+             WHERE attachment_text_fts MATCH ?1 ORDER BY rank LIMIT ?2",
+            columns
+        ))?;
+        let hits = stmt.query_map(params![match_query, limit], map_row)?.collect::<Result<Vec<_>>>()?;
+        return Ok(hits);
+    }
+
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let conditions = (1..=terms.len()).map(|i| format!("t.content LIKE ?{}", i)).collect::<Vec<_>>().join(" OR ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, t.content FROM attachment_texts t JOIN attachments a ON a.id = t.attachment_id
+         WHERE {} ORDER BY a.created_at DESC LIMIT {}",
+        columns, conditions, limit
+    ))?;
+    let patterns: Vec<String> = terms.iter().map(|t| format!("%{}%", t.iter().collect::<String>())).collect();
+    let hits = stmt.query_map(rusqlite::params_from_iter(patterns), map_row)?.collect::<Result<Vec<_>>>()?;
+    Ok(hits)
 }
 
 // ===== 데이터셋(엑셀) 관련 함수 =====
@@ -830,46 +3444,67 @@ pub fn save_dataset(name: &str, description: &str, columns: &[String]) -> Result
     Ok(conn.last_insert_rowid())
 }
 
-// 데이터셋 행 저장 (배치)
-pub fn save_dataset_rows(dataset_id: i64, rows: &[Vec<String>]) -> Result<i64> {
-    let conn = get_db().lock();
-
-    for (idx, row) in rows.iter().enumerate() {
-        let data_json = serde_json::to_string(row).unwrap_or_default();
-        conn.execute(
+// 데이터셋 행 추가 (row_index는 start_index부터, 한 트랜잭션으로 저장)
+pub fn append_dataset_rows(dataset_id: i64, start_index: i64, rows: &[Vec<String>]) -> Result<()> {
+    let mut conn = get_db().lock();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
             "INSERT INTO dataset_rows (dataset_id, row_index, data_json) VALUES (?1, ?2, ?3)",
-            params![dataset_id, idx as i64, data_json],
         )?;
+        for (idx, row) in rows.iter().enumerate() {
+            let data_json = serde_json::to_string(row).unwrap_or_default();
+            stmt.execute(params![dataset_id, start_index + idx as i64, data_json])?;
+        }
     }
+    tx.commit()
+}
 
-    // row_count 업데이트
+// 데이터셋 row_count 업데이트
+pub fn update_dataset_row_count(dataset_id: i64, row_count: i64) -> Result<()> {
+    let conn = get_db().lock();
     conn.execute(
         "UPDATE datasets SET row_count = ?1 WHERE id = ?2",
-        params![rows.len() as i64, dataset_id],
+        params![row_count, dataset_id],
     )?;
+    Ok(())
+}
 
-    Ok(rows.len() as i64)
+const DATASET_COLUMNS: &str =
+    "id, name, description, columns_json, row_count, created_at, column_types_json, column_type_overrides_json";
+
+fn row_to_dataset(row: &rusqlite::Row) -> Result<Dataset> {
+    let columns_json: String = row.get(3)?;
+    let columns: Vec<String> = serde_json::from_str(&columns_json).unwrap_or_default();
+    let inferred: Vec<ColumnType> = row
+        .get::<_, Option<String>>(6)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| vec![ColumnType::Text; columns.len()]);
+    let column_type_overrides: Vec<Option<ColumnType>> = row
+        .get::<_, Option<String>>(7)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    Ok(Dataset {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        columns,
+        row_count: row.get(4)?,
+        created_at: row.get(5)?,
+        column_types: dataset_schema::effective_types(&inferred, &column_type_overrides),
+        column_type_overrides,
+    })
 }
 
 // 모든 데이터셋 조회
 pub fn get_all_datasets() -> Result<Vec<Dataset>> {
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT id, name, description, columns_json, row_count, created_at FROM datasets ORDER BY created_at DESC"
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM datasets ORDER BY created_at DESC",
+        DATASET_COLUMNS
+    ))?;
 
-    let datasets = stmt.query_map([], |row| {
-        let columns_json: String = row.get(3)?;
-        let columns: Vec<String> = serde_json::from_str(&columns_json).unwrap_or_default();
-        Ok(Dataset {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            columns,
-            row_count: row.get(4)?,
-            created_at: row.get(5)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    let datasets = stmt.query_map([], row_to_dataset)?.collect::<Result<Vec<_>>>()?;
 
     Ok(datasets)
 }
@@ -877,56 +3512,200 @@ pub fn get_all_datasets() -> Result<Vec<Dataset>> {
 // 데이터셋 상세 조회
 pub fn get_dataset(id: i64) -> Result<Dataset> {
     let conn = get_db().lock();
-    let dataset = conn.query_row(
-        "SELECT id, name, description, columns_json, row_count, created_at FROM datasets WHERE id = ?1",
+    conn.query_row(
+        &format!("SELECT {} FROM datasets WHERE id = ?1", DATASET_COLUMNS),
         params![id],
-        |row| {
-            let columns_json: String = row.get(3)?;
-            let columns: Vec<String> = serde_json::from_str(&columns_json).unwrap_or_default();
-            Ok(Dataset {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                columns,
-                row_count: row.get(4)?,
-                created_at: row.get(5)?,
-            })
-        },
+        row_to_dataset,
+    )
+}
+
+// 데이터셋 행을 id 순서로 CHUNK개씩 읽기 (잠금을 오래 잡지 않도록)
+fn dataset_row_chunk(dataset_id: i64, after_id: i64, limit: i64) -> Result<Vec<(i64, Vec<String>)>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, data_json FROM dataset_rows WHERE dataset_id = ?1 AND id > ?2 ORDER BY id LIMIT ?3",
     )?;
-    Ok(dataset)
+    let rows = stmt
+        .query_map(params![dataset_id, after_id, limit], |row| {
+            let data_json: String = row.get(1)?;
+            Ok((row.get(0)?, serde_json::from_str(&data_json).unwrap_or_default()))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(rows)
 }
 
-// 데이터셋 행 조회 (페이징)
-pub fn get_dataset_rows(dataset_id: i64, offset: i64, limit: i64) -> Result<Vec<DatasetRow>> {
+const TYPED_ROW_CHUNK: i64 = 5_000;
+
+// 타입 변환한 행(typed_json)을 id 순서로 CHUNK개씩 읽기 (SQL 샌드박스로 옮길 때)
+pub fn dataset_typed_chunk(dataset_id: i64, after_id: i64, limit: i64) -> Result<Vec<(i64, Vec<serde_json::Value>)>> {
     let conn = get_db().lock();
     let mut stmt = conn.prepare(
-        "SELECT id, dataset_id, row_index, data_json FROM dataset_rows
-         WHERE dataset_id = ?1 ORDER BY row_index LIMIT ?2 OFFSET ?3"
+        "SELECT id, typed_json FROM dataset_rows WHERE dataset_id = ?1 AND id > ?2 ORDER BY id LIMIT ?3",
     )?;
+    let rows = stmt
+        .query_map(params![dataset_id, after_id, limit], |row| {
+            let typed_json: Option<String> = row.get(1)?;
+            let values = typed_json
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            Ok((row.get(0)?, values))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(rows)
+}
 
-    let rows = stmt.query_map(params![dataset_id, limit, offset], |row| {
-        let data_json: String = row.get(3)?;
-        let data: Vec<String> = serde_json::from_str(&data_json).unwrap_or_default();
-        Ok(DatasetRow {
-            id: row.get(0)?,
-            dataset_id: row.get(1)?,
-            row_index: row.get(2)?,
-            data,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+// 컬럼 타입 다시 추측 (모든 행을 봄) 후 typed_json 다시 생성 → 실제 타입 반환
+pub fn refresh_dataset_types(dataset_id: i64) -> Result<Vec<ColumnType>> {
+    let dataset = get_dataset(dataset_id)?;
+    let mut inference = dataset_schema::TypeInference::new(dataset.columns.len());
+    let mut after_id = 0;
+    loop {
+        let chunk = dataset_row_chunk(dataset_id, after_id, TYPED_ROW_CHUNK)?;
+        let Some((last_id, _)) = chunk.last() else {
+            break;
+        };
+        after_id = *last_id;
+        for (_, row) in &chunk {
+            inference.observe(row);
+        }
+    }
+    let inferred = inference.finish();
+    {
+        let conn = get_db().lock();
+        conn.execute(
+            "UPDATE datasets SET column_types_json = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&inferred).unwrap_or_default(), dataset_id],
+        )?;
+    }
+    let types = dataset_schema::effective_types(&inferred, &dataset.column_type_overrides);
+    rebuild_typed_rows(dataset_id, &types)?;
+    Ok(types)
+}
+
+// 타입이 아직 없는 데이터셋(이전 버전에서 가져온 것)이면 추측해서 채움
+pub fn ensure_dataset_types(dataset_id: i64) -> Result<()> {
+    let missing = {
+        let conn = get_db().lock();
+        conn.query_row(
+            "SELECT column_types_json IS NULL FROM datasets WHERE id = ?1",
+            params![dataset_id],
+            |row| row.get::<_, bool>(0),
+        )?
+    };
+    if missing {
+        refresh_dataset_types(dataset_id)?;
+    }
+    Ok(())
+}
+
+// 사용자 지정 컬럼 타입 저장 (None이면 추측한 타입 사용) 후 typed_json 다시 생성
+pub fn set_dataset_column_overrides(dataset_id: i64, overrides: &[Option<ColumnType>]) -> Result<Dataset> {
+    ensure_dataset_types(dataset_id)?;
+    {
+        let conn = get_db().lock();
+        conn.execute(
+            "UPDATE datasets SET column_type_overrides_json = ?1 WHERE id = ?2",
+            params![serde_json::to_string(overrides).unwrap_or_default(), dataset_id],
+        )?;
+    }
+    let dataset = get_dataset(dataset_id)?;
+    rebuild_typed_rows(dataset_id, &dataset.column_types)?;
+    Ok(dataset)
+}
 
+// 모든 행의 typed_json 다시 생성
+fn rebuild_typed_rows(dataset_id: i64, types: &[ColumnType]) -> Result<()> {
+    let mut after_id = 0;
+    loop {
+        let chunk = dataset_row_chunk(dataset_id, after_id, TYPED_ROW_CHUNK)?;
+        let Some((last_id, _)) = chunk.last() else {
+            break;
+        };
+        after_id = *last_id;
+        let typed: Vec<(i64, String)> = chunk
+            .iter()
+            .map(|(id, row)| (*id, dataset_schema::typed_json(row, types)))
+            .collect();
+
+        let mut conn = get_db().lock();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare("UPDATE dataset_rows SET typed_json = ?1 WHERE id = ?2")?;
+            for (id, json) in &typed {
+                stmt.execute(params![json, id])?;
+            }
+        }
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+// 타입에 맞게 변환한 행 조회 (페이징)
+pub fn get_dataset_typed_rows(dataset_id: i64, offset: i64, limit: i64) -> Result<Vec<TypedDatasetRow>> {
+    let types = get_dataset(dataset_id)?.column_types;
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(
+        "SELECT id, row_index, typed_json FROM dataset_rows
+         WHERE dataset_id = ?1 ORDER BY row_index LIMIT ?2 OFFSET ?3"
+    )?;
+    let rows = stmt
+        .query_map(params![dataset_id, limit, offset], |row| {
+            let typed_json: Option<String> = row.get(2)?;
+            let values: Vec<serde_json::Value> = typed_json
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            Ok(TypedDatasetRow {
+                id: row.get(0)?,
+                row_index: row.get(1)?,
+                values: types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| values.get(i).map(|v| CellValue::from_json(v, *t)).unwrap_or(CellValue::Null))
+                    .collect(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
     Ok(rows)
 }
 
-// 데이터셋 전체 행 조회 (AI 분석용)
-pub fn get_all_dataset_rows(dataset_id: i64) -> Result<Vec<DatasetRow>> {
+// 구조화된 데이터셋 질의 실행 (dataset_query::build로 만든 SQL)
+pub fn run_dataset_query(dataset_id: i64, query: &dataset_query::BuiltQuery) -> Result<dataset_query::DatasetQueryResult> {
+    let conn = get_db().lock();
+    let mut params = vec![rusqlite::types::Value::Integer(dataset_id)];
+    params.extend(query.params.iter().cloned());
+
+    let total: i64 = conn.query_row(&query.count_sql, rusqlite::params_from_iter(params.iter()), |row| row.get(0))?;
+    let mut stmt = conn.prepare(&query.sql)?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            query
+                .column_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| Ok(dataset_query::cell_from_sql(row.get(i)?, *t)))
+                .collect::<Result<Vec<_>>>()
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(dataset_query::DatasetQueryResult {
+        columns: query.columns.clone(),
+        column_types: query.column_types.clone(),
+        rows,
+        total,
+        offset: query.offset,
+        limit: query.limit,
+    })
+}
+
+// 데이터셋 행 조회 (페이징)
+pub fn get_dataset_rows(dataset_id: i64, offset: i64, limit: i64) -> Result<Vec<DatasetRow>> {
     let conn = get_db().lock();
     let mut stmt = conn.prepare(
         "SELECT id, dataset_id, row_index, data_json FROM dataset_rows
-         WHERE dataset_id = ?1 ORDER BY row_index"
+         WHERE dataset_id = ?1 ORDER BY row_index LIMIT ?2 OFFSET ?3"
     )?;
 
-    let rows = stmt.query_map(params![dataset_id], |row| {
+    let rows = stmt.query_map(params![dataset_id, limit, offset], |row| {
         let data_json: String = row.get(3)?;
         let data: Vec<String> = serde_json::from_str(&data_json).unwrap_or_default();
         Ok(DatasetRow {
@@ -1051,35 +3830,95 @@ pub fn delete_postit(id: &str) -> Result<()> {
 
 // === Alarm 관련 함수 ===
 
-pub fn save_alarm(time: &str, message: &str, days: &str) -> Result<i64> {
+pub fn save_alarm(alarm: &Alarm) -> Result<i64> {
     let conn = get_db().lock();
     conn.execute(
-        "INSERT INTO alarms (time, message, enabled, days) VALUES (?1, ?2, 1, ?3)",
-        params![time, message, days],
+        "INSERT INTO alarms (time, message, enabled, days, date, timezone, schedule_id, offset_minutes, snooze_minutes)
+         VALUES (?1, ?2, 1, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            alarm.time,
+            alarm.message,
+            alarm.days,
+            alarm.date,
+            alarm.timezone,
+            alarm.schedule_id,
+            alarm.offset_minutes,
+            alarm.snooze_minutes
+        ],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
+const ALARM_COLUMNS: &str =
+    "id, time, message, enabled, days, created_at, date, timezone, schedule_id, offset_minutes, snooze_minutes, next_fire_at";
+
+fn row_to_alarm(row: &rusqlite::Row) -> Result<Alarm> {
+    Ok(Alarm {
+        id: row.get(0)?,
+        time: row.get(1)?,
+        message: row.get(2)?,
+        enabled: row.get::<_, i32>(3)? != 0,
+        days: row.get(4)?,
+        created_at: row.get(5)?,
+        date: row.get(6)?,
+        timezone: row.get(7)?,
+        schedule_id: row.get(8)?,
+        offset_minutes: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+        snooze_minutes: row.get::<_, Option<i64>>(10)?.unwrap_or_else(default_snooze_minutes),
+        next_fire_at: row.get(11)?,
+    })
+}
+
 pub fn get_all_alarms() -> Result<Vec<Alarm>> {
     let conn = get_db().lock();
-    let mut stmt = conn.prepare(
-        "SELECT id, time, message, enabled, days, created_at FROM alarms ORDER BY time ASC"
-    )?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM alarms ORDER BY time ASC", ALARM_COLUMNS))?;
 
-    let alarms = stmt.query_map([], |row| {
-        Ok(Alarm {
-            id: row.get(0)?,
-            time: row.get(1)?,
-            message: row.get(2)?,
-            enabled: row.get::<_, i32>(3)? != 0,
-            days: row.get(4)?,
-            created_at: row.get(5)?,
-        })
-    })?.collect::<Result<Vec<_>>>()?;
+    let alarms = stmt.query_map([], row_to_alarm)?.collect::<Result<Vec<_>>>()?;
+
+    Ok(alarms)
+}
+
+pub fn get_alarm(id: i64) -> Result<Option<Alarm>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM alarms WHERE id = ?1", ALARM_COLUMNS),
+        params![id],
+        row_to_alarm,
+    );
+
+    match result {
+        Ok(alarm) => Ok(Some(alarm)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 다가오는 알람 (켜져 있고 다음 울릴 시각이 있는 것, 시각 순)
+pub fn get_upcoming_alarms(until: Option<&str>, limit: i64) -> Result<Vec<Alarm>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM alarms
+         WHERE enabled = 1 AND next_fire_at IS NOT NULL AND (?1 IS NULL OR next_fire_at <= ?1)
+         ORDER BY next_fire_at ASC
+         LIMIT ?2",
+        ALARM_COLUMNS
+    ))?;
+
+    let alarms = stmt.query_map(params![until, limit], row_to_alarm)?.collect::<Result<Vec<_>>>()?;
 
     Ok(alarms)
 }
 
+// 다음 울릴 시각 갱신 (한 번만 울리는 알람이 지나면 끔)
+pub fn update_alarm_next_fire(id: i64, next_fire_at: Option<&str>, enabled: bool) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "UPDATE alarms SET next_fire_at = ?1, enabled = ?2 WHERE id = ?3",
+        params![next_fire_at, enabled as i32, id],
+    )?;
+    Ok(())
+}
+
 pub fn toggle_alarm(id: i64) -> Result<()> {
     let conn = get_db().lock();
     conn.execute(
@@ -1094,3 +3933,192 @@ pub fn delete_alarm(id: i64) -> Result<()> {
     conn.execute("DELETE FROM alarms WHERE id = ?1", params![id])?;
     Ok(())
 }
+
+// === 리마인더 상태 ===
+
+const REMINDER_COLUMNS: &str = "key, kind, source_id, title, message, trigger_at, status, snooze_until, fired_at";
+
+fn row_to_reminder(row: &rusqlite::Row) -> Result<Reminder> {
+    Ok(Reminder {
+        key: row.get(0)?,
+        kind: row.get(1)?,
+        source_id: row.get(2)?,
+        title: row.get(3)?,
+        message: row.get(4)?,
+        trigger_at: row.get(5)?,
+        status: row.get(6)?,
+        snooze_until: row.get(7)?,
+        fired_at: row.get(8)?,
+    })
+}
+
+pub fn get_reminder(key: &str) -> Result<Option<Reminder>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        &format!("SELECT {} FROM reminder_states WHERE key = ?1", REMINDER_COLUMNS),
+        params![key],
+        row_to_reminder,
+    );
+
+    match result {
+        Ok(reminder) => Ok(Some(reminder)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 이미 울렸거나 닫은 알림인지
+pub fn reminder_exists(key: &str) -> Result<bool> {
+    let conn = get_db().lock();
+    let exists = conn.prepare("SELECT 1 FROM reminder_states WHERE key = ?1")?.exists(params![key])?;
+    Ok(exists)
+}
+
+// 알림 울림 기록 (다시 알림이었으면 fired로 되돌림)
+pub fn record_reminder_fired(reminder: &Reminder) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute(
+        "INSERT INTO reminder_states (key, kind, source_id, title, message, trigger_at, status, fired_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'fired', datetime('now', 'localtime'))
+         ON CONFLICT(key) DO UPDATE SET
+            status = 'fired',
+            snooze_until = NULL,
+            fired_at = datetime('now', 'localtime'),
+            updated_at = datetime('now')",
+        params![
+            reminder.key,
+            reminder.kind,
+            reminder.source_id,
+            reminder.title,
+            reminder.message,
+            reminder.trigger_at
+        ],
+    )?;
+    Ok(())
+}
+
+// 다시 알림 시각이 된 알림
+pub fn get_due_snoozed_reminders(now: &str) -> Result<Vec<Reminder>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminder_states
+         WHERE status = 'snoozed' AND snooze_until <= ?1
+         ORDER BY snooze_until ASC",
+        REMINDER_COLUMNS
+    ))?;
+    let reminders = stmt.query_map(params![now], row_to_reminder)?.collect::<Result<Vec<_>>>()?;
+    Ok(reminders)
+}
+
+// 닫지 않은 알림 (울렸거나 다시 알림 대기 중)
+pub fn get_active_reminders() -> Result<Vec<Reminder>> {
+    let conn = get_db().lock();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminder_states
+         WHERE status IN ('fired', 'snoozed')
+         ORDER BY trigger_at DESC",
+        REMINDER_COLUMNS
+    ))?;
+    let reminders = stmt.query_map([], row_to_reminder)?.collect::<Result<Vec<_>>>()?;
+    Ok(reminders)
+}
+
+// 다시 알림 (알림이 없으면 false)
+pub fn snooze_reminder(key: &str, until: &str) -> Result<bool> {
+    let conn = get_db().lock();
+    let count = conn.execute(
+        "UPDATE reminder_states SET status = 'snoozed', snooze_until = ?1, updated_at = datetime('now') WHERE key = ?2",
+        params![until, key],
+    )?;
+    Ok(count > 0)
+}
+
+// 알림 닫기 (알림이 없으면 false)
+pub fn dismiss_reminder(key: &str) -> Result<bool> {
+    let conn = get_db().lock();
+    let count = conn.execute(
+        "UPDATE reminder_states SET status = 'dismissed', snooze_until = NULL, updated_at = datetime('now') WHERE key = ?1",
+        params![key],
+    )?;
+    Ok(count > 0)
+}
+
+// 오래된 알림 기록 정리 (다시 알림 대기 중인 것은 유지)
+pub fn cleanup_reminder_states(before: &str) -> Result<usize> {
+    let conn = get_db().lock();
+    let count = conn.execute(
+        "DELETE FROM reminder_states WHERE trigger_at < ?1 AND status != 'snoozed'",
+        params![before],
+    )?;
+    Ok(count)
+}
+
+// === AI 응답 캐시 관련 함수 ===
+
+// 캐시 조회 (만료된 항목은 무시)
+pub fn get_ai_cache(cache_key: &str) -> Result<Option<AiCacheEntry>> {
+    let conn = get_db().lock();
+    let result = conn.query_row(
+        "SELECT cache_key, provider, model, operation, prompt_hash, config_hash, response_text,
+                input_tokens, output_tokens, created_at, expires_at, cost_usd
+         FROM ai_cache WHERE cache_key = ?1 AND expires_at > datetime('now')",
+        params![cache_key],
+        |row| {
+            Ok(AiCacheEntry {
+                cache_key: row.get(0)?,
+                provider: row.get(1)?,
+                model: row.get(2)?,
+                operation: row.get(3)?,
+                prompt_hash: row.get(4)?,
+                config_hash: row.get(5)?,
+                response_text: row.get(6)?,
+                input_tokens: row.get(7)?,
+                output_tokens: row.get(8)?,
+                created_at: row.get(9)?,
+                expires_at: row.get(10)?,
+                cost_usd: row.get(11)?,
+            })
+        },
+    );
+
+    match result {
+        Ok(entry) => Ok(Some(entry)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// 캐시 저장 (같은 키가 있으면 교체, 만료된 항목도 함께 정리)
+pub fn save_ai_cache(entry: &AiCacheEntry, ttl_secs: i64) -> Result<()> {
+    let conn = get_db().lock();
+    conn.execute("DELETE FROM ai_cache WHERE expires_at <= datetime('now')", [])?;
+    conn.execute(
+        "INSERT OR REPLACE INTO ai_cache
+         (cache_key, provider, model, operation, prompt_hash, config_hash, response_text, input_tokens, output_tokens, expires_at, cost_usd)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now', ?10), ?11)",
+        params![
+            entry.cache_key,
+            entry.provider,
+            entry.model,
+            entry.operation,
+            entry.prompt_hash,
+            entry.config_hash,
+            entry.response_text,
+            entry.input_tokens,
+            entry.output_tokens,
+            format!("+{} seconds", ttl_secs),
+            entry.cost_usd,
+        ],
+    )?;
+    Ok(())
+}
+
+// 캐시 삭제 (operation 지정 시 해당 작업만)
+pub fn clear_ai_cache(operation: Option<&str>) -> Result<usize> {
+    let conn = get_db().lock();
+    let count = match operation {
+        Some(op) => conn.execute("DELETE FROM ai_cache WHERE operation = ?1", params![op])?,
+        None => conn.execute("DELETE FROM ai_cache", [])?,
+    };
+    Ok(count)
+}
@@ -0,0 +1,386 @@
+// ===== iCalendar (RFC 5545) 내보내기/가져오기 =====
+//
+// VEVENT ↔ 일정, VTODO ↔ 할일
+// 내보낼 때 시간은 타임존 없는 로컬 시간(floating)으로 쓰고,
+// 가져올 때 UTC(Z) 값은 로컬 시간으로 변환, TZID가 붙은 값은 로컬 시간으로 취급
+
+use crate::recurrence;
+use chrono::{Duration, NaiveDateTime, TimeZone};
+
+const PRODID: &str = "-//jolajoamemo//JolaJoa Memo//KO";
+
+#[derive(Debug, Clone, Default)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start: Option<String>,      // "YYYY-MM-DD" 또는 "YYYY-MM-DDTHH:MM"
+    pub end: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub rrule: Option<String>,
+    pub exdates: Vec<String>,
+    pub last_modified: Option<NaiveDateTime>,  // UTC
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IcsTodo {
+    pub uid: String,
+    pub summary: String,
+    pub due: Option<String>,
+    pub completed: bool,
+    pub priority: Option<String>,   // high, medium, low
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IcsCalendar {
+    pub events: Vec<IcsEvent>,
+    pub todos: Vec<IcsTodo>,
+}
+
+// ----- 내보내기 -----
+
+/// TEXT 값 이스케이프 (\, ;, , 줄바꿈)
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 75옥텟 단위 줄 접기 (UTF-8 문자 중간에서 자르지 않음)
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+/// 날짜/시간 속성 (종일이면 VALUE=DATE)
+fn date_property(name: &str, value: &NaiveDateTime, date_only: bool) -> String {
+    if date_only {
+        format!("{};VALUE=DATE:{}", name, value.format("%Y%m%d"))
+    } else {
+        format!("{}:{}", name, value.format("%Y%m%dT%H%M%S"))
+    }
+}
+
+/// 일정/할일 목록을 VCALENDAR 텍스트로 변환
+pub fn build_calendar(calendar: &IcsCalendar) -> String {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for event in &calendar.events {
+        let start_text = match event.start.as_deref() {
+            Some(start) => start,
+            None => continue,
+        };
+        let start = match recurrence::parse_datetime(start_text) {
+            Some(start) => start,
+            None => continue,
+        };
+        let date_only = recurrence::is_date_only(start_text);
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(date_property("DTSTART", &start, date_only));
+        if let Some(end) = event.end.as_deref().and_then(recurrence::parse_datetime) {
+            // 종일 일정의 DTEND는 다음 날 (배타적)
+            let end = if date_only { end + Duration::days(1) } else { end };
+            if end > start {
+                lines.push(date_property("DTEND", &end, date_only));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(description) = event.description.as_deref().filter(|d| !d.is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(rrule) = event.rrule.as_deref().filter(|r| !r.is_empty()) {
+            lines.push(format!("RRULE:{}", rrule.strip_prefix("RRULE:").unwrap_or(rrule)));
+        }
+        for exdate in event.exdates.iter().filter_map(|e| recurrence::parse_datetime(e)) {
+            lines.push(date_property("EXDATE", &exdate, date_only));
+        }
+        if let Some(modified) = event.last_modified {
+            lines.push(format!("LAST-MODIFIED:{}", modified.format("%Y%m%dT%H%M%SZ")));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    for todo in &calendar.todos {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}", todo.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape_text(&todo.summary)));
+        if let Some(due_text) = todo.due.as_deref() {
+            if let Some(due) = recurrence::parse_datetime(due_text) {
+                lines.push(date_property("DUE", &due, recurrence::is_date_only(due_text)));
+            }
+        }
+        if let Some(description) = todo.description.as_deref().filter(|d| !d.is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        let priority = match todo.priority.as_deref() {
+            Some("high") => Some(1),
+            Some("medium") => Some(5),
+            Some("low") => Some(9),
+            _ => None,
+        };
+        if let Some(priority) = priority {
+            lines.push(format!("PRIORITY:{}", priority));
+        }
+        lines.push(format!("STATUS:{}", if todo.completed { "COMPLETED" } else { "NEEDS-ACTION" }));
+        lines.push("END:VTODO".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in &lines {
+        fold_line(line, &mut out);
+    }
+    out
+}
+
+// ----- 가져오기 -----
+
+/// 속성 한 줄: 이름, 파라미터, 값
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 접힌 줄 펴기 (공백/탭으로 시작하는 줄은 앞 줄에 이어 붙임)
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.trim_start_matches('\u{feff}').split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(rest) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// "NAME;P1=V1;P2=\"V:2\":VALUE" 파싱 (따옴표 안의 ; : 는 구분자로 보지 않음)
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut value = None;
+
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(ch);
+            }
+            ';' if !in_quotes => segments.push(std::mem::take(&mut current)),
+            ':' if !in_quotes => {
+                segments.push(std::mem::take(&mut current));
+                value = Some(line[i + 1..].to_string());
+                break;
+            }
+            _ => current.push(ch),
+        }
+    }
+
+    let value = value?;
+    let mut segments = segments.into_iter();
+    let name = segments.next()?.trim().to_uppercase();
+    let params = segments
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((k.trim().to_uppercase(), v.trim().trim_matches('"').to_string()))
+        })
+        .collect();
+
+    Some(Property { name, params, value })
+}
+
+/// TEXT 값 이스케이프 해제
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// 날짜/시간 값 → 앱 형식 ("YYYY-MM-DD" 또는 "YYYY-MM-DDTHH:MM")
+/// UTC(Z)는 로컬 시간으로 변환
+fn parse_date_value(property: &Property, value: &str) -> Option<String> {
+    let value = value.trim();
+    let parsed = recurrence::parse_datetime(value)?;
+
+    let date_only = property.param("VALUE").map(|v| v.eq_ignore_ascii_case("DATE")).unwrap_or(false)
+        || !value.contains('T');
+    if date_only {
+        return Some(parsed.format("%Y-%m-%d").to_string());
+    }
+
+    let local = if value.ends_with('Z') {
+        chrono::Utc.from_utc_datetime(&parsed).with_timezone(&chrono::Local).naive_local()
+    } else {
+        parsed
+    };
+    Some(local.format("%Y-%m-%dT%H:%M").to_string())
+}
+
+/// VCALENDAR 텍스트에서 VEVENT/VTODO 추출
+/// UID가 없는 항목은 건너뜀 (가져오기 시 UID 기준으로 갱신하므로)
+/// 반복 일정의 개별 회차 수정본(RECURRENCE-ID)은 지원하지 않으므로 원본 일정만 사용
+pub fn parse_calendar(text: &str) -> IcsCalendar {
+    let mut calendar = IcsCalendar::default();
+    let mut stack: Vec<String> = Vec::new();
+    let mut event = IcsEvent::default();
+    let mut todo = IcsTodo::default();
+    let mut todo_status_completed = false;
+    let mut is_override = false;
+
+    for line in unfold_lines(text) {
+        let property = match parse_property(&line) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_uppercase();
+                if stack.last().map(|c| c == "VCALENDAR").unwrap_or(false) {
+                    match component.as_str() {
+                        "VEVENT" => {
+                            event = IcsEvent::default();
+                            is_override = false;
+                        }
+                        "VTODO" => {
+                            todo = IcsTodo::default();
+                            todo_status_completed = false;
+                        }
+                        _ => {}
+                    }
+                }
+                stack.push(component);
+                continue;
+            }
+            "END" => {
+                let component = stack.pop().unwrap_or_default();
+                // VCALENDAR 바로 아래 구성요소만 (VALARM 등 하위 구성요소 제외)
+                if stack.last().map(|c| c == "VCALENDAR").unwrap_or(false) {
+                    match component.as_str() {
+                        "VEVENT" if !event.uid.is_empty() && !is_override => calendar.events.push(std::mem::take(&mut event)),
+                        "VTODO" if !todo.uid.is_empty() => {
+                            todo.completed = todo.completed || todo_status_completed;
+                            calendar.todos.push(std::mem::take(&mut todo));
+                        }
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        // 하위 구성요소(VALARM 등) 안의 속성은 무시
+        if stack.len() != 2 {
+            continue;
+        }
+
+        match stack[1].as_str() {
+            "VEVENT" => match property.name.as_str() {
+                "UID" => event.uid = property.value.trim().to_string(),
+                "SUMMARY" => event.summary = unescape_text(&property.value),
+                "DTSTART" => event.start = parse_date_value(&property, &property.value),
+                "DTEND" => event.end = parse_date_value(&property, &property.value),
+                "LOCATION" => event.location = Some(unescape_text(&property.value)),
+                "DESCRIPTION" => event.description = Some(unescape_text(&property.value)),
+                "RRULE" => event.rrule = Some(property.value.trim().to_string()),
+                "RECURRENCE-ID" => is_override = true,
+                "LAST-MODIFIED" => {
+                    event.last_modified = recurrence::parse_datetime(&property.value);
+                }
+                "EXDATE" => {
+                    for value in property.value.split(',') {
+                        if let Some(exdate) = parse_date_value(&property, value) {
+                            event.exdates.push(exdate);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            "VTODO" => match property.name.as_str() {
+                "UID" => todo.uid = property.value.trim().to_string(),
+                "SUMMARY" => todo.summary = unescape_text(&property.value),
+                "DUE" => todo.due = parse_date_value(&property, &property.value),
+                "DESCRIPTION" => todo.description = Some(unescape_text(&property.value)),
+                "STATUS" => todo_status_completed = property.value.trim().eq_ignore_ascii_case("COMPLETED"),
+                "COMPLETED" => todo.completed = true,
+                "PRIORITY" => {
+                    todo.priority = match property.value.trim().parse::<u8>() {
+                        Ok(1..=4) => Some("high".to_string()),
+                        Ok(5) => Some("medium".to_string()),
+                        Ok(6..=9) => Some("low".to_string()),
+                        _ => None,
+                    };
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // 종일 일정의 DTEND(배타적)를 앱 형식(포함)으로 되돌림
+    for event in &mut calendar.events {
+        let start = event.start.as_deref().map(recurrence::is_date_only).unwrap_or(false);
+        if let (true, Some(end)) = (start, event.end.as_deref()) {
+            event.end = recurrence::parse_datetime(end)
+                .map(|e| (e - Duration::days(1)).format("%Y-%m-%d").to_string())
+                .filter(|e| Some(e.as_str()) >= event.start.as_deref());
+        }
+    }
+
+    calendar
+}
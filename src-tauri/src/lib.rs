@@ -1,17 +1,32 @@
 mod ai;
+mod attachment_store;
+mod attachment_text;
+mod bank_import;
+mod caldav;
+mod currency;
+mod dataset_import;
+mod dataset_query;
+mod dataset_schema;
+mod dataset_sql;
 mod db;
+mod ics;
+mod receipt;
+mod recurrence;
+mod reminder;
+mod thumbnail;
 
 use db::{Attachment, Memo, Schedule, Todo, Transaction, Dataset, DatasetRow, Postit};
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager};
-use calamine::{Reader, Xlsx};
-use std::io::Cursor;
 use std::sync::atomic::{AtomicBool, Ordering};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 
 // 스캔 취소 플래그
 static SCAN_CANCELLED: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(false));
 
+// 앱 핸들 (명령 밖에서 이벤트 발송용)
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InputResult {
     pub success: bool,
@@ -39,6 +54,128 @@ pub struct UsageStats {
     pub today_input_tokens: i64,
     pub today_output_tokens: i64,
     pub today_cost_usd: f64,
+    pub today_cache_hits: i64,
+    pub today_saved_usd: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BudgetWarning {
+    pub operation: String,
+    pub period: String,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: db::ApiBudget,
+    pub spent_usd: f64,
+    pub ratio: f64,
+}
+
+// API 사용량 기록 + 예산 경고 임계값을 넘으면 budget-warning 이벤트 발송
+fn log_usage(operation: &str, model: &str, input_tokens: i64, output_tokens: i64, cost_usd: f64) -> rusqlite::Result<()> {
+    db::log_api_usage(operation, model, input_tokens, output_tokens, cost_usd)?;
+
+    if cost_usd > 0.0 {
+        if let Some(app) = APP_HANDLE.get() {
+            emit_budget_warnings(app, operation, cost_usd);
+        }
+    }
+    Ok(())
+}
+
+fn emit_budget_warnings(app: &tauri::AppHandle, operation: &str, cost_usd: f64) {
+    let thresholds: Vec<f64> = db::get_setting("budget_warning_thresholds")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let budgets = db::get_all_api_budgets().unwrap_or_default();
+
+    for budget in budgets.iter().filter(|b| b.operation == "*" || b.operation == operation) {
+        let spent = match db::get_spent_usd(&budget.operation, &budget.period) {
+            Ok(spent) => spent,
+            Err(_) => continue,
+        };
+        let before = spent - cost_usd;
+
+        // 이번 호출로 넘어선 임계값 중 가장 높은 것만 알림
+        let crossed = thresholds
+            .iter()
+            .copied()
+            .filter(|t| before < t * budget.limit_usd && spent >= t * budget.limit_usd)
+            .fold(None, |max: Option<f64>, t| Some(max.map_or(t, |m| m.max(t))));
+
+        if let Some(threshold) = crossed {
+            app.emit("budget-warning", BudgetWarning {
+                operation: budget.operation.clone(),
+                period: budget.period.clone(),
+                limit_usd: budget.limit_usd,
+                spent_usd: spent,
+                threshold,
+            }).ok();
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionBudgetWarning {
+    pub category: String,
+    pub period: String,
+    pub period_start: String,
+    pub limit_amount: i64,
+    pub spent: i64,
+    pub threshold: f64,
+}
+
+// 지출 거래 저장 후 예산 경고 임계값을 넘으면 transaction-budget-warning 이벤트 발송
+fn emit_transaction_budget_warnings(transaction: &Transaction) {
+    if transaction.tx_type == "income" || transaction.tx_type == "transfer" {
+        return;
+    }
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let thresholds: Vec<f64> = db::get_setting("transaction_budget_warning_thresholds")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let date = transaction.tx_date.clone()
+        .filter(|d| recurrence::parse_datetime(d).is_some())
+        .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    let category = transaction.category.as_deref().map(str::trim).filter(|c| !c.is_empty()).unwrap_or("미분류");
+    // 환율이 없는 외화 거래는 예산 합계에도 들어가지 않으므로 경고하지 않음
+    let Ok(Some(amount)) = db::convert_to_base(transaction.amount, &transaction.currency, &date) else {
+        return;
+    };
+    let budgets = db::get_all_transaction_budgets().unwrap_or_default();
+
+    for budget in budgets.iter().filter(|b| b.category == "*" || b.category == category) {
+        let Ok((spent, period_start)) = db::get_transaction_spent(&budget.category, &budget.period, &date) else {
+            continue;
+        };
+        let before = spent - amount;
+        let limit = budget.limit_amount as f64;
+
+        // 이번 거래로 넘어선 임계값 중 가장 높은 것만 알림
+        let crossed = thresholds
+            .iter()
+            .copied()
+            .filter(|t| (before as f64) < t * limit && spent as f64 >= t * limit)
+            .fold(None, |max: Option<f64>, t| Some(max.map_or(t, |m| m.max(t))));
+
+        if let Some(threshold) = crossed {
+            app.emit("transaction-budget-warning", TransactionBudgetWarning {
+                category: budget.category.clone(),
+                period: budget.period.clone(),
+                period_start,
+                limit_amount: budget.limit_amount,
+                spent,
+                threshold,
+            }).ok();
+        }
+    }
 }
 
 // 입력: 텍스트를 분석해서 저장 또는 병합 (여러 개 자동 분리)
@@ -63,11 +200,11 @@ async fn input_memo(content: String) -> Result<InputResult, String> {
     let existing_categories = db::get_all_categories().map_err(|e| e.to_string())?;
 
     // AI 분석 (여러 개 자동 분리)
-    let (items, usage) = ai::analyze_multi_memo(&api_key, &model, &content, &memo_info, &existing_categories).await?;
+    let (items, usage) = ai::analyze_multi_memo(&api_key, &model, "analyze", &content, &memo_info, &existing_categories).await?;
 
     // 사용량 기록
     let model_name = if model.is_empty() { "gemini-3-flash-preview" } else { &model };
-    db::log_api_usage(
+    log_usage(
         "analyze",
         model_name,
         usage.input_tokens,
@@ -153,39 +290,23 @@ async fn input_memo(content: String) -> Result<InputResult, String> {
                 description: schedule_info.description,
                 google_event_id: None,
                 created_at: String::new(),
+                recurrence: valid_recurrence(schedule_info.recurrence.as_deref()),
+                exdates: None,
             };
             db::save_schedule(&schedule).map_err(|e| e.to_string())?;
             schedules_added += 1;
         }
 
         // 할일 저장 (메모와 연결)
-        for todo_info in analysis.todos {
-            let todo = Todo {
-                id: 0,
-                memo_id,  // 원본 메모와 연결
-                title: todo_info.title,
-                completed: false,
-                priority: todo_info.priority,
-                due_date: todo_info.due_date,
-                created_at: String::new(),
-            };
-            db::save_todo(&todo).map_err(|e| e.to_string())?;
-            todos_added += 1;
+        for todo_info in &analysis.todos {
+            todos_added += save_analyzed_todo(memo_id, todo_info)?;  // 원본 메모와 연결
         }
 
         // 거래 저장 (메모와 연결)
-        for tx_info in analysis.transactions {
-            let transaction = Transaction {
-                id: 0,
-                memo_id,  // 원본 메모와 연결
-                tx_type: tx_info.tx_type,
-                amount: tx_info.amount,
-                description: tx_info.description,
-                category: tx_info.category,
-                tx_date: tx_info.tx_date,
-                created_at: String::new(),
-            };
+        for tx_info in &analysis.transactions {
+            let transaction = analyzed_transaction(memo_id, tx_info);  // 원본 메모와 연결
             db::save_transaction(&transaction).map_err(|e| e.to_string())?;
+            emit_transaction_budget_warnings(&transaction);
             transactions_added += 1;
         }
     }
@@ -222,6 +343,10 @@ async fn input_memo(content: String) -> Result<InputResult, String> {
     })
 }
 
+// AI 찾기에 넣을 첨부파일 수와 파일당 발췌 길이
+const ATTACHMENT_CONTEXT_FILES: i64 = 5;
+const ATTACHMENT_CONTEXT_CHARS: usize = 3_000;
+
 // 찾기: 질문에 대한 답변
 #[tauri::command]
 async fn search_memo(question: String) -> Result<SearchResult, String> {
@@ -246,17 +371,27 @@ async fn search_memo(question: String) -> Result<SearchResult, String> {
     }
 
     // 컨텍스트 구성
-    let context: Vec<(String, String)> = memos
+    let mut context: Vec<(String, String)> = memos
         .iter()
         .map(|m| (m.title.clone(), m.formatted_content.clone()))
         .collect();
 
+    // 질문과 관련된 첨부파일 본문 발췌 추가
+    let attachment_hits = db::search_attachment_texts(&question, ATTACHMENT_CONTEXT_FILES).map_err(|e| e.to_string())?;
+    for (attachment, content) in attachment_hits {
+        let memo_title = memos.iter().find(|m| m.id == attachment.memo_id).map(|m| m.title.as_str()).unwrap_or("");
+        context.push((
+            format!("첨부파일 {} (메모: {})", attachment.file_name, memo_title),
+            attachment_text::excerpt(&content, &question, ATTACHMENT_CONTEXT_CHARS),
+        ));
+    }
+
     // AI 질의응답
     let (answer, usage) = ai::ask_question(&api_key, &model, &question, &context).await?;
 
     // 사용량 기록
     let model_name = if model.is_empty() { "gemini-3-flash-preview" } else { &model };
-    db::log_api_usage(
+    log_usage(
         "search",
         model_name,
         usage.input_tokens,
@@ -285,9 +420,15 @@ fn save_setting(key: String, value: String) -> Result<(), String> {
     db::save_setting(&key, &value).map_err(|e| e.to_string())
 }
 
+// 화면에서 조회할 수 없는 설정 (저장만 가능, 백엔드에서만 읽음)
+const WRITE_ONLY_SETTINGS: &[&str] = &["caldav_password"];
+
 // 설정 조회
 #[tauri::command]
 fn get_setting(key: String) -> Result<String, String> {
+    if WRITE_ONLY_SETTINGS.contains(&key.as_str()) {
+        return Err(format!("조회할 수 없는 설정입니다: {}", key));
+    }
     db::get_setting(&key).map_err(|e| e.to_string())
 }
 
@@ -295,13 +436,190 @@ fn get_setting(key: String) -> Result<String, String> {
 #[tauri::command]
 fn get_usage() -> Result<UsageStats, String> {
     let (input, output, cost) = db::get_today_usage().map_err(|e| e.to_string())?;
+    let (cache_hits, saved) = db::get_today_cache_savings().map_err(|e| e.to_string())?;
     Ok(UsageStats {
         today_input_tokens: input,
         today_output_tokens: output,
         today_cost_usd: cost,
+        today_cache_hits: cache_hits,
+        today_saved_usd: saved,
     })
 }
 
+// AI 응답 캐시 삭제 (operation 미지정 시 전체)
+#[tauri::command]
+fn clear_ai_cache(operation: Option<String>) -> Result<usize, String> {
+    db::clear_ai_cache(operation.as_deref()).map_err(|e| e.to_string())
+}
+
+// 사용량 통계 날짜 범위 확인 (로컬 날짜 YYYY-MM-DD)
+fn validate_usage_range(start_date: &str, end_date: &str) -> Result<(), String> {
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| format!("잘못된 시작일: {}", start_date))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| format!("잘못된 종료일: {}", end_date))?;
+    if start > end {
+        return Err("시작일이 종료일보다 늦습니다".to_string());
+    }
+    Ok(())
+}
+
+// 기간별 사용량 통계 (period: day/week/month, group_by: operation/model)
+#[tauri::command]
+fn get_usage_stats(start_date: String, end_date: String, period: Option<String>, group_by: Option<String>) -> Result<Vec<db::UsageAggregate>, String> {
+    validate_usage_range(&start_date, &end_date)?;
+    let period = period.unwrap_or_else(|| "day".to_string());
+    if !["day", "week", "month", "none"].contains(&period.as_str()) {
+        return Err("기간 단위는 day, week, month, none 중 하나여야 합니다".to_string());
+    }
+    let group_by = group_by.unwrap_or_default();
+    if !["", "operation", "model"].contains(&group_by.as_str()) {
+        return Err("그룹 기준은 operation 또는 model만 가능합니다".to_string());
+    }
+    db::get_usage_aggregates(&start_date, &end_date, &period, &group_by).map_err(|e| e.to_string())
+}
+
+// 비용 상위 작업
+#[tauri::command]
+fn get_top_cost_operations(start_date: String, end_date: String, limit: Option<i64>) -> Result<Vec<db::UsageAggregate>, String> {
+    validate_usage_range(&start_date, &end_date)?;
+    db::get_top_cost_operations(&start_date, &end_date, limit.unwrap_or(10)).map_err(|e| e.to_string())
+}
+
+// 사용 기록 CSV 내보내기 (다운로드 폴더에 저장 후 경로 반환)
+#[tauri::command]
+fn export_usage_csv(start_date: String, end_date: String) -> Result<String, String> {
+    use std::io::Write;
+
+    validate_usage_range(&start_date, &end_date)?;
+    let records = db::get_api_usage_records(&start_date, &end_date).map_err(|e| e.to_string())?;
+
+    let downloads_dir = dirs::download_dir()
+        .ok_or("다운로드 폴더를 찾을 수 없습니다")?;
+    let filename = format!("api_usage_{}_{}.csv", start_date.replace('-', ""), end_date.replace('-', ""));
+    let filepath = downloads_dir.join(&filename);
+
+    let mut file = std::fs::File::create(&filepath)
+        .map_err(|e| format!("파일 생성 실패: {}", e))?;
+
+    // BOM for Excel UTF-8
+    file.write_all(&[0xEF, 0xBB, 0xBF])
+        .map_err(|e| format!("BOM 쓰기 실패: {}", e))?;
+
+    writeln!(file, "id,timestamp,operation,model,input_tokens,output_tokens,cost_usd,cache_hit,saved_usd")
+        .map_err(|e| format!("쓰기 실패: {}", e))?;
+    for r in &records {
+        writeln!(file, "{},\"{}\",\"{}\",\"{}\",{},{},{:.6},{},{:.6}",
+            r.id,
+            r.timestamp,
+            r.operation.replace("\"", "\"\""),
+            r.model.replace("\"", "\"\""),
+            r.input_tokens,
+            r.output_tokens,
+            r.cost_usd,
+            if r.cache_hit { 1 } else { 0 },
+            r.saved_usd
+        ).map_err(|e| format!("쓰기 실패: {}", e))?;
+    }
+
+    Ok(filepath.to_string_lossy().to_string())
+}
+
+// AI 응답 검증 실패 기록 (디버깅용, 최근 순)
+#[tauri::command]
+fn get_ai_output_failures(limit: Option<i64>) -> Result<Vec<db::AiOutputFailure>, String> {
+    db::get_ai_output_failures(limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+// AI 응답 검증 실패 기록 삭제
+#[tauri::command]
+fn clear_ai_output_failures() -> Result<usize, String> {
+    db::clear_ai_output_failures().map_err(|e| e.to_string())
+}
+
+// 모델 가격 목록 (적용 시각별 이력 포함)
+#[tauri::command]
+fn get_model_prices() -> Result<Vec<db::ModelPrice>, String> {
+    db::get_all_model_prices().map_err(|e| e.to_string())
+}
+
+// 모델 가격 저장 (effective_from을 비우면 지금부터 적용)
+#[tauri::command]
+fn save_model_price(mut price: db::ModelPrice) -> Result<i64, String> {
+    if price.provider.is_empty() {
+        price.provider = "gemini".to_string();
+    }
+    if price.model.trim().is_empty() {
+        return Err("모델 이름을 입력해주세요".to_string());
+    }
+    let prices = [Some(price.input_per_m), Some(price.output_per_m), price.cached_input_per_m, price.reasoning_per_m];
+    if prices.iter().flatten().any(|p| *p < 0.0) {
+        return Err("가격은 0 이상이어야 합니다".to_string());
+    }
+    if !price.effective_from.is_empty()
+        && chrono::NaiveDateTime::parse_from_str(&price.effective_from, "%Y-%m-%d %H:%M:%S").is_err()
+    {
+        return Err("적용 시각은 YYYY-MM-DD HH:MM:SS (UTC) 형식이어야 합니다".to_string());
+    }
+    db::save_model_price(&price).map_err(|e| e.to_string())
+}
+
+// 모델 가격 삭제
+#[tauri::command]
+fn delete_model_price(id: i64) -> Result<(), String> {
+    db::delete_model_price(id).map_err(|e| e.to_string())
+}
+
+// 예산 목록
+#[tauri::command]
+fn get_budgets() -> Result<Vec<db::ApiBudget>, String> {
+    db::get_all_api_budgets().map_err(|e| e.to_string())
+}
+
+// 예산 저장 (operation 미지정 시 전체, 같은 작업/기간이면 덮어씀)
+#[tauri::command]
+fn save_budget(operation: Option<String>, period: String, limit_usd: f64, action: Option<String>) -> Result<i64, String> {
+    if period != "daily" && period != "monthly" {
+        return Err("기간은 daily 또는 monthly만 가능합니다".to_string());
+    }
+    if limit_usd < 0.0 {
+        return Err("예산 한도는 0 이상이어야 합니다".to_string());
+    }
+    let action = action.unwrap_or_else(|| "block".to_string());
+    if action != "block" && action != "confirm" {
+        return Err("초과 시 동작은 block 또는 confirm만 가능합니다".to_string());
+    }
+    let operation = operation.filter(|o| !o.is_empty()).unwrap_or_else(|| "*".to_string());
+    db::save_api_budget(&operation, &period, limit_usd, &action).map_err(|e| e.to_string())
+}
+
+// 예산 삭제
+#[tauri::command]
+fn delete_budget(id: i64) -> Result<(), String> {
+    db::delete_api_budget(id).map_err(|e| e.to_string())
+}
+
+// 예산별 현재 사용액
+#[tauri::command]
+fn get_budget_status() -> Result<Vec<BudgetStatus>, String> {
+    let budgets = db::get_all_api_budgets().map_err(|e| e.to_string())?;
+    budgets
+        .into_iter()
+        .map(|budget| {
+            let spent_usd = db::get_spent_usd(&budget.operation, &budget.period).map_err(|e| e.to_string())?;
+            let ratio = if budget.limit_usd > 0.0 { spent_usd / budget.limit_usd } else { 0.0 };
+            Ok(BudgetStatus { budget, spent_usd, ratio })
+        })
+        .collect()
+}
+
+// 확인 모드 예산 초과 승인 (승인한 예산만 잠시 동안 확인 없이 진행)
+// budget_id: [BUDGET_CONFIRM] 에러에 포함된 budget_id
+#[tauri::command]
+fn approve_budget_overrun(budget_id: i64) {
+    ai::approve_budget_overrun(budget_id);
+}
+
 // DB 내보내기 (JSON)
 #[tauri::command]
 fn export_db() -> Result<String, String> {
@@ -365,11 +683,11 @@ async fn reanalyze_memo(id: i64, new_content: String) -> Result<InputResult, Str
     let existing_categories = db::get_all_categories().map_err(|e| e.to_string())?;
 
     // AI 재분석 (병합 없이 단일 분석)
-    let (items, usage) = ai::analyze_multi_memo(&api_key, &model, &new_content, &[], &existing_categories).await?;
+    let (items, usage) = ai::analyze_multi_memo(&api_key, &model, "reanalyze", &new_content, &[], &existing_categories).await?;
 
     // 사용량 기록
     let model_name = if model.is_empty() { "gemini-3-flash-preview" } else { &model };
-    db::log_api_usage(
+    log_usage(
         "reanalyze",
         model_name,
         usage.input_tokens,
@@ -415,6 +733,8 @@ async fn reanalyze_memo(id: i64, new_content: String) -> Result<InputResult, Str
                 description: schedule_info.description.clone(),
                 google_event_id: None,
                 created_at: String::new(),
+                recurrence: valid_recurrence(schedule_info.recurrence.as_deref()),
+                exdates: None,
             };
             db::save_schedule(&schedule).map_err(|e| e.to_string())?;
             schedules_added += 1;
@@ -422,32 +742,14 @@ async fn reanalyze_memo(id: i64, new_content: String) -> Result<InputResult, Str
 
         // 할일 저장
         for todo_info in &analysis.todos {
-            let todo = Todo {
-                id: 0,
-                memo_id: Some(id),
-                title: todo_info.title.clone(),
-                completed: false,
-                priority: todo_info.priority.clone(),
-                due_date: todo_info.due_date.clone(),
-                created_at: String::new(),
-            };
-            db::save_todo(&todo).map_err(|e| e.to_string())?;
-            todos_added += 1;
+            todos_added += save_analyzed_todo(Some(id), todo_info)?;
         }
 
         // 거래 저장
         for tx_info in &analysis.transactions {
-            let transaction = Transaction {
-                id: 0,
-                memo_id: Some(id),
-                tx_type: tx_info.tx_type.clone(),
-                amount: tx_info.amount,
-                description: tx_info.description.clone(),
-                category: tx_info.category.clone(),
-                tx_date: tx_info.tx_date.clone(),
-                created_at: String::new(),
-            };
+            let transaction = analyzed_transaction(Some(id), tx_info);
             db::save_transaction(&transaction).map_err(|e| e.to_string())?;
+            emit_transaction_budget_warnings(&transaction);
             transactions_added += 1;
         }
     }
@@ -472,15 +774,27 @@ async fn reanalyze_memo(id: i64, new_content: String) -> Result<InputResult, Str
     })
 }
 
+// 메모와 첨부파일 함께 삭제
+// 외래 키 제약(PRAGMA foreign_keys)을 켜지 않으므로 ON DELETE CASCADE 대신 첨부파일을 직접 지움
+fn delete_memo_with_attachments(id: i64) -> Result<(), String> {
+    for attachment in db::get_attachments_by_memo(id).map_err(|e| e.to_string())? {
+        delete_attachment_with_file(attachment.id)?;
+    }
+    db::delete_memo(id).map_err(|e| e.to_string())
+}
+
 // 메모 삭제
 #[tauri::command]
 fn delete_memo(id: i64) -> Result<(), String> {
-    db::delete_memo(id).map_err(|e| e.to_string())
+    delete_memo_with_attachments(id)
 }
 
-// 전체 메모 삭제
+// 전체 메모 삭제 (첨부파일도 함께)
 #[tauri::command]
 fn delete_all_memos() -> Result<usize, String> {
+    for attachment in db::get_all_attachments().map_err(|e| e.to_string())? {
+        delete_attachment_with_file(attachment.id)?;
+    }
     db::delete_all_memos().map_err(|e| e.to_string())
 }
 
@@ -490,12 +804,139 @@ fn get_schedules() -> Result<Vec<Schedule>, String> {
     db::get_all_schedules().map_err(|e| e.to_string())
 }
 
+// AI가 추출한 반복 규칙 검증 (해석할 수 없으면 단일 일정으로 저장)
+fn valid_recurrence(rule: Option<&str>) -> Option<String> {
+    let rule = rule?.trim();
+    let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+    recurrence::parse_rrule(rule).ok().map(|_| rule.to_string())
+}
+
+// AI가 추출한 할일과 체크리스트 하위 할일 저장 (저장한 개수 반환)
+fn save_analyzed_todo(memo_id: Option<i64>, info: &ai::TodoInfo) -> Result<i32, String> {
+    let parent_id = db::save_todo(&Todo {
+        id: 0,
+        memo_id,
+        title: info.title.clone(),
+        completed: false,
+        priority: info.priority.clone(),
+        due_date: info.due_date.clone(),
+        created_at: String::new(),
+        parent_id: None,
+        project: None,
+        sort_order: None,
+        completed_at: None,
+        recurrence: None,
+        series_id: None,
+    }).map_err(|e| e.to_string())?;
+    if let Some(rule) = valid_recurrence(info.recurrence.as_deref()) {
+        db::set_todo_recurrence(parent_id, Some(&rule)).map_err(|e| e.to_string())?;
+    }
+
+    for (index, sub) in info.subtasks.iter().enumerate() {
+        db::save_todo(&Todo {
+            id: 0,
+            memo_id,
+            title: sub.title.clone(),
+            completed: false,
+            priority: sub.priority.clone().or_else(|| info.priority.clone()),
+            due_date: sub.due_date.clone().or_else(|| info.due_date.clone()),
+            created_at: String::new(),
+            parent_id: Some(parent_id),
+            project: None,
+            sort_order: Some(index as i64),  // 체크리스트 순서 유지
+            completed_at: None,
+            recurrence: None,
+            series_id: None,
+        }).map_err(|e| e.to_string())?;
+    }
+
+    Ok(1 + info.subtasks.len() as i32)
+}
+
+// AI가 추출한 거래 (통화 코드 정리, 금액은 통화의 최소 단위로)
+fn analyzed_transaction(memo_id: Option<i64>, info: &ai::TransactionInfo) -> Transaction {
+    let currency = info.currency.as_deref()
+        .and_then(|c| currency::normalize(c).ok())
+        .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+    Transaction {
+        id: 0,
+        memo_id,
+        tx_type: info.tx_type.clone(),
+        amount: currency::to_minor(info.amount, &currency),
+        description: info.description.clone(),
+        category: info.category.clone(),
+        tx_date: info.tx_date.clone(),
+        created_at: String::new(),
+        currency,
+        account_id: None,
+        to_account_id: None,
+        to_amount: None,
+    }
+}
+
+// 기간 내 일정 발생 목록 (반복 일정 전개, start_date ~ end_date 로컬 날짜, 양 끝 포함)
+#[tauri::command]
+fn get_schedule_occurrences(start_date: String, end_date: String) -> Result<Vec<db::ScheduleOccurrence>, String> {
+    let start = chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|_| format!("잘못된 시작일: {}", start_date))?;
+    let end = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|_| format!("잘못된 종료일: {}", end_date))?;
+    let end_exclusive = end.succ_opt().ok_or("잘못된 종료일")?;
+    db::get_schedule_occurrences(
+        &start.format("%Y-%m-%d").to_string(),
+        &end_exclusive.format("%Y-%m-%d").to_string(),
+    ).map_err(|e| e.to_string())
+}
+
+// 반복 규칙 설정 (None이면 반복 해제)
+#[tauri::command]
+fn set_schedule_recurrence(id: i64, recurrence: Option<String>) -> Result<(), String> {
+    let schedule = db::get_schedule(id).map_err(|e| e.to_string())?.ok_or("일정을 찾을 수 없습니다")?;
+    let rule = match recurrence.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        Some(rule) => {
+            let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+            recurrence::parse_rrule(rule)?;
+            Some(rule.to_string())
+        }
+        None => None,
+    };
+    // 반복을 해제하면 제외 날짜도 의미가 없으므로 정리
+    let exdates = if rule.is_some() { schedule.exdates } else { None };
+    db::update_schedule_recurrence(id, rule.as_deref(), exdates.as_deref()).map_err(|e| e.to_string())
+}
+
+// 반복 일정의 특정 회차 건너뛰기 (EXDATE 추가)
+#[tauri::command]
+fn skip_schedule_occurrence(id: i64, occurrence_start: String) -> Result<(), String> {
+    let schedule = db::get_schedule(id).map_err(|e| e.to_string())?.ok_or("일정을 찾을 수 없습니다")?;
+    if schedule.recurrence.as_deref().unwrap_or("").is_empty() {
+        return Err("반복 일정이 아닙니다".to_string());
+    }
+    if recurrence::parse_datetime(&occurrence_start).is_none() {
+        return Err(format!("잘못된 일시: {}", occurrence_start));
+    }
+
+    let mut exdates: Vec<String> = schedule
+        .exdates
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    if !exdates.contains(&occurrence_start) {
+        exdates.push(occurrence_start);
+    }
+    db::update_schedule_recurrence(id, schedule.recurrence.as_deref(), Some(&exdates.join(",")))
+        .map_err(|e| e.to_string())
+}
+
 // 일정 삭제 (원본 메모도 함께 삭제)
 #[tauri::command]
 fn delete_schedule(id: i64) -> Result<(), String> {
     // 먼저 연결된 메모 ID 조회
     if let Ok(Some(memo_id)) = db::get_schedule_memo_id(id) {
-        db::delete_memo(memo_id).ok(); // 메모 삭제 (실패해도 계속)
+        delete_memo_with_attachments(memo_id).ok(); // 메모 삭제 (실패해도 계속)
     }
     db::delete_schedule(id).map_err(|e| e.to_string())
 }
@@ -507,199 +948,1963 @@ fn get_todos() -> Result<Vec<Todo>, String> {
 }
 
 // 할일 완료 토글
+// 하위 할일이 남아 있으면 설정(todo_parent_completion)에 따라 막거나(block) 함께 완료(cascade)
+// 다시 미완료로 바꾸면 완료돼 있던 상위 할일도 미완료로
 #[tauri::command]
 fn toggle_todo(id: i64) -> Result<(), String> {
-    db::toggle_todo(id).map_err(|e| e.to_string())
+    let todo = db::get_todo(id).map_err(|e| e.to_string())?
+        .ok_or("할일을 찾을 수 없습니다")?;
+
+    if todo.completed {
+        let reopen: Vec<i64> = db::get_todo_ancestors(id).map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|t| t.completed)
+            .map(|t| t.id)
+            .collect();
+        db::set_todos_completed(&reopen, false).map_err(|e| e.to_string())?;
+        if todo.series_id.is_some() {
+            db::delete_todo_completion(id).map_err(|e| e.to_string())?;
+        }
+        return db::toggle_todo(id).map_err(|e| e.to_string());
+    }
+
+    let open: Vec<Todo> = db::get_todo_descendants(id).map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|t| !t.completed)
+        .collect();
+    if !open.is_empty() {
+        let mode = db::get_setting("todo_parent_completion").map_err(|e| e.to_string())?;
+        if mode == "cascade" {
+            let ids: Vec<i64> = open.iter().map(|t| t.id).collect();
+            db::set_todos_completed(&ids, true).map_err(|e| e.to_string())?;
+            // 함께 완료한 반복 하위 할일도 직접 완료할 때처럼 완료 기록을 남기고 다음 회차를 만듦
+            for child in open.iter().filter(|t| t.recurrence.is_some()) {
+                complete_recurring_todo(child)?;
+            }
+        } else {
+            return Err(format!("완료되지 않은 하위 할일이 {}개 있습니다", open.len()));
+        }
+    }
+    db::toggle_todo(id).map_err(|e| e.to_string())?;
+    if todo.recurrence.is_some() {
+        complete_recurring_todo(&todo)?;
+    }
+    Ok(())
+}
+
+// 반복 할일 완료 기록 (마감일 안에 끝냈는지 포함) → 묶음 ID 반환
+fn record_todo_completion(todo: &Todo) -> Result<i64, String> {
+    let series_id = todo.series_id.unwrap_or(todo.id);
+    let today = chrono::Local::now().date_naive();
+    let on_time = todo.due_date.as_deref()
+        .and_then(recurrence::parse_datetime)
+        .map(|due| due.date() >= today)
+        .unwrap_or(true);
+    db::save_todo_completion(series_id, todo, on_time).map_err(|e| e.to_string())?;
+    Ok(series_id)
+}
+
+// 반복 할일 완료: 완료 기록을 남기고 다음 마감일로 새 할일 생성 (하위 할일도 미완료 상태로 복사)
+// 같은 묶음에 이미 미완료 할일이 있으면 (완료 취소 후 다시 완료한 경우 등) 새로 만들지 않음
+fn complete_recurring_todo(todo: &Todo) -> Result<(), String> {
+    let series_id = record_todo_completion(todo)?;
+    let today = chrono::Local::now().date_naive();
+
+    let Some(rule) = todo.recurrence.as_deref().and_then(|r| recurrence::parse_rrule(r).ok()) else {
+        return Ok(());
+    };
+    let series = db::get_todos_in_series(series_id).map_err(|e| e.to_string())?;
+    if series.iter().any(|t| !t.completed && t.id != todo.id) {
+        return Ok(());
+    }
+    if let Some(count) = rule.count {
+        let done = db::get_todo_completions(series_id, None).map_err(|e| e.to_string())?.len();
+        if done >= count as usize {
+            return Ok(());
+        }
+    }
+    let Some(next_due) = recurrence::next_due(&rule, todo.due_date.as_deref(), today) else {
+        return Ok(());  // UNTIL이 지나 반복 종료
+    };
+
+    let shift = todo.due_date.as_deref()
+        .and_then(recurrence::parse_datetime)
+        .zip(recurrence::parse_datetime(&next_due))
+        .map(|(old, new)| new - old);
+    let next_id = db::save_todo(&Todo {
+        id: 0,
+        completed: false,
+        due_date: Some(next_due),
+        created_at: String::new(),
+        completed_at: None,
+        series_id: Some(series_id),
+        ..todo.clone()
+    }).map_err(|e| e.to_string())?;
+
+    let subtasks = db::get_todo_descendants(todo.id).map_err(|e| e.to_string())?;
+    copy_subtasks(&subtasks, todo.id, next_id, shift)
+}
+
+// 하위 할일 트리 복사 (마감일은 상위 할일이 옮겨간 만큼 이동)
+// 반복 하위 할일은 자기 반복으로 다음 회차가 만들어지므로 복사하지 않고, 미완료인 다음 회차를 새 상위 할일 아래로 옮김
+fn copy_subtasks(all: &[Todo], from: i64, to: i64, shift: Option<chrono::Duration>) -> Result<(), String> {
+    for child in all.iter().filter(|t| t.parent_id == Some(from)) {
+        if !child.completed {
+            db::set_todo_parent(child.id, Some(to)).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if child.recurrence.is_some() {
+            continue;
+        }
+        let due_date = match (child.due_date.as_deref(), shift) {
+            (Some(due), Some(shift)) => recurrence::parse_datetime(due)
+                .map(|dt| recurrence::format_like(&(dt + shift), due)),
+            _ => None,
+        };
+        let id = db::save_todo(&Todo {
+            id: 0,
+            completed: false,
+            due_date,
+            created_at: String::new(),
+            parent_id: Some(to),
+            completed_at: None,
+            recurrence: None,
+            series_id: None,
+            ..child.clone()
+        }).map_err(|e| e.to_string())?;
+        copy_subtasks(all, child.id, id, shift)?;
+    }
+    Ok(())
+}
+
+// 반복 규칙 지정/해제 (recurrence 없으면 반복 해제)
+#[tauri::command]
+fn set_todo_recurrence(id: i64, recurrence: Option<String>) -> Result<(), String> {
+    let rule = recurrence.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    let rule = match rule {
+        Some(rule) => {
+            let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+            recurrence::parse_rrule(rule)?;
+            Some(rule)
+        }
+        None => None,
+    };
+    db::set_todo_recurrence(id, rule).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoSeriesStats {
+    pub series_id: i64,
+    pub title: String,
+    pub recurrence: Option<String>,
+    pub open_todo_id: Option<i64>,      // 현재 진행 중인 회차
+    pub next_due: Option<String>,
+    pub total_completions: usize,
+    pub on_time_completions: usize,
+    pub current_streak: usize,          // 최근부터 연속으로 기한 내 완료한 횟수 (현재 회차가 밀려 있으면 0)
+    pub longest_streak: usize,
+    pub last_completed_at: Option<String>,
+}
+
+// 반복 할일 묶음별 통계 (연속 달성, 완료 횟수)
+fn todo_series_stats(series_id: i64) -> Result<Option<TodoSeriesStats>, String> {
+    let todos = db::get_todos_in_series(series_id).map_err(|e| e.to_string())?;
+    let completions = db::get_todo_completions(series_id, None).map_err(|e| e.to_string())?;
+    let open = todos.iter().find(|t| !t.completed);
+    let Some(latest) = open.or(todos.first()) else {
+        return Ok(None);
+    };
+
+    let today = chrono::Local::now().date_naive();
+    let overdue = open
+        .and_then(|t| t.due_date.as_deref())
+        .and_then(recurrence::parse_datetime)
+        .is_some_and(|due| due.date() < today);
+    let current_streak = if overdue { 0 } else { completions.iter().take_while(|c| c.on_time).count() };
+    let longest_streak = completions
+        .split(|c| !c.on_time)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+
+    Ok(Some(TodoSeriesStats {
+        series_id,
+        title: latest.title.clone(),
+        recurrence: latest.recurrence.clone(),
+        open_todo_id: open.map(|t| t.id),
+        next_due: open.and_then(|t| t.due_date.clone()),
+        total_completions: completions.len(),
+        on_time_completions: completions.iter().filter(|c| c.on_time).count(),
+        current_streak,
+        longest_streak,
+        last_completed_at: completions.first().map(|c| c.completed_at.clone()),
+    }))
+}
+
+// 반복 할일 통계 (series_id 없으면 전체)
+#[tauri::command]
+fn get_todo_streaks(series_id: Option<i64>) -> Result<Vec<TodoSeriesStats>, String> {
+    let ids = match series_id {
+        Some(id) => vec![id],
+        None => db::get_todo_series_ids().map_err(|e| e.to_string())?,
+    };
+    let mut stats = Vec::new();
+    for id in ids {
+        if let Some(item) = todo_series_stats(id)? {
+            stats.push(item);
+        }
+    }
+    Ok(stats)
+}
+
+// 반복 할일 완료 기록 (최근 순)
+#[tauri::command]
+fn get_todo_history(series_id: i64, limit: Option<i64>) -> Result<Vec<db::TodoCompletion>, String> {
+    db::get_todo_completions(series_id, limit).map_err(|e| e.to_string())
 }
 
-// 할일 삭제 (원본 메모도 함께 삭제)
+// 할일 삭제 (하위 할일도 함께, 최상위 할일이면 원본 메모도 함께 삭제)
 #[tauri::command]
 fn delete_todo(id: i64) -> Result<(), String> {
-    // 먼저 연결된 메모 ID 조회
-    if let Ok(Some(memo_id)) = db::get_todo_memo_id(id) {
-        db::delete_memo(memo_id).ok(); // 메모 삭제 (실패해도 계속)
+    let todo = db::get_todo(id).map_err(|e| e.to_string())?;
+    // 하위 할일은 같은 메모를 공유하므로 메모는 남겨둠
+    if let Some(memo_id) = todo.filter(|t| t.parent_id.is_none()).and_then(|t| t.memo_id) {
+        delete_memo_with_attachments(memo_id).ok(); // 메모 삭제 (실패해도 계속)
     }
     db::delete_todo(id).map_err(|e| e.to_string())
 }
 
-// 메모 페이징 조회
+// 하위 할일로 옮기기 (parent_id 없으면 최상위로)
 #[tauri::command]
-fn get_memos_paginated(offset: i64, limit: i64) -> Result<Vec<Memo>, String> {
-    db::get_memos_paginated(offset, limit).map_err(|e| e.to_string())
+fn set_todo_parent(id: i64, parent_id: Option<i64>) -> Result<(), String> {
+    if let Some(parent_id) = parent_id {
+        if parent_id == id {
+            return Err("자기 자신을 상위 할일로 지정할 수 없습니다".to_string());
+        }
+        let parent = db::get_todo(parent_id).map_err(|e| e.to_string())?
+            .ok_or("상위 할일을 찾을 수 없습니다")?;
+        let descendants = db::get_todo_descendants(id).map_err(|e| e.to_string())?;
+        if descendants.iter().any(|t| t.id == parent_id) {
+            return Err("하위 할일 아래로 옮길 수 없습니다".to_string());
+        }
+        // 부모의 프로젝트를 따라감
+        let mut ids = vec![id];
+        ids.extend(descendants.iter().map(|t| t.id));
+        db::set_todo_project(&ids, parent.project.as_deref()).map_err(|e| e.to_string())?;
+    }
+    db::set_todo_parent(id, parent_id).map_err(|e| e.to_string())
 }
 
-// 메모 총 개수
+// 프로젝트(목록) 지정 - 하위 할일도 함께 이동, 빈 문자열이면 프로젝트 해제
 #[tauri::command]
-fn get_memo_count() -> Result<i64, String> {
-    db::get_memo_count().map_err(|e| e.to_string())
+fn set_todo_project(id: i64, project: Option<String>) -> Result<(), String> {
+    let project = project.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let mut ids = vec![id];
+    ids.extend(db::get_todo_descendants(id).map_err(|e| e.to_string())?.iter().map(|t| t.id));
+    db::set_todo_project(&ids, project.as_deref()).map_err(|e| e.to_string())
 }
 
-// 모든 거래 조회
+// 할일 순서 변경 (드래그 정렬 결과를 순서대로 전달)
 #[tauri::command]
-fn get_transactions() -> Result<Vec<Transaction>, String> {
-    db::get_all_transactions().map_err(|e| e.to_string())
+fn reorder_todos(ids: Vec<i64>) -> Result<(), String> {
+    db::reorder_todos(&ids).map_err(|e| e.to_string())
 }
 
-// 거래 삭제 (원본 메모도 함께 삭제)
+// 프로젝트 목록
 #[tauri::command]
-fn delete_transaction(id: i64) -> Result<(), String> {
-    // 먼저 연결된 메모 ID 조회
-    if let Ok(Some(memo_id)) = db::get_transaction_memo_id(id) {
-        db::delete_memo(memo_id).ok(); // 메모 삭제 (실패해도 계속)
-    }
-    db::delete_transaction(id).map_err(|e| e.to_string())
+fn get_todo_projects() -> Result<Vec<db::TodoProject>, String> {
+    db::get_todo_projects().map_err(|e| e.to_string())
+}
+
+// ===== iCalendar 내보내기/가져오기 =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IcsImportResult {
+    pub created_schedules: usize,
+    pub updated_schedules: usize,
+    pub created_todos: usize,
+    pub updated_todos: usize,
+    pub memo_id: Option<i64>,
+}
+
+// 일정/할일을 .ics 파일로 내보내기 (start_date ~ end_date 로컬 날짜, 양 끝 포함, 없으면 전체)
+// 기간을 지정하면 그 기간에 발생하는 일정(반복 포함)과 마감일이 그 기간인 할일만
+#[tauri::command]
+fn export_ics(start_date: Option<String>, end_date: Option<String>) -> Result<String, String> {
+    let range = match (start_date.as_deref(), end_date.as_deref()) {
+        (Some(start), Some(end)) => {
+            let start = chrono::NaiveDate::parse_from_str(start, "%Y-%m-%d")
+                .map_err(|_| format!("잘못된 시작일: {}", start))?;
+            let end = chrono::NaiveDate::parse_from_str(end, "%Y-%m-%d")
+                .map_err(|_| format!("잘못된 종료일: {}", end))?;
+            if end < start {
+                return Err("종료일이 시작일보다 빠릅니다".to_string());
+            }
+            Some((start, end))
+        }
+        (None, None) => None,
+        _ => return Err("시작일과 종료일을 함께 지정해주세요".to_string()),
+    };
+
+    let (schedules, todos) = match range {
+        Some((start, end)) => {
+            let end_exclusive = end.succ_opt().ok_or("잘못된 종료일")?;
+            let mut schedules: Vec<Schedule> = Vec::new();
+            for occurrence in db::get_schedule_occurrences(
+                &start.format("%Y-%m-%d").to_string(),
+                &end_exclusive.format("%Y-%m-%d").to_string(),
+            ).map_err(|e| e.to_string())? {
+                if !schedules.iter().any(|s| s.id == occurrence.schedule.id) {
+                    schedules.push(occurrence.schedule);
+                }
+            }
+            let todos: Vec<Todo> = db::get_all_todos()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|t| {
+                    t.due_date
+                        .as_deref()
+                        .and_then(recurrence::parse_datetime)
+                        .map(|due| due.date() >= start && due.date() <= end)
+                        .unwrap_or(false)
+                })
+                .collect();
+            (schedules, todos)
+        }
+        None => (
+            db::get_all_schedules().map_err(|e| e.to_string())?,
+            db::get_all_todos().map_err(|e| e.to_string())?,
+        ),
+    };
+
+    let mut calendar = ics::IcsCalendar::default();
+    for schedule in schedules {
+        calendar.events.push(ics::IcsEvent {
+            uid: db::ensure_ical_uid("schedules", schedule.id).map_err(|e| e.to_string())?,
+            summary: schedule.title,
+            start: schedule.start_time,
+            end: schedule.end_time,
+            location: schedule.location,
+            description: schedule.description,
+            rrule: schedule.recurrence,
+            exdates: schedule
+                .exdates
+                .as_deref()
+                .unwrap_or("")
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            last_modified: None,
+        });
+    }
+    for todo in todos {
+        calendar.todos.push(ics::IcsTodo {
+            uid: db::ensure_ical_uid("todos", todo.id).map_err(|e| e.to_string())?,
+            summary: todo.title,
+            due: todo.due_date,
+            completed: todo.completed,
+            priority: todo.priority,
+            description: None,
+        });
+    }
+
+    let downloads_dir = dirs::download_dir()
+        .ok_or("다운로드 폴더를 찾을 수 없습니다")?;
+    let filename = format!("jolajoa_calendar_{}.ics", chrono::Local::now().format("%Y%m%d_%H%M%S"));
+    let filepath = downloads_dir.join(&filename);
+    std::fs::write(&filepath, ics::build_calendar(&calendar))
+        .map_err(|e| format!("파일 생성 실패: {}", e))?;
+
+    Ok(filepath.to_string_lossy().to_string())
+}
+
+// .ics 가져오기로 만든 메모의 제목 접두어와 태그 (다시 가져올 때 이 메모만 재사용)
+const ICS_IMPORT_TITLE_PREFIX: &str = "[캘린더 가져오기]";
+const ICS_IMPORT_TAGS: &str = "캘린더,가져오기";
+
+// .ics 파일 가져오기 (UID가 같은 일정/할일은 갱신, 없으면 새로 추가)
+// 가져온 항목 목록으로 메모를 하나 만들고(다시 가져오면 기존 메모 갱신), 메모가 연결되지 않은 항목을 그 메모에 연결
+#[tauri::command]
+fn import_ics(file_path: String) -> Result<IcsImportResult, String> {
+    let bytes = std::fs::read(&file_path).map_err(|e| format!("파일 읽기 실패: {}", e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let calendar = ics::parse_calendar(&text);
+    if calendar.events.is_empty() && calendar.todos.is_empty() {
+        return Err("가져올 일정이나 할일이 없습니다".to_string());
+    }
+
+    // 가져온 항목을 정리한 메모
+    let file_name = std::path::Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+    let mut content = String::new();
+    if !calendar.events.is_empty() {
+        content.push_str("## 일정\n");
+        for event in &calendar.events {
+            content.push_str(&format!("- {} ({})", event.summary, event.start.as_deref().unwrap_or("날짜 없음")));
+            if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+                content.push_str(&format!(" @ {}", location));
+            }
+            content.push('\n');
+        }
+    }
+    if !calendar.todos.is_empty() {
+        content.push_str("\n## 할일\n");
+        for todo in &calendar.todos {
+            let check = if todo.completed { "x" } else { " " };
+            match todo.due.as_deref() {
+                Some(due) => content.push_str(&format!("- [{}] {} (마감: {})\n", check, todo.summary, due)),
+                None => content.push_str(&format!("- [{}] {}\n", check, todo.summary)),
+            }
+        }
+    }
+    let summary = format!(
+        "{}에서 일정 {}개, 할일 {}개를 가져왔습니다",
+        file_name,
+        calendar.events.len(),
+        calendar.todos.len()
+    );
+
+    // 같은 파일을 다시 가져오면 이전 가져오기로 만든 메모를 갱신해서 재사용
+    // (직접 쓴 메모에서 추출한 항목을 내보냈다 다시 가져온 경우에는 그 메모를 건드리지 않고 새로 만듦)
+    let mut linked_memo = None;
+    let uids = calendar
+        .events
+        .iter()
+        .map(|e| ("schedules", e.uid.as_str()))
+        .chain(calendar.todos.iter().map(|t| ("todos", t.uid.as_str())));
+    for (table, uid) in uids {
+        linked_memo = db::find_import_memo_by_ical_uid(table, uid, ICS_IMPORT_TITLE_PREFIX, ICS_IMPORT_TAGS)
+            .map_err(|e| e.to_string())?;
+        if linked_memo.is_some() {
+            break;
+        }
+    }
+    let memo_id = match linked_memo {
+        Some(id) => {
+            db::update_memo(id, &content, &content, &summary, ICS_IMPORT_TAGS, None).map_err(|e| e.to_string())?;
+            id
+        }
+        None => db::save_memo(&Memo {
+            id: 0,
+            title: format!("{} {}", ICS_IMPORT_TITLE_PREFIX, file_name),
+            content: content.clone(),
+            formatted_content: content,
+            summary,
+            category: "일정".to_string(),
+            tags: ICS_IMPORT_TAGS.to_string(),
+            embedding: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }).map_err(|e| e.to_string())?,
+    };
+
+    let mut result = IcsImportResult {
+        created_schedules: 0,
+        updated_schedules: 0,
+        created_todos: 0,
+        updated_todos: 0,
+        memo_id: Some(memo_id),
+    };
+
+    for event in calendar.events {
+        let title = if event.summary.trim().is_empty() { "(제목 없음)".to_string() } else { event.summary };
+        let exdates = if event.exdates.is_empty() { None } else { Some(event.exdates.join(",")) };
+        let recurrence = valid_recurrence(event.rrule.as_deref());
+        let existing = match db::find_by_ical_uid("schedules", &event.uid).map_err(|e| e.to_string())? {
+            Some(id) => db::get_schedule(id).map_err(|e| e.to_string())?,
+            None => None,
+        };
+
+        match existing {
+            Some(existing) => {
+                db::update_schedule(&Schedule {
+                    memo_id: existing.memo_id.or(Some(memo_id)),
+                    title,
+                    start_time: event.start,
+                    end_time: event.end,
+                    location: event.location,
+                    description: event.description,
+                    exdates: recurrence.as_ref().and(exdates),
+                    recurrence,
+                    ..existing
+                }).map_err(|e| e.to_string())?;
+                result.updated_schedules += 1;
+            }
+            None => {
+                let id = db::save_schedule(&Schedule {
+                    id: 0,
+                    memo_id: Some(memo_id),
+                    title,
+                    start_time: event.start,
+                    end_time: event.end,
+                    location: event.location,
+                    description: event.description,
+                    google_event_id: None,
+                    created_at: String::new(),
+                    exdates: recurrence.as_ref().and(exdates),
+                    recurrence,
+                }).map_err(|e| e.to_string())?;
+                db::set_ical_uid("schedules", id, &event.uid).map_err(|e| e.to_string())?;
+                result.created_schedules += 1;
+            }
+        }
+    }
+
+    for item in calendar.todos {
+        let title = if item.summary.trim().is_empty() { "(제목 없음)".to_string() } else { item.summary };
+        match db::find_by_ical_uid("todos", &item.uid).map_err(|e| e.to_string())? {
+            Some(id) => {
+                let existing = db::get_todo(id).map_err(|e| e.to_string())?
+                    .ok_or("할일을 찾을 수 없습니다")?;
+                db::update_todo(&Todo {
+                    memo_id: existing.memo_id.or(Some(memo_id)),
+                    title,
+                    completed: item.completed,
+                    priority: item.priority,
+                    due_date: item.due,
+                    ..existing
+                }).map_err(|e| e.to_string())?;
+                result.updated_todos += 1;
+            }
+            None => {
+                let id = db::save_todo(&Todo {
+                    id: 0,
+                    memo_id: Some(memo_id),
+                    title,
+                    completed: item.completed,
+                    priority: item.priority,
+                    due_date: item.due,
+                    created_at: String::new(),
+                    parent_id: None,
+                    project: None,
+                    sort_order: None,
+                    completed_at: None,
+                    recurrence: None,
+                    series_id: None,
+                }).map_err(|e| e.to_string())?;
+                db::set_ical_uid("todos", id, &item.uid).map_err(|e| e.to_string())?;
+                result.created_todos += 1;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// ===== CalDAV 동기화 =====
+
+// CalDAV 서버와 일정 양방향 동기화 (설정: caldav_url, caldav_username, caldav_password, caldav_conflict_policy)
+#[tauri::command]
+async fn sync_caldav() -> Result<caldav::SyncReport, String> {
+    let config = caldav::CalDavConfig::load()?;
+    caldav::sync(config).await
+}
+
+// CalDAV 연결 테스트
+#[tauri::command]
+async fn test_caldav_connection(url: String, username: String, password: String) -> Result<String, String> {
+    caldav::test_connection(&url, &username, &password).await
+}
+
+// 사용자 선택을 기다리는 동기화 충돌 목록
+#[tauri::command]
+fn get_caldav_conflicts() -> Result<Vec<db::CalDavConflict>, String> {
+    db::get_caldav_conflicts().map_err(|e| e.to_string())
+}
+
+// 동기화 충돌 해결 (keep: "local" 또는 "remote")
+#[tauri::command]
+fn resolve_caldav_conflict(id: i64, keep: String) -> Result<(), String> {
+    let keep_local = match keep.as_str() {
+        "local" => true,
+        "remote" => false,
+        _ => return Err(format!("잘못된 선택: {} (local 또는 remote)", keep)),
+    };
+    caldav::resolve_conflict(id, keep_local)
+}
+
+// 메모 페이징 조회
+#[tauri::command]
+fn get_memos_paginated(offset: i64, limit: i64) -> Result<Vec<Memo>, String> {
+    db::get_memos_paginated(offset, limit).map_err(|e| e.to_string())
+}
+
+// 메모 총 개수
+#[tauri::command]
+fn get_memo_count() -> Result<i64, String> {
+    db::get_memo_count().map_err(|e| e.to_string())
+}
+
+// 모든 거래 조회
+#[tauri::command]
+fn get_transactions() -> Result<Vec<Transaction>, String> {
+    db::get_all_transactions().map_err(|e| e.to_string())
+}
+
+// 거래 삭제 (원본 메모도 함께 삭제)
+#[tauri::command]
+fn delete_transaction(id: i64) -> Result<(), String> {
+    // 먼저 연결된 메모 ID 조회
+    if let Ok(Some(memo_id)) = db::get_transaction_memo_id(id) {
+        delete_memo_with_attachments(memo_id).ok(); // 메모 삭제 (실패해도 계속)
+    }
+    db::delete_transaction(id).map_err(|e| e.to_string())
+}
+
+// 거래 수정 추가 항목 (없으면 기존 값 유지)
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TransactionDetails {
+    pub currency: Option<String>,
+    pub account_id: Option<i64>,
+    pub to_account_id: Option<i64>,
+    pub to_amount: Option<i64>,
+}
+
+#[tauri::command]
+fn update_transaction(
+    id: i64,
+    tx_type: String,
+    amount: i64,
+    description: String,
+    category: Option<String>,
+    tx_date: Option<String>,
+    details: Option<TransactionDetails>,
+) -> Result<(), String> {
+    let existing = db::get_transaction(id).map_err(|e| e.to_string())?
+        .ok_or("거래를 찾을 수 없습니다")?;
+    let details = details.unwrap_or_default();
+    let currency = match details.currency.as_deref() {
+        Some(code) => currency::normalize(code)?,
+        None => existing.currency.clone(),
+    };
+    if let Some(account_id) = details.account_id {
+        db::get_account(account_id).map_err(|e| e.to_string())?.ok_or("계좌를 찾을 수 없습니다")?;
+    }
+    // 분류를 고치면 같은 적요의 다음 거래부터 자동 분류되도록 학습
+    let new_category = category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if new_category.is_some() && new_category != existing.category.as_deref().map(str::trim) {
+        let pattern = bank_import::category_pattern(&description);
+        if let Some(new_category) = new_category.filter(|_| !pattern.is_empty()) {
+            db::learn_category_rule(&pattern, new_category).ok();
+        }
+    }
+    db::update_transaction(&Transaction {
+        tx_type,
+        amount,
+        description,
+        category,
+        tx_date,
+        currency,
+        account_id: details.account_id.or(existing.account_id),
+        to_account_id: details.to_account_id.or(existing.to_account_id),
+        to_amount: details.to_amount.or(existing.to_amount),
+        ..existing
+    })
+    .map_err(|e| e.to_string())
+}
+
+// ===== 계좌/이체/환율 =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account: db::Account,
+    pub balance: i64,              // 계좌 통화 기준 (최소 단위)
+    pub transaction_count: i64,
+    pub missing_rates: Vec<String>, // 환율이 없어 잔액에서 빠진 통화
+}
+
+const ACCOUNT_TYPES: [&str; 4] = ["cash", "card", "bank", "other"];
+
+// 계좌 목록과 잔액 (계좌 통화와 다른 통화의 거래는 오늘 환율로 환산)
+#[tauri::command]
+fn get_accounts() -> Result<Vec<AccountBalance>, String> {
+    let accounts = db::get_all_accounts().map_err(|e| e.to_string())?;
+    let flows = db::get_account_flows().map_err(|e| e.to_string())?;
+    let base = db::get_base_currency().map_err(|e| e.to_string())?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    accounts
+        .into_iter()
+        .map(|account| {
+            let mut balance = account.opening_balance;
+            let mut transaction_count = 0;
+            let mut missing_rates = Vec::new();
+            for flow in flows.iter().filter(|f| f.account_id == account.id) {
+                transaction_count += flow.count;
+                let rate_from = db::get_exchange_rate(&flow.currency, &base, &today).map_err(|e| e.to_string())?;
+                let rate_to = db::get_exchange_rate(&account.currency, &base, &today).map_err(|e| e.to_string())?;
+                match rate_from.zip(rate_to) {
+                    Some((from, to)) => balance += currency::convert(flow.amount, &flow.currency, &account.currency, from, to),
+                    None => missing_rates.push(flow.currency.clone()),
+                }
+            }
+            Ok(AccountBalance { account, balance, transaction_count, missing_rates })
+        })
+        .collect()
+}
+
+// 계좌 추가/수정 (id 없으면 추가) - 계좌 id 반환
+#[tauri::command]
+fn save_account(id: Option<i64>, name: String, account_type: String, currency: Option<String>, opening_balance: Option<i64>) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("계좌 이름을 입력해주세요".to_string());
+    }
+    if !ACCOUNT_TYPES.contains(&account_type.as_str()) {
+        return Err("계좌 종류는 cash, card, bank, other 중 하나여야 합니다".to_string());
+    }
+    let currency = match currency.as_deref() {
+        Some(code) => currency::normalize(code)?,
+        None => db::get_base_currency().map_err(|e| e.to_string())?,
+    };
+    db::save_account(&db::Account {
+        id: id.unwrap_or(0),
+        name: name.to_string(),
+        account_type,
+        currency,
+        opening_balance: opening_balance.unwrap_or(0),
+        created_at: String::new(),
+    })
+    .map_err(|e| e.to_string())
+}
+
+// 계좌 삭제 (거래는 남김)
+#[tauri::command]
+fn delete_account(id: i64) -> Result<(), String> {
+    db::delete_account(id).map_err(|e| e.to_string())
+}
+
+// 계좌 간 이체 (amount는 보내는 계좌 통화, 통화가 다르면 to_amount 또는 환율로 환산) - 거래 id 반환
+#[tauri::command]
+fn create_transfer(
+    from_account_id: i64,
+    to_account_id: i64,
+    amount: i64,
+    to_amount: Option<i64>,
+    description: Option<String>,
+    tx_date: Option<String>,
+) -> Result<i64, String> {
+    if from_account_id == to_account_id {
+        return Err("같은 계좌로는 이체할 수 없습니다".to_string());
+    }
+    if amount <= 0 {
+        return Err("이체 금액은 0보다 커야 합니다".to_string());
+    }
+    let from = db::get_account(from_account_id).map_err(|e| e.to_string())?.ok_or("보내는 계좌를 찾을 수 없습니다")?;
+    let to = db::get_account(to_account_id).map_err(|e| e.to_string())?.ok_or("받는 계좌를 찾을 수 없습니다")?;
+    let date = tx_date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+
+    let to_amount = if from.currency == to.currency {
+        None
+    } else if to_amount.is_some() {
+        to_amount
+    } else {
+        let base = db::get_base_currency().map_err(|e| e.to_string())?;
+        let rate_from = db::get_exchange_rate(&from.currency, &base, &date).map_err(|e| e.to_string())?;
+        let rate_to = db::get_exchange_rate(&to.currency, &base, &date).map_err(|e| e.to_string())?;
+        let (rate_from, rate_to) = rate_from.zip(rate_to)
+            .ok_or_else(|| format!("{} → {} 환율이 없습니다. 받는 금액을 입력하거나 환율을 등록해주세요", from.currency, to.currency))?;
+        Some(currency::convert(amount, &from.currency, &to.currency, rate_from, rate_to))
+    };
+
+    db::save_transaction(&Transaction {
+        id: 0,
+        memo_id: None,
+        tx_type: "transfer".to_string(),
+        amount,
+        description: description.filter(|d| !d.trim().is_empty())
+            .unwrap_or_else(|| format!("{} → {}", from.name, to.name)),
+        category: Some("이체".to_string()),
+        tx_date: Some(date),
+        created_at: String::new(),
+        currency: from.currency,
+        account_id: Some(from.id),
+        to_account_id: Some(to.id),
+        to_amount,
+    })
+    .map_err(|e| e.to_string())
+}
+
+// 환율 목록
+#[tauri::command]
+fn get_exchange_rates() -> Result<Vec<db::ExchangeRate>, String> {
+    db::get_exchange_rates().map_err(|e| e.to_string())
+}
+
+// 환율 등록 (1 currency = rate 기준 통화, rate_date부터 적용, 없으면 오늘)
+#[tauri::command]
+fn save_exchange_rate(currency: String, rate: f64, rate_date: Option<String>) -> Result<i64, String> {
+    let code = currency::normalize(&currency)?;
+    let base = db::get_base_currency().map_err(|e| e.to_string())?;
+    if code == base {
+        return Err("기준 통화의 환율은 등록할 필요가 없습니다".to_string());
+    }
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err("환율은 0보다 커야 합니다".to_string());
+    }
+    let rate_date = rate_date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    chrono::NaiveDate::parse_from_str(&rate_date, "%Y-%m-%d").map_err(|_| format!("잘못된 날짜: {}", rate_date))?;
+    db::save_exchange_rate(&code, &base, rate, &rate_date).map_err(|e| e.to_string())
+}
+
+// 환율 삭제
+#[tauri::command]
+fn delete_exchange_rate(id: i64) -> Result<(), String> {
+    db::delete_exchange_rate(id).map_err(|e| e.to_string())
+}
+
+// 기준 통화 변경 (리포트/예산 환산 기준, 환율은 기준 통화별로 따로 관리)
+// 예산 한도는 기준 통화 금액이므로 오늘 환율로 새 통화로 바꿈 (환율이 없으면 변경하지 않음)
+// 계좌 기초 잔액은 계좌 통화로 저장돼 있어 그대로 둠
+#[tauri::command]
+fn set_base_currency(currency: String) -> Result<String, String> {
+    let code = currency::normalize(&currency)?;
+    let old = db::get_base_currency().map_err(|e| e.to_string())?;
+    if code == old {
+        return Ok(code);
+    }
+    let budgets = db::get_all_transaction_budgets().map_err(|e| e.to_string())?;
+    let mut limits = Vec::new();
+    if !budgets.is_empty() {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        // 이전 기준으로 등록한 환율 (1 새 통화 = rate 이전 통화), 없으면 새 기준으로 등록한 환율
+        let (rate_from, rate_to) = match db::get_exchange_rate(&code, &old, &today).map_err(|e| e.to_string())? {
+            Some(rate) => (1.0, rate),
+            None => db::get_exchange_rate(&old, &code, &today)
+                .map_err(|e| e.to_string())?
+                .map(|rate| (rate, 1.0))
+                .ok_or_else(|| format!("{} → {} 환율이 없어 예산 한도를 바꿀 수 없습니다. 환율을 먼저 등록해주세요", old, code))?,
+        };
+        limits = budgets
+            .iter()
+            .map(|b| (b.id, currency::convert(b.limit_amount, &old, &code, rate_from, rate_to)))
+            .collect();
+    }
+    db::change_base_currency(&code, &limits).map_err(|e| e.to_string())?;
+    Ok(code)
+}
+
+// ===== 은행 거래내역 가져오기 =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportRow {
+    #[serde(flatten)]
+    pub row: bank_import::ImportedRow,
+    pub duplicate: bool,                    // 이미 같은 거래가 있음
+    pub suggested_category: Option<String>, // 파일의 분류 또는 학습한 규칙
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportPreview {
+    pub format: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<BankImportRow>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BankImportOptions {
+    pub account_id: Option<i64>,
+    #[serde(default)]
+    pub include_duplicates: bool,           // 중복으로 판단된 거래도 저장
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportResult {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub categorized: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankCsvHeaders {
+    pub headers: Vec<String>,
+    pub guessed_mapping: Option<bank_import::CsvMapping>,
+}
+
+// 파일 파싱 + 중복 판별 + 분류 추천 (직접 준 매핑 > 프로필 > 헤더로 추측)
+fn prepare_bank_import(file_path: &str, profile_id: Option<i64>, mapping: Option<bank_import::CsvMapping>) -> Result<BankImportPreview, String> {
+    let mapping = match (mapping, profile_id) {
+        (Some(mapping), _) => Some(mapping),
+        (None, Some(id)) => {
+            let profile = db::get_bank_import_profile(id).map_err(|e| e.to_string())?
+                .ok_or("가져오기 프로필을 찾을 수 없습니다")?;
+            Some(serde_json::from_str(&profile.mapping).map_err(|e| format!("프로필 매핑 오류: {}", e))?)
+        }
+        (None, None) => None,
+    };
+    let base = db::get_base_currency().map_err(|e| e.to_string())?;
+    let statement = bank_import::parse_file(file_path, mapping.as_ref(), &base)?;
+
+    let rules = db::get_category_rules().map_err(|e| e.to_string())?;
+    let mut existing: std::collections::HashMap<String, i64> = db::get_transaction_fingerprint_counts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    let rows = statement.rows
+        .into_iter()
+        .map(|row| {
+            let fingerprint = db::transaction_fingerprint(Some(&row.tx_date), &row.tx_type, row.amount, &row.currency, &row.description);
+            // 같은 날 같은 금액의 거래가 여러 건이면 기존 건수만큼만 중복으로 처리
+            let duplicate = match existing.get_mut(&fingerprint) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            };
+            let suggested_category = row.category.clone()
+                .or_else(|| bank_import::suggest_category(&rules, &row.description).map(str::to_string));
+            BankImportRow { row, duplicate, suggested_category }
+        })
+        .collect();
+
+    Ok(BankImportPreview {
+        format: statement.format,
+        headers: statement.headers,
+        rows,
+        errors: statement.errors,
+    })
+}
+
+// CSV 헤더와 추측한 매핑 (매핑 편집 화면용)
+#[tauri::command]
+fn get_bank_csv_headers(file_path: String, encoding: Option<String>, delimiter: Option<String>, skip_rows: Option<usize>) -> Result<BankCsvHeaders, String> {
+    let bytes = std::fs::read(&file_path).map_err(|e| format!("파일을 읽을 수 없습니다: {}", e))?;
+    let text = bank_import::decode(&bytes, encoding.as_deref());
+    let headers = bank_import::read_headers(&text, delimiter.as_deref(), skip_rows.unwrap_or(0))?;
+    let guessed_mapping = bank_import::guess_mapping(&headers);
+    Ok(BankCsvHeaders { headers, guessed_mapping })
+}
+
+// 가져오기 미리보기 (저장하지 않음)
+#[tauri::command]
+fn preview_bank_import(file_path: String, profile_id: Option<i64>, mapping: Option<bank_import::CsvMapping>) -> Result<BankImportPreview, String> {
+    prepare_bank_import(&file_path, profile_id, mapping)
+}
+
+// 은행 거래내역 가져오기 (중복은 건너뜀, 분류가 없으면 학습한 규칙으로 분류)
+// 과거 내역을 한꺼번에 넣는 것이므로 예산 경고는 보내지 않음
+#[tauri::command]
+fn import_bank_statement(
+    file_path: String,
+    profile_id: Option<i64>,
+    mapping: Option<bank_import::CsvMapping>,
+    options: Option<BankImportOptions>,
+) -> Result<BankImportResult, String> {
+    let options = options.unwrap_or_default();
+    if let Some(account_id) = options.account_id {
+        db::get_account(account_id).map_err(|e| e.to_string())?.ok_or("계좌를 찾을 수 없습니다")?;
+    }
+    let preview = prepare_bank_import(&file_path, profile_id, mapping)?;
+    let mut result = BankImportResult { imported: 0, duplicates: 0, categorized: 0, errors: preview.errors };
+
+    let mut transactions = Vec::new();
+    for item in preview.rows {
+        if item.duplicate {
+            result.duplicates += 1;
+            if !options.include_duplicates {
+                continue;
+            }
+        }
+        if item.row.category.is_none() && item.suggested_category.is_some() {
+            result.categorized += 1;
+        }
+        transactions.push(Transaction {
+            id: 0,
+            memo_id: None,
+            tx_type: item.row.tx_type,
+            amount: item.row.amount,
+            description: item.row.description,
+            category: item.suggested_category,
+            tx_date: Some(item.row.tx_date),
+            created_at: String::new(),
+            currency: item.row.currency,
+            account_id: options.account_id,
+            to_account_id: None,
+            to_amount: None,
+        });
+    }
+    // 한 트랜잭션으로 저장해 중간에 실패해도 일부만 들어가지 않게 함
+    db::save_transactions(&transactions).map_err(|e| e.to_string())?;
+    result.imported = transactions.len();
+
+    Ok(result)
+}
+
+// 가져오기 프로필 목록
+#[tauri::command]
+fn get_bank_import_profiles() -> Result<Vec<db::BankImportProfile>, String> {
+    db::get_bank_import_profiles().map_err(|e| e.to_string())
+}
+
+// 가져오기 프로필 저장 (은행 이름별, 같은 이름이면 덮어씀)
+#[tauri::command]
+fn save_bank_import_profile(name: String, mapping: bank_import::CsvMapping) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("프로필 이름을 입력해주세요".to_string());
+    }
+    let mapping = serde_json::to_string(&mapping).map_err(|e| e.to_string())?;
+    db::save_bank_import_profile(name, &mapping).map_err(|e| e.to_string())
+}
+
+// 가져오기 프로필 삭제
+#[tauri::command]
+fn delete_bank_import_profile(id: i64) -> Result<(), String> {
+    db::delete_bank_import_profile(id).map_err(|e| e.to_string())
+}
+
+// 자동 분류 규칙 목록
+#[tauri::command]
+fn get_category_rules() -> Result<Vec<db::CategoryRule>, String> {
+    db::get_category_rules().map_err(|e| e.to_string())
+}
+
+// 자동 분류 규칙 추가 (pattern이 적요에 포함되면 category로 분류)
+#[tauri::command]
+fn save_category_rule(pattern: String, category: String) -> Result<i64, String> {
+    let pattern = bank_import::category_pattern(&pattern);
+    let category = category.trim();
+    if pattern.is_empty() || category.is_empty() {
+        return Err("패턴과 분류를 입력해주세요".to_string());
+    }
+    db::save_category_rule(&pattern, category).map_err(|e| e.to_string())
+}
+
+// 자동 분류 규칙 삭제
+#[tauri::command]
+fn delete_category_rule(id: i64) -> Result<(), String> {
+    db::delete_category_rule(id).map_err(|e| e.to_string())
+}
+
+// 기간별 수입/지출 통계 (period: day/week/month/none, group_by: category/type)
+#[tauri::command]
+fn get_transaction_stats(start_date: String, end_date: String, period: Option<String>, group_by: Option<String>) -> Result<Vec<db::TransactionAggregate>, String> {
+    validate_usage_range(&start_date, &end_date)?;
+    let period = period.unwrap_or_else(|| "month".to_string());
+    if !["day", "week", "month", "none"].contains(&period.as_str()) {
+        return Err("기간 단위는 day, week, month, none 중 하나여야 합니다".to_string());
+    }
+    let group_by = group_by.unwrap_or_default();
+    if !["", "category", "type"].contains(&group_by.as_str()) {
+        return Err("그룹 기준은 category 또는 type만 가능합니다".to_string());
+    }
+    db::get_transaction_aggregates(&start_date, &end_date, &period, &group_by).map_err(|e| e.to_string())
+}
+
+// 기간별 누적 잔액
+#[tauri::command]
+fn get_transaction_balance(start_date: String, end_date: String, period: Option<String>) -> Result<Vec<db::BalancePoint>, String> {
+    validate_usage_range(&start_date, &end_date)?;
+    let period = period.unwrap_or_else(|| "day".to_string());
+    if !["day", "week", "month"].contains(&period.as_str()) {
+        return Err("기간 단위는 day, week, month 중 하나여야 합니다".to_string());
+    }
+    db::get_transaction_balance(&start_date, &end_date, &period).map_err(|e| e.to_string())
+}
+
+// 카테고리 목록
+#[tauri::command]
+fn get_transaction_categories() -> Result<Vec<db::TransactionCategory>, String> {
+    db::get_transaction_categories().map_err(|e| e.to_string())
+}
+
+// 카테고리 등록/수정
+#[tauri::command]
+fn save_transaction_category(name: String, tx_type: Option<String>, color: Option<String>) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name == "*" {
+        return Err("카테고리 이름을 입력해주세요".to_string());
+    }
+    let tx_type = tx_type.unwrap_or_else(|| "expense".to_string());
+    if tx_type != "income" && tx_type != "expense" {
+        return Err("유형은 income 또는 expense만 가능합니다".to_string());
+    }
+    db::save_transaction_category(name, &tx_type, color.as_deref()).map_err(|e| e.to_string())
+}
+
+// 카테고리 이름 변경 (기존 거래와 예산도 함께, 같은 이름이 있으면 합침)
+#[tauri::command]
+fn rename_transaction_category(old_name: String, new_name: String) -> Result<usize, String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name == "*" {
+        return Err("카테고리 이름을 입력해주세요".to_string());
+    }
+    db::rename_transaction_category(&old_name, new_name).map_err(|e| e.to_string())
+}
+
+// 카테고리 등록 해제
+#[tauri::command]
+fn delete_transaction_category(name: String) -> Result<(), String> {
+    db::delete_transaction_category(&name).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionBudgetStatus {
+    pub budget: db::TransactionBudget,
+    pub period_start: String,   // monthly: YYYY-MM, weekly: 그 주 월요일
+    pub spent: i64,
+    pub remaining: i64,
+    pub ratio: f64,
+    pub over_budget: bool,
+}
+
+// 가계부 예산 목록
+#[tauri::command]
+fn get_transaction_budgets() -> Result<Vec<db::TransactionBudget>, String> {
+    db::get_all_transaction_budgets().map_err(|e| e.to_string())
+}
+
+// 가계부 예산 저장 (category 미지정 시 전체 지출, 같은 카테고리/기간이면 덮어씀)
+#[tauri::command]
+fn save_transaction_budget(category: Option<String>, period: String, limit_amount: i64) -> Result<i64, String> {
+    if period != "monthly" && period != "weekly" {
+        return Err("기간은 monthly 또는 weekly만 가능합니다".to_string());
+    }
+    if limit_amount < 0 {
+        return Err("예산 한도는 0 이상이어야 합니다".to_string());
+    }
+    let category = category.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).unwrap_or_else(|| "*".to_string());
+    db::save_transaction_budget(&category, &period, limit_amount).map_err(|e| e.to_string())
+}
+
+// 가계부 예산 삭제
+#[tauri::command]
+fn delete_transaction_budget(id: i64) -> Result<(), String> {
+    db::delete_transaction_budget(id).map_err(|e| e.to_string())
+}
+
+// 예산별 지출 현황 (date가 속한 달/주 기준, 없으면 오늘)
+#[tauri::command]
+fn get_transaction_budget_status(date: Option<String>) -> Result<Vec<TransactionBudgetStatus>, String> {
+    let date = date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| format!("잘못된 날짜: {}", date))?;
+    let budgets = db::get_all_transaction_budgets().map_err(|e| e.to_string())?;
+    budgets
+        .into_iter()
+        .map(|budget| {
+            let (spent, period_start) = db::get_transaction_spent(&budget.category, &budget.period, &date)
+                .map_err(|e| e.to_string())?;
+            let ratio = if budget.limit_amount > 0 { spent as f64 / budget.limit_amount as f64 } else { 0.0 };
+            Ok(TransactionBudgetStatus {
+                period_start,
+                spent,
+                remaining: budget.limit_amount - spent,
+                ratio,
+                over_budget: spent > budget.limit_amount,
+                budget,
+            })
+        })
+        .collect()
+}
+
+// ===== 첨부파일 관련 명령어 =====
+
+// 복사 모드 첨부파일 저장 폴더 (설정이 없으면 앱 데이터 디렉토리/attachments)
+fn attachment_storage_dir(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let storage_path = db::get_setting("attachment_storage_path").unwrap_or_default();
+    if storage_path.is_empty() {
+        Ok(app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join("attachments"))
+    } else {
+        Ok(std::path::PathBuf::from(storage_path))
+    }
+}
+
+// 첨부파일 추가
+#[tauri::command]
+async fn add_attachment(
+    app_handle: tauri::AppHandle,
+    memo_id: i64,
+    file_path: String,
+) -> Result<Attachment, String> {
+    use std::fs;
+    use std::path::Path;
+
+    let original_path = Path::new(&file_path);
+    if !original_path.exists() {
+        return Err("파일을 찾을 수 없습니다".to_string());
+    }
+
+    let file_name = original_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let file_size = fs::metadata(&file_path)
+        .map(|m| m.len() as i64)
+        .unwrap_or(0);
+
+    let sha256 = attachment_store::hash_file(original_path)?;
+
+    // 설정 확인: 복사 모드 여부
+    let copy_mode = db::get_setting("attachment_copy_mode")
+        .unwrap_or_default();
+    let is_copy = copy_mode == "copy";
+
+    let stored_path = if is_copy {
+        // 내용 해시 경로에 저장 (같은 파일은 한 번만 복사)
+        let storage_dir = attachment_storage_dir(&app_handle)?;
+        attachment_store::store_file(&storage_dir, original_path, &sha256, &file_name)?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        // 링크 모드: 원본 경로 그대로 사용
+        file_path.clone()
+    };
+
+    let attachment = Attachment {
+        id: 0,
+        memo_id,
+        file_name,
+        file_path: stored_path.clone(),
+        original_path: file_path,
+        is_copy,
+        file_size,
+        created_at: String::new(),
+        sha256: Some(sha256),
+    };
+
+    let id = db::save_attachment(&attachment).map_err(|e| e.to_string())?;
+    let attachment = Attachment {
+        id,
+        ..attachment
+    };
+
+    // 본문 추출과 미리보기 생성은 PDF 등에서 오래 걸릴 수 있어 백그라운드에서 처리
+    let has_text = attachment_text::is_supported(&attachment.file_path);
+    let has_preview = thumbnail::is_supported(&attachment.file_path);
+    if has_text || has_preview {
+        let target = attachment.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if has_text && index_attachment_text(&target).is_ok() {
+                app_handle.emit("attachment-text-updated", vec![target.id]).ok();
+            }
+            if has_preview {
+                if let Ok(preview) = build_attachment_preview(&app_handle, &target) {
+                    app_handle.emit("attachment-preview-updated", &preview).ok();
+                }
+            }
+        });
+    }
+
+    Ok(attachment)
+}
+
+// 메모별 첨부파일 조회
+#[tauri::command]
+fn get_attachments(memo_id: i64) -> Result<Vec<Attachment>, String> {
+    db::get_attachments_by_memo(memo_id).map_err(|e| e.to_string())
+}
+
+// 첨부파일 행(본문/미리보기 포함) 삭제, 복사된 파일은 같은 내용을 쓰는 다른 첨부파일이 없을 때만 삭제
+fn delete_attachment_with_file(id: i64) -> Result<(), String> {
+    let attachment = db::delete_attachment(id).map_err(|e| e.to_string())?;
+    if attachment.is_copy && db::count_attachments_by_path(&attachment.file_path).map_err(|e| e.to_string())? == 0 {
+        std::fs::remove_file(&attachment.file_path).ok(); // 실패해도 무시
+    }
+    Ok(())
+}
+
+// 첨부파일 삭제
+#[tauri::command]
+fn remove_attachment(id: i64) -> Result<(), String> {
+    delete_attachment_with_file(id)
+}
+
+// ===== 첨부파일 미리보기 =====
+
+// 썸네일 폴더 (앱 데이터 디렉토리/thumbnails)
+fn thumbnails_dir(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join("thumbnails"))
+}
+
+// 썸네일과 이미지 정보 생성 후 저장 (썸네일은 내용 해시 이름이라 같은 파일이면 다시 만들지 않음)
+fn build_attachment_preview(app_handle: &tauri::AppHandle, attachment: &Attachment) -> Result<db::AttachmentPreview, String> {
+    let path = std::path::Path::new(&attachment.file_path);
+    let sha256 = match &attachment.sha256 {
+        Some(sha256) => sha256.clone(),
+        None => {
+            // 해시가 없던 첨부파일은 계산한 해시를 기록 (다음 조회부터 미리보기를 다시 만들지 않도록)
+            let sha256 = attachment_store::hash_file(path)?;
+            let file_size = std::fs::metadata(path).map(|m| m.len() as i64).unwrap_or(attachment.file_size);
+            db::update_attachment_file(&Attachment { file_size, sha256: Some(sha256.clone()), ..attachment.clone() })
+                .map_err(|e| e.to_string())?;
+            sha256
+        }
+    };
+    let target = thumbnails_dir(app_handle)?.join(format!("{}.jpg", sha256));
+
+    let (created, error) = if target.exists() {
+        (true, None)
+    } else {
+        match thumbnail::generate(&attachment.file_path, &target) {
+            Ok(created) => (created, None),
+            Err(e) => (false, Some(e)),
+        }
+    };
+    let info = thumbnail::read_image_info(&attachment.file_path);
+    let thumbnail_path = created.then(|| target.to_string_lossy().to_string());
+
+    let preview = db::AttachmentPreview {
+        attachment_id: attachment.id,
+        sha256: Some(sha256),
+        thumbnail_url: thumbnail_path.as_deref().map(thumbnail::asset_url),
+        thumbnail_path,
+        width: info.width,
+        height: info.height,
+        taken_at: info.taken_at,
+        camera: info.camera,
+        error,
+        created_at: String::new(),
+    };
+    db::save_attachment_preview(&preview).map_err(|e| e.to_string())?;
+    Ok(preview)
+}
+
+// 미리보기가 없거나 파일 내용이 바뀐 첨부파일은 새로 만듦
+fn ensure_attachment_previews(app_handle: &tauri::AppHandle, attachments: Vec<Attachment>) -> Result<Vec<db::AttachmentPreview>, String> {
+    let mut previews = Vec::new();
+    for attachment in attachments.into_iter().filter(|a| thumbnail::is_supported(&a.file_path)) {
+        let existing = db::get_attachment_preview(attachment.id).map_err(|e| e.to_string())?;
+        let fresh = existing.as_ref().is_some_and(|p| {
+            p.sha256.is_some()
+                && p.sha256 == attachment.sha256
+                && p.thumbnail_path.as_deref().is_none_or(|t| std::path::Path::new(t).exists())
+        });
+        match existing {
+            Some(preview) if fresh => previews.push(preview),
+            _ if !std::path::Path::new(&attachment.file_path).exists() => {}
+            _ => previews.push(build_attachment_preview(app_handle, &attachment)?),
+        }
+    }
+    Ok(previews)
+}
+
+// 메모의 첨부파일 미리보기 목록 (이미지/PDF만)
+#[tauri::command]
+async fn get_attachment_previews(app_handle: tauri::AppHandle, memo_id: i64) -> Result<Vec<db::AttachmentPreview>, String> {
+    let attachments = db::get_attachments_by_memo(memo_id).map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn_blocking(move || ensure_attachment_previews(&app_handle, attachments))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 첨부파일 하나의 미리보기 (미리보기를 만들 수 없는 형식이면 None)
+#[tauri::command]
+async fn get_attachment_preview(app_handle: tauri::AppHandle, id: i64) -> Result<Option<db::AttachmentPreview>, String> {
+    let attachment = db::get_attachment(id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    let previews = tauri::async_runtime::spawn_blocking(move || ensure_attachment_previews(&app_handle, vec![attachment]))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(previews.into_iter().next())
+}
+
+// ===== 첨부파일 무결성 =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentIssue {
+    pub attachment: Attachment,
+    pub status: String,                  // missing (파일 없음), modified (내용이 바뀜), unreadable (읽을 수 없음)
+    pub current_sha256: Option<String>,
+    pub current_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentScanResult {
+    pub checked: usize,
+    pub hashed: usize,                   // 해시가 없던 기존 첨부파일에 새로 기록한 수
+    pub issues: Vec<AttachmentIssue>,
+}
+
+// 모든 첨부파일의 파일 존재 여부와 해시 확인 (해시가 없던 첨부파일은 현재 내용을 기준으로 기록)
+fn scan_attachment_files() -> Result<AttachmentScanResult, String> {
+    let attachments = db::get_all_attachments().map_err(|e| e.to_string())?;
+    let mut result = AttachmentScanResult { checked: attachments.len(), hashed: 0, issues: Vec::new() };
+
+    for attachment in attachments {
+        let path = std::path::Path::new(&attachment.file_path);
+        let Ok(meta) = std::fs::metadata(path) else {
+            result.issues.push(AttachmentIssue { attachment, status: "missing".to_string(), current_sha256: None, current_size: None });
+            continue;
+        };
+        let Ok(current) = attachment_store::hash_file(path) else {
+            // 권한 없음, 잠긴 파일, 연결이 끊긴 네트워크 드라이브 등 (검사는 계속)
+            result.issues.push(AttachmentIssue {
+                attachment,
+                status: "unreadable".to_string(),
+                current_sha256: None,
+                current_size: Some(meta.len() as i64),
+            });
+            continue;
+        };
+        match attachment.sha256.as_deref() {
+            None => {
+                db::update_attachment_file(&Attachment { file_size: meta.len() as i64, sha256: Some(current), ..attachment })
+                    .map_err(|e| e.to_string())?;
+                result.hashed += 1;
+            }
+            Some(stored) if stored != current => {
+                result.issues.push(AttachmentIssue {
+                    attachment,
+                    status: "modified".to_string(),
+                    current_sha256: Some(current),
+                    current_size: Some(meta.len() as i64),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(result)
+}
+
+// 첨부파일 검사 (없어지거나 바뀐 파일 목록)
+#[tauri::command]
+async fn scan_attachments() -> Result<AttachmentScanResult, String> {
+    tauri::async_runtime::spawn_blocking(scan_attachment_files)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 없어진 첨부파일과 같은 내용의 파일 찾기
+// search_paths가 없으면 원래 폴더와 그 상위 폴더, 홈 폴더에서 찾음
+#[tauri::command]
+async fn find_attachment_by_hash(id: i64, search_paths: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let attachment = db::get_attachment(id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    let sha256 = attachment.sha256.clone().ok_or("해시가 기록되지 않은 첨부파일입니다")?;
+    let roots: Vec<std::path::PathBuf> = match search_paths {
+        Some(paths) if !paths.is_empty() => paths.into_iter().map(std::path::PathBuf::from).collect(),
+        _ => {
+            let original = std::path::Path::new(&attachment.original_path);
+            let mut roots: Vec<std::path::PathBuf> = original.ancestors().skip(1).take(2).map(|p| p.to_path_buf()).collect();
+            roots.extend(dirs::home_dir());
+            roots
+        }
+    };
+
+    let size = attachment.file_size.max(0) as u64;
+    let found = tauri::async_runtime::spawn_blocking(move || attachment_store::find_by_hash(&roots, &sha256, size))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(found.into_iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
+
+// 옮겨진 첨부파일 다시 연결 (내용 해시가 같아야 함, 복사 모드는 저장소에 다시 보관)
+#[tauri::command]
+fn relocate_attachment(app_handle: tauri::AppHandle, id: i64, file_path: String) -> Result<Attachment, String> {
+    let attachment = db::get_attachment(id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    let source = std::path::Path::new(&file_path);
+    let sha256 = attachment_store::hash_file(source)?;
+    if attachment.sha256.as_deref().is_some_and(|stored| stored != sha256) {
+        return Err("선택한 파일의 내용이 첨부파일과 다릅니다".to_string());
+    }
+
+    let relocated = if attachment.is_copy {
+        let stored = attachment_store::store_file(&attachment_storage_dir(&app_handle)?, source, &sha256, &attachment.file_name)?;
+        Attachment { file_path: stored.to_string_lossy().to_string(), sha256: Some(sha256), ..attachment }
+    } else {
+        Attachment { file_path: file_path.clone(), original_path: file_path, sha256: Some(sha256), ..attachment }
+    };
+    db::update_attachment_file(&relocated).map_err(|e| e.to_string())?;
+    Ok(relocated)
+}
+
+// 바뀐 첨부파일 내용을 새 기준으로 인정 (현재 파일의 해시/크기 기록)
+// 복사 모드는 새 해시 경로로 다시 보관하고, 다른 첨부파일이 쓰지 않으면 이전 파일 삭제
+#[tauri::command]
+fn accept_attachment_change(app_handle: tauri::AppHandle, id: i64) -> Result<Attachment, String> {
+    let attachment = db::get_attachment(id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    let old_path = attachment.file_path.clone();
+    let path = std::path::Path::new(&old_path);
+    let size = std::fs::metadata(path).map_err(|_| "파일을 찾을 수 없습니다".to_string())?.len() as i64;
+    let sha256 = attachment_store::hash_file(path)?;
+
+    let file_path = if attachment.is_copy {
+        attachment_store::store_file(&attachment_storage_dir(&app_handle)?, path, &sha256, &attachment.file_name)?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        old_path.clone()
+    };
+    let updated = Attachment { file_path, file_size: size, sha256: Some(sha256), ..attachment };
+    db::update_attachment_file(&updated).map_err(|e| e.to_string())?;
+
+    if updated.is_copy && updated.file_path != old_path && db::count_attachments_by_path(&old_path).map_err(|e| e.to_string())? == 0 {
+        std::fs::remove_file(&old_path).ok();
+    }
+    Ok(updated)
+}
+
+// ===== 첨부파일 저장소 정리/이동 =====
+
+// 진행 상황 이벤트 (attachment-storage-progress)
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentStorageProgress {
+    pub operation: String,   // gc, move
+    pub phase: String,       // gc: delete / move: copy, cleanup
+    pub done: usize,
+    pub total: usize,
+}
+
+fn emit_storage_progress(app: &tauri::AppHandle, operation: &str, phase: &str, done: usize, total: usize) {
+    app.emit(
+        "attachment-storage-progress",
+        AttachmentStorageProgress { operation: operation.to_string(), phase: phase.to_string(), done, total },
+    )
+    .ok();
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentGcReport {
+    pub dry_run: bool,
+    pub orphan_rows: usize,          // 메모가 없는 첨부파일 행
+    pub stale_references: usize,     // 없는 첨부파일을 가리키는 본문/인덱스/영수증 연결
+    pub orphan_files: Vec<String>,   // 어느 첨부파일도 쓰지 않는 저장 파일
+    pub orphan_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentMoveResult {
+    pub storage_path: String,
+    pub moved_files: usize,
+    pub moved_bytes: u64,
+    pub missing: Vec<i64>,           // 파일이 없어 옮기지 못한 첨부파일 (경로 유지)
+}
+
+// 저장소 정리: 메모가 없는 첨부파일 행, 끊어진 연결, 쓰이지 않는 저장 파일과 썸네일 삭제
+// 저장 폴더를 직접 지정한 경우 그 폴더의 파일은 해시 이름 파일만 대상 (사용자 파일 보호)
+fn collect_garbage(app: &tauri::AppHandle, dry_run: bool) -> Result<AttachmentGcReport, String> {
+    let orphan_rows = db::get_orphan_attachments().map_err(|e| e.to_string())?;
+    if !dry_run {
+        for attachment in &orphan_rows {
+            db::delete_attachment(attachment.id).map_err(|e| e.to_string())?;
+        }
+    }
+    let stale_references = db::cleanup_attachment_references(dry_run).map_err(|e| e.to_string())?;
+
+    let referenced: std::collections::HashSet<String> = db::get_all_attachments()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|a| !orphan_rows.iter().any(|o| o.id == a.id))
+        .map(|a| a.file_path)
+        .collect();
+
+    let default_dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("attachments");
+    let mut dirs = vec![default_dir.clone()];
+    let current_dir = attachment_storage_dir(app)?;
+    if current_dir != default_dir {
+        dirs.push(current_dir);
+    }
+
+    let mut orphan_files = Vec::new();
+    let mut orphan_bytes = 0;
+    for dir in &dirs {
+        for file in attachment_store::list_files(dir) {
+            let path = file.to_string_lossy().to_string();
+            if referenced.contains(&path)
+                || attachment_store::is_fresh_temp_file(&file)
+                || (*dir != default_dir && !attachment_store::is_content_file(dir, &file))
+            {
+                continue;
+            }
+            orphan_bytes += std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            orphan_files.push(path);
+        }
+    }
+
+    // 어느 미리보기도 쓰지 않는 썸네일
+    let thumbnails: std::collections::HashSet<String> = db::get_thumbnail_paths().map_err(|e| e.to_string())?.into_iter().collect();
+    for file in attachment_store::list_files(&thumbnails_dir(app)?) {
+        let path = file.to_string_lossy().to_string();
+        if !thumbnails.contains(&path) {
+            orphan_bytes += std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            orphan_files.push(path);
+        }
+    }
+
+    if !dry_run {
+        let total = orphan_files.len();
+        for (i, path) in orphan_files.iter().enumerate() {
+            std::fs::remove_file(path).ok(); // 실패해도 무시
+            emit_storage_progress(app, "gc", "delete", i + 1, total);
+        }
+        for dir in &dirs {
+            attachment_store::remove_empty_shards(dir);
+        }
+    }
+
+    Ok(AttachmentGcReport { dry_run, orphan_rows: orphan_rows.len(), stale_references, orphan_files, orphan_bytes })
+}
+
+// 첨부파일 저장소 정리 (dry_run이면 삭제하지 않고 대상만 보고)
+#[tauri::command]
+async fn collect_attachment_garbage(app_handle: tauri::AppHandle, dry_run: bool) -> Result<AttachmentGcReport, String> {
+    tauri::async_runtime::spawn_blocking(move || collect_garbage(&app_handle, dry_run))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 저장 폴더 이동: 새 폴더에 모두 복사 → 경로와 설정을 한 트랜잭션으로 변경 → 이전 파일 삭제
+// 복사나 DB 변경이 실패하면 새로 복사한 파일을 지우고 이전 상태 유지
+fn move_storage(app: &tauri::AppHandle, new_dir: std::path::PathBuf) -> Result<AttachmentMoveResult, String> {
+    use std::collections::HashMap;
+
+    let old_dir = attachment_storage_dir(app)?;
+    if new_dir == old_dir {
+        return Err("현재 저장 폴더와 같습니다".to_string());
+    }
+    if new_dir.starts_with(&old_dir) || old_dir.starts_with(&new_dir) {
+        return Err("현재 저장 폴더의 상위/하위 폴더로는 옮길 수 없습니다".to_string());
+    }
+    std::fs::create_dir_all(&new_dir).map_err(|e| format!("폴더를 만들 수 없습니다: {}", e))?;
+
+    // 같은 파일을 공유하는 첨부파일은 한 번만 옮김
+    let mut groups: HashMap<String, Vec<Attachment>> = HashMap::new();
+    for attachment in db::get_all_attachments().map_err(|e| e.to_string())?.into_iter().filter(|a| a.is_copy) {
+        groups.entry(attachment.file_path.clone()).or_default().push(attachment);
+    }
+
+    let mut result = AttachmentMoveResult {
+        storage_path: new_dir.to_string_lossy().to_string(),
+        moved_files: 0,
+        moved_bytes: 0,
+        missing: Vec::new(),
+    };
+    let mut changes: Vec<(i64, String, String)> = Vec::new();
+    let mut created: Vec<std::path::PathBuf> = Vec::new();
+    let mut sources: Vec<std::path::PathBuf> = Vec::new();
+    let rollback = |created: &[std::path::PathBuf]| {
+        for path in created {
+            std::fs::remove_file(path).ok();
+        }
+        attachment_store::remove_empty_shards(&new_dir);
+    };
+
+    let total = groups.len();
+    for (i, (path, attachments)) in groups.iter().enumerate() {
+        emit_storage_progress(app, "move", "copy", i + 1, total);
+        let source = std::path::PathBuf::from(path);
+        if !source.is_file() {
+            result.missing.extend(attachments.iter().map(|a| a.id));
+            continue;
+        }
+        let copied = match attachments.iter().find_map(|a| a.sha256.clone()) {
+            Some(sha256) => Ok(sha256),
+            None => attachment_store::hash_file(&source),
+        }
+        .and_then(|sha256| {
+            let target = attachment_store::content_path(&new_dir, &sha256, &attachments[0].file_name);
+            if !target.exists() {
+                result.moved_bytes += attachment_store::copy_verified(&source, &target)?;
+                created.push(target.clone());
+            }
+            Ok((sha256, target))
+        });
+        let (sha256, target) = match copied {
+            Ok(copied) => copied,
+            Err(e) => {
+                rollback(&created);
+                return Err(e);
+            }
+        };
+        result.moved_files += 1;
+        let target = target.to_string_lossy().to_string();
+        changes.extend(attachments.iter().map(|a| (a.id, target.clone(), sha256.clone())));
+        sources.push(source);
+    }
+
+    if let Err(e) = db::move_attachment_storage(&changes, &result.storage_path) {
+        rollback(&created);
+        return Err(e.to_string());
+    }
+
+    let total = sources.len();
+    for (i, source) in sources.iter().enumerate() {
+        std::fs::remove_file(source).ok(); // 실패해도 무시 (정리 명령으로 다시 지울 수 있음)
+        emit_storage_progress(app, "move", "cleanup", i + 1, total);
+    }
+    attachment_store::remove_empty_shards(&old_dir);
+
+    Ok(result)
+}
+
+// 첨부파일 저장 폴더 이동
+#[tauri::command]
+async fn move_attachment_storage(app_handle: tauri::AppHandle, new_path: String) -> Result<AttachmentMoveResult, String> {
+    if new_path.trim().is_empty() {
+        return Err("새 저장 폴더를 지정해주세요".to_string());
+    }
+    let new_dir = std::path::PathBuf::from(new_path.trim());
+    tauri::async_runtime::spawn_blocking(move || move_storage(&app_handle, new_dir))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// 첨부파일 열기
+#[tauri::command]
+fn open_attachment(file_path: String) -> Result<(), String> {
+    open::that(&file_path).map_err(|e| e.to_string())
+}
+
+// 첨부파일 검색 (파일명 일치 먼저, 그다음 본문 일치)
+#[tauri::command]
+fn search_attachments(query: String) -> Result<Vec<Attachment>, String> {
+    let mut attachments = db::search_attachments(&query).map_err(|e| e.to_string())?;
+    for (attachment, _) in db::search_attachment_texts(&query, 50).map_err(|e| e.to_string())? {
+        if !attachments.iter().any(|a| a.id == attachment.id) {
+            attachments.push(attachment);
+        }
+    }
+    Ok(attachments)
+}
+
+// 첨부파일 본문 추출 후 저장 (추출 실패도 사유와 함께 저장해 같은 파일을 반복해서 시도하지 않음)
+fn index_attachment_text(attachment: &Attachment) -> Result<(), String> {
+    let stamp = attachment_text::file_stamp(&attachment.file_path);
+    let (content, error) = match attachment_text::extract_text(&attachment.file_path) {
+        Ok(Some(content)) => (content, None),
+        Ok(None) => return Ok(()),
+        Err(e) => (String::new(), Some(e)),
+    };
+    db::save_attachment_text(&db::AttachmentText {
+        attachment_id: attachment.id,
+        content,
+        file_modified: stamp.map(|(modified, _)| modified),
+        file_size: stamp.map(|(_, size)| size),
+        error,
+        extracted_at: String::new(),
+    })
+    .map_err(|e| e.to_string())
+}
+
+// 본문이 없는 첨부파일은 추출하고, 링크 모드 첨부파일은 원본이 바뀌었으면 다시 추출
+// 반환: 새로 추출한 첨부파일 ID
+fn refresh_attachment_text_index() -> Result<Vec<i64>, String> {
+    let attachments = db::get_all_attachments().map_err(|e| e.to_string())?;
+    let stamps = db::get_attachment_text_stamps().map_err(|e| e.to_string())?;
+
+    let mut updated = Vec::new();
+    for attachment in attachments.iter().filter(|a| attachment_text::is_supported(&a.file_path)) {
+        // 파일이 없어진 경우는 기존 본문을 그대로 둠
+        let Some(stamp) = attachment_text::file_stamp(&attachment.file_path) else {
+            continue;
+        };
+        let stale = match stamps.get(&attachment.id) {
+            None => true,
+            Some(stored) => !attachment.is_copy && *stored != stamp,
+        };
+        if stale && index_attachment_text(attachment).is_ok() {
+            updated.push(attachment.id);
+        }
+    }
+    Ok(updated)
+}
+
+// 첨부파일 본문 다시 확인 (수동 실행), 새로 추출한 개수 반환
+#[tauri::command]
+async fn refresh_attachment_texts(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let updated = tauri::async_runtime::spawn_blocking(refresh_attachment_text_index)
+        .await
+        .map_err(|e| e.to_string())??;
+    if !updated.is_empty() {
+        app_handle.emit("attachment-text-updated", &updated).ok();
+    }
+    Ok(updated.len())
+}
+
+// 첨부파일 추출 본문 조회
+#[tauri::command]
+fn get_attachment_text(attachment_id: i64) -> Result<Option<db::AttachmentText>, String> {
+    db::get_attachment_text(attachment_id).map_err(|e| e.to_string())
+}
+
+// 첨부파일 본문 감시 (attachment_text_refresh_minutes 간격, 0이면 끔)
+fn start_attachment_text_watcher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let minutes = db::get_setting("attachment_text_refresh_minutes")
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .unwrap_or(10);
+            if minutes > 0 {
+                if let Ok(Ok(updated)) = tauri::async_runtime::spawn_blocking(refresh_attachment_text_index).await {
+                    if !updated.is_empty() {
+                        app.emit("attachment-text-updated", &updated).ok();
+                    }
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(minutes.max(1) * 60)).await;
+        }
+    });
 }
 
-#[tauri::command]
-fn update_transaction(
-    id: i64,
-    tx_type: String,
-    amount: i64,
-    description: String,
-    category: Option<String>,
-    tx_date: Option<String>,
-) -> Result<(), String> {
-    db::update_transaction(
-        id,
-        &tx_type,
-        amount,
-        &description,
-        category.as_deref(),
-        tx_date.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+// ===== 영수증 인식 =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptScan {
+    pub attachment_id: i64,
+    pub memo_id: i64,
+    pub backend: String,                    // vision, command (로컬 OCR + AI), rules (로컬 OCR + 규칙)
+    pub receipt: ai::ReceiptInfo,           // 사용자가 검토/수정한 뒤 save_receipt_transaction으로 저장
+    pub ocr_text: Option<String>,           // 로컬 OCR로 읽은 원문
+    pub existing_transaction_ids: Vec<i64>, // 이 영수증으로 이미 만든 거래
 }
 
-// ===== 첨부파일 관련 명령어 =====
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReceiptSaveOptions {
+    #[serde(default)]
+    pub account_id: Option<i64>,
+    #[serde(default)]
+    pub backend: Option<String>,            // scan_receipt 결과의 backend (기록용)
+}
 
-// 첨부파일 추가
+// 첨부한 영수증 이미지 인식 (저장하지 않고 검토용 결과만 반환)
 #[tauri::command]
-async fn add_attachment(
-    app_handle: tauri::AppHandle,
-    memo_id: i64,
-    file_path: String,
-) -> Result<Attachment, String> {
-    use std::fs;
-    use std::path::Path;
-
-    let original_path = Path::new(&file_path);
-    if !original_path.exists() {
+async fn scan_receipt(attachment_id: i64) -> Result<ReceiptScan, String> {
+    let attachment = db::get_attachment(attachment_id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    let mime_type = receipt::mime_type(&attachment.file_path)
+        .ok_or("영수증으로 인식할 수 없는 파일 형식입니다 (JPG, PNG, WEBP, HEIC, PDF)")?;
+    if !std::path::Path::new(&attachment.file_path).exists() {
         return Err("파일을 찾을 수 없습니다".to_string());
     }
 
-    let file_name = original_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    let file_size = fs::metadata(&file_path)
-        .map(|m| m.len() as i64)
-        .unwrap_or(0);
-
-    // 설정 확인: 복사 모드 여부
-    let copy_mode = db::get_setting("attachment_copy_mode")
-        .unwrap_or_default();
-    let is_copy = copy_mode == "copy";
+    let api_key = db::get_setting("gemini_api_key").unwrap_or_default();
+    let model = db::get_setting("gemini_model").unwrap_or_default();
+    let use_command = db::get_setting("receipt_ocr_backend").unwrap_or_default() == "command";
+    let categories: Vec<String> = db::get_transaction_categories()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
 
-    let stored_path = if is_copy {
-        // 저장 경로 가져오기
-        let storage_path = db::get_setting("attachment_storage_path")
-            .unwrap_or_default();
-
-        let storage_dir = if storage_path.is_empty() {
-            // 기본 경로: 앱 데이터 디렉토리/attachments
-            app_handle
-                .path()
-                .app_data_dir()
-                .map_err(|e| e.to_string())?
-                .join("attachments")
+    let (backend, mut info, ocr_text, usage) = if use_command {
+        let command = db::get_setting("receipt_ocr_command").unwrap_or_default();
+        let text = receipt::run_ocr_command(&command, &attachment.file_path).await?;
+        if api_key.is_empty() {
+            ("rules", receipt::parse_receipt_text(&text), Some(text), None)
         } else {
-            Path::new(&storage_path).to_path_buf()
-        };
-
-        // 디렉토리 생성
-        fs::create_dir_all(&storage_dir).map_err(|e| e.to_string())?;
-
-        // 중복 파일명 처리
-        let mut target_path = storage_dir.join(&file_name);
-        let mut counter = 1;
-        while target_path.exists() {
-            let stem = original_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("file");
-            let ext = original_path
-                .extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("");
-            let new_name = if ext.is_empty() {
-                format!("{}_{}", stem, counter)
-            } else {
-                format!("{}_{}.{}", stem, counter, ext)
-            };
-            target_path = storage_dir.join(new_name);
-            counter += 1;
+            let (info, usage) = ai::extract_receipt(&api_key, &model, ai::ReceiptSource::Text(&text), &categories).await?;
+            ("command", info, Some(text), Some(usage))
         }
-
-        // 파일 복사
-        fs::copy(&file_path, &target_path).map_err(|e| e.to_string())?;
-        target_path.to_string_lossy().to_string()
     } else {
-        // 링크 모드: 원본 경로 그대로 사용
-        file_path.clone()
+        if api_key.is_empty() {
+            return Err("API 키를 먼저 설정해주세요".to_string());
+        }
+        if attachment.file_size as u64 > receipt::MAX_INLINE_BYTES {
+            return Err("영수증 파일이 너무 큽니다 (최대 15MB)".to_string());
+        }
+        let data = std::fs::read(&attachment.file_path).map_err(|e| e.to_string())?;
+        let source = ai::ReceiptSource::Image { data: &data, mime_type };
+        let (info, usage) = ai::extract_receipt(&api_key, &model, source, &categories).await?;
+        ("vision", info, None, Some(usage))
     };
 
-    let attachment = Attachment {
-        id: 0,
-        memo_id,
-        file_name,
-        file_path: stored_path.clone(),
-        original_path: file_path,
-        is_copy,
-        file_size,
-        created_at: String::new(),
-    };
+    if let Some(usage) = usage {
+        let model_name = if model.is_empty() { "gemini-3-flash-preview" } else { &model };
+        log_usage("receipt", model_name, usage.input_tokens, usage.output_tokens, usage.cost_usd)
+            .map_err(|e| e.to_string())?;
+    }
 
-    let id = db::save_attachment(&attachment).map_err(|e| e.to_string())?;
+    // 검토 화면에 바로 쓸 수 있게 정리 (날짜 형식, 통화 코드, 학습된 분류)
+    info.total = info.total.abs();
+    info.date = info.date.and_then(|d| bank_import::parse_date(&d, None));
+    info.currency = info.currency.and_then(|c| currency::normalize(&c).ok());
+    if info.category.as_deref().is_none_or(|c| c.trim().is_empty()) {
+        let rules = db::get_category_rules().map_err(|e| e.to_string())?;
+        info.category = info.merchant.as_deref()
+            .and_then(|m| bank_import::suggest_category(&rules, m))
+            .map(str::to_string);
+    }
 
-    Ok(Attachment {
-        id,
-        ..attachment
+    Ok(ReceiptScan {
+        attachment_id,
+        memo_id: attachment.memo_id,
+        backend: backend.to_string(),
+        receipt: info,
+        ocr_text,
+        existing_transaction_ids: db::get_receipt_transaction_ids(attachment_id).map_err(|e| e.to_string())?,
     })
 }
 
-// 메모별 첨부파일 조회
-#[tauri::command]
-fn get_attachments(memo_id: i64) -> Result<Vec<Attachment>, String> {
-    db::get_attachments_by_memo(memo_id).map_err(|e| e.to_string())
-}
-
-// 첨부파일 삭제
+// 검토한 영수증 내용을 지출 거래로 저장 (첨부파일의 메모에 연결, 품목은 영수증 정보로 보관)
 #[tauri::command]
-fn remove_attachment(id: i64) -> Result<(), String> {
-    use std::fs;
-
-    let attachment = db::delete_attachment(id).map_err(|e| e.to_string())?;
-
-    // 복사된 파일인 경우 파일도 삭제
-    if attachment.is_copy {
-        fs::remove_file(&attachment.file_path).ok(); // 실패해도 무시
+fn save_receipt_transaction(
+    attachment_id: i64,
+    receipt: ai::ReceiptInfo,
+    options: Option<ReceiptSaveOptions>,
+) -> Result<Transaction, String> {
+    let options = options.unwrap_or_default();
+    let attachment = db::get_attachment(attachment_id).map_err(|e| e.to_string())?
+        .ok_or("첨부파일을 찾을 수 없습니다")?;
+    if receipt.total <= 0.0 {
+        return Err("결제 금액을 입력해주세요".to_string());
     }
+    if let Some(account_id) = options.account_id {
+        db::get_account(account_id).map_err(|e| e.to_string())?.ok_or("계좌를 찾을 수 없습니다")?;
+    }
+    let currency = match receipt.currency.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        Some(code) => currency::normalize(code)?,
+        None => db::get_base_currency().map_err(|e| e.to_string())?,
+    };
+    let tx_date = match receipt.date.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(date) => Some(bank_import::parse_date(date, None).ok_or(format!("잘못된 날짜: {}", date))?),
+        None => Some(chrono::Local::now().format("%Y-%m-%d").to_string()),
+    };
+    let merchant = receipt.merchant.as_deref().map(str::trim).filter(|m| !m.is_empty()).map(str::to_string);
 
-    Ok(())
-}
+    let mut transaction = Transaction {
+        id: 0,
+        memo_id: Some(attachment.memo_id),
+        tx_type: "expense".to_string(),
+        amount: currency::to_minor(receipt.total, &currency),
+        description: merchant.clone().unwrap_or_else(|| attachment.file_name.clone()),
+        category: receipt.category.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+        tx_date,
+        created_at: String::new(),
+        currency,
+        account_id: options.account_id,
+        to_account_id: None,
+        to_amount: None,
+    };
+    transaction.id = db::save_transaction(&transaction).map_err(|e| e.to_string())?;
+
+    db::save_transaction_receipt(&db::TransactionReceipt {
+        transaction_id: transaction.id,
+        attachment_id: Some(attachment_id),
+        merchant,
+        items: receipt.items
+            .iter()
+            .filter(|item| !item.name.trim().is_empty())
+            .map(|item| db::ReceiptItem {
+                name: item.name.trim().to_string(),
+                quantity: item.quantity,
+                amount: currency::to_minor(item.amount, &transaction.currency),
+            })
+            .collect(),
+        backend: options.backend.unwrap_or_else(|| "manual".to_string()),
+        created_at: String::new(),
+    })
+    .map_err(|e| e.to_string())?;
 
-// 첨부파일 열기
-#[tauri::command]
-fn open_attachment(file_path: String) -> Result<(), String> {
-    open::that(&file_path).map_err(|e| e.to_string())
+    emit_transaction_budget_warnings(&transaction);
+    Ok(transaction)
 }
 
-// 첨부파일 검색
+// 거래의 영수증 정보 (원본 첨부파일, 품목)
 #[tauri::command]
-fn search_attachments(query: String) -> Result<Vec<Attachment>, String> {
-    db::search_attachments(&query).map_err(|e| e.to_string())
+fn get_transaction_receipt(transaction_id: i64) -> Result<Option<db::TransactionReceipt>, String> {
+    db::get_transaction_receipt(transaction_id).map_err(|e| e.to_string())
 }
 
 // ===== 폴더 정리 기능 =====
@@ -999,11 +3204,19 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
         app.emit("research-progress", &progress).ok();
     };
 
+    // 예산 확인 헬퍼 (초과 시 지금까지의 사용량을 기록하고 중단)
+    let check_research_budget = |prompt: &str, input: i64, output: i64, cost: f64| -> Result<(), String> {
+        ai::check_budget(ai::PROVIDER, "research", model, prompt, cost).inspect_err(|_| {
+            let _ = log_usage("research", model, input, output, cost);
+        })
+    };
+
     // === 1단계: AI가 검색 쿼리 계획 수립 ===
     tasks[0].status = "in_progress".to_string();
     tasks[0].description = "AI가 검색 쿼리 계획 수립 중...".to_string();
     emit_progress(&app_handle, 1, &tasks, "plan", "in_progress");
 
+    check_research_budget(&query, total_input_tokens, total_output_tokens, total_cost)?;
     let (search_queries, input, output, cost) = ai::plan_research(&gemini_api_key, &model, &query).await?;
     total_input_tokens += input;
     total_output_tokens += output;
@@ -1098,6 +3311,8 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
     tasks[2].description = format!("AI가 {}개 결과 중 크롤링할 페이지 선택 중...", all_search_results.len());
     emit_progress(&app_handle, 3, &tasks, "select", "in_progress");
 
+    let results_text = serde_json::to_string(&all_search_results).unwrap_or_default();
+    check_research_budget(&results_text, total_input_tokens, total_output_tokens, total_cost)?;
     let (selected_urls, input, output, cost) = ai::select_pages_to_crawl(&gemini_api_key, &model, &query, &all_search_results).await?;
     total_input_tokens += input;
     total_output_tokens += output;
@@ -1142,6 +3357,8 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
         tasks[4].description = format!("분석 중 ({}/{}): {} [인사이트 {}개]", idx + 1, crawled_contents.len(), domain, all_insights.len());
         emit_progress(&app_handle, 5, &tasks, "analyze", "in_progress");
 
+        let page_text: String = content.chars().take(8000).collect();
+        check_research_budget(&page_text, total_input_tokens, total_output_tokens, total_cost)?;
         if let Ok((insights, input, output, cost)) = ai::extract_insights(&gemini_api_key, &model, &query, url, content).await {
             total_input_tokens += input;
             total_output_tokens += output;
@@ -1176,6 +3393,8 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
             tasks[5].description = format!("요약 중 ({}/{}): \"{}...\"", summarized_count + 1, crawled_contents.len(), short_title);
             emit_progress(&app_handle, 6, &tasks, "summarize", "in_progress");
 
+            let page_text: String = content.chars().take(6000).collect();
+            check_research_budget(&page_text, total_input_tokens, total_output_tokens, total_cost)?;
             if let Ok((summary_text, input, output, cost)) = ai::summarize_source(
                 &gemini_api_key, &model, &query, &source_info.title, url, content
             ).await {
@@ -1203,6 +3422,7 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
     tasks[6].description = format!("{}개 인사이트를 바탕으로 최종 리포트 작성 중...", all_insights.len());
     emit_progress(&app_handle, 7, &tasks, "compile", "in_progress");
 
+    check_research_budget(&all_insights.join("\n"), total_input_tokens, total_output_tokens, total_cost)?;
     let (summary, full_report, key_points, input, output, cost) =
         ai::compile_final_report(&gemini_api_key, &model, &query, &all_insights, &used_sources).await?;
     total_input_tokens += input;
@@ -1214,7 +3434,7 @@ async fn run_research(app_handle: tauri::AppHandle, query: String) -> Result<Res
     emit_progress(&app_handle, 7, &tasks, "compile", "completed");
 
     // 사용량 기록
-    db::log_api_usage(
+    log_usage(
         "research",
         model,
         total_input_tokens,
@@ -1302,6 +3522,7 @@ pub struct DatasetAnalysis {
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cost_usd: f64,
+    pub cached: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1321,115 +3542,111 @@ pub struct ChartData {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatasetQAResult {
     pub answer: String,
-    pub relevant_rows: Vec<Vec<String>>,
+    pub sql: String,                     // 전체 행에 대해 실행한 쿼리
+    pub sql_explanation: String,
+    pub columns: Vec<String>,            // 결과 표 컬럼
+    pub relevant_rows: Vec<Vec<String>>, // 결과 표
+    pub truncated: bool,                 // 결과가 최대 행 수에서 잘렸는지
     pub input_tokens: i64,
     pub output_tokens: i64,
     pub cost_usd: f64,
 }
 
-// 엑셀 파일 임포트 (Base64 데이터로 받음)
-#[tauri::command]
-async fn import_excel(file_data: String, file_name: String) -> Result<ImportExcelResult, String> {
-    // Base64 디코딩
-    let bytes = base64::Engine::decode(
-        &base64::engine::general_purpose::STANDARD,
-        &file_data
-    ).map_err(|e| format!("Base64 디코딩 실패: {}", e))?;
-
-    // 엑셀 파일 파싱
-    let cursor = Cursor::new(bytes);
-    let mut workbook: Xlsx<_> = Xlsx::new(cursor)
-        .map_err(|e| format!("엑셀 파일 읽기 실패: {}", e))?;
+// 데이터셋 가져오기 진행 상황 이벤트 (dataset-import-progress)
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetImportProgress {
+    pub name: String,
+    pub rows: i64,   // 지금까지 저장한 행 수
+}
 
-    // 모든 시트 읽기
-    let sheet_names = workbook.sheet_names().to_vec();
-    if sheet_names.is_empty() {
-        return Err("엑셀 파일에 시트가 없습니다".to_string());
+fn to_import_result(dataset: dataset_import::ImportedDataset) -> ImportExcelResult {
+    ImportExcelResult {
+        success: true,
+        dataset_id: dataset.dataset_id,
+        message: format!("{}개 행 임포트 완료", dataset.row_count),
+        name: dataset.name,
+        columns: dataset.columns,
+        row_count: dataset.row_count,
     }
+}
 
-    let mut all_rows_data: Vec<Vec<String>> = Vec::new();
-    let mut columns: Vec<String> = Vec::new();
-    let mut first_sheet = true;
-
-    // 모든 시트를 순회하며 데이터 합치기
-    for sheet_name in &sheet_names {
-        let range = match workbook.worksheet_range(sheet_name) {
-            Ok(r) => r,
-            Err(_) => continue,
-        };
-
-        for (row_idx, row) in range.rows().enumerate() {
-            let row_values: Vec<String> = row
-                .iter()
-                .map(|cell| {
-                    match cell {
-                        calamine::Data::Empty => String::new(),
-                        calamine::Data::String(s) => s.clone(),
-                        calamine::Data::Float(f) => {
-                            if f.fract() == 0.0 {
-                                format!("{}", *f as i64)
-                            } else {
-                                format!("{}", f)
-                            }
-                        },
-                        calamine::Data::Int(i) => format!("{}", i),
-                        calamine::Data::Bool(b) => format!("{}", b),
-                        calamine::Data::DateTime(dt) => format!("{}", dt),
-                        calamine::Data::Error(e) => format!("Error: {:?}", e),
-                        _ => String::new(),
-                    }
-                })
-                .collect();
-
-            if row_idx == 0 && first_sheet {
-                // 첫 번째 시트의 첫 번째 행은 컬럼명
-                columns = row_values.iter()
-                    .enumerate()
-                    .map(|(i, v)| if v.is_empty() { format!("Column{}", i + 1) } else { v.clone() })
-                    .collect();
-            } else if row_idx > 0 || !first_sheet {
-                // 데이터 행 추가 (다른 시트의 헤더는 스킵)
-                if row_idx > 0 {
-                    all_rows_data.push(row_values);
-                }
-            }
-        }
-        first_sheet = false;
-    }
+fn emit_dataset_import_progress(app: &tauri::AppHandle, name: &str, rows: i64) {
+    app.emit("dataset-import-progress", DatasetImportProgress { name: name.to_string(), rows }).ok();
+}
 
-    let rows_data = all_rows_data;
+// 가져올 파일 열기 → (파일명, 형식 감지용 앞부분, 처음부터 읽는 reader)
+fn open_dataset_file(file_path: &str) -> Result<(String, Vec<u8>, std::io::BufReader<std::fs::File>), String> {
+    use std::io::{Read, Seek};
 
-    if columns.is_empty() {
-        return Err("컬럼 정보를 찾을 수 없습니다".to_string());
-    }
+    let mut file = std::fs::File::open(file_path).map_err(|e| format!("파일을 열 수 없습니다: {}", e))?;
+    let mut head = Vec::new();
+    (&mut file)
+        .take(dataset_import::SAMPLE_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+    file.rewind().map_err(|e| e.to_string())?;
 
-    // 데이터셋 이름 (파일명에서 확장자 제거)
-    let dataset_name = file_name
-        .rsplit('.')
-        .skip(1)
-        .collect::<Vec<_>>()
-        .into_iter()
-        .rev()
-        .collect::<Vec<_>>()
-        .join(".");
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.to_string());
+    Ok((file_name, head, std::io::BufReader::new(file)))
+}
 
-    let dataset_name = if dataset_name.is_empty() { file_name.clone() } else { dataset_name };
+// 엑셀/CSV 파일 임포트 (Base64 데이터로 받음, 형식은 내용으로 감지)
+// 헤더는 시트마다 감지하고, 시트가 여러 개면 sheet 열을 붙여 하나의 데이터셋으로 합침
+#[tauri::command]
+async fn import_excel(app: tauri::AppHandle, file_data: String, file_name: String) -> Result<ImportExcelResult, String> {
+    let bytes = base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD,
+        &file_data
+    ).map_err(|e| format!("Base64 디코딩 실패: {}", e))?;
 
-    // DB에 저장
-    let dataset_id = db::save_dataset(&dataset_name, "", &columns)
-        .map_err(|e| format!("데이터셋 저장 실패: {}", e))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let head = bytes[..bytes.len().min(dataset_import::SAMPLE_BYTES)].to_vec();
+        let options = dataset_import::ImportOptions { union: true, ..Default::default() };
+        let datasets = dataset_import::import(&file_name, &head, std::io::Cursor::new(bytes), &options, |name, rows| {
+            emit_dataset_import_progress(&app, name, rows)
+        })?;
+        datasets
+            .into_iter()
+            .next()
+            .map(to_import_result)
+            .ok_or_else(|| "가져올 데이터가 없습니다".to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    let row_count = db::save_dataset_rows(dataset_id, &rows_data)
-        .map_err(|e| format!("데이터 저장 실패: {}", e))?;
+// 가져오기 미리보기 (시트 목록, 감지한 헤더 위치, 컬럼 수, 행 수, 앞부분 행)
+#[tauri::command]
+async fn preview_dataset_file(file_path: String) -> Result<dataset_import::ImportPreview, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (file_name, head, reader) = open_dataset_file(&file_path)?;
+        dataset_import::preview(&file_name, &head, reader)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    Ok(ImportExcelResult {
-        success: true,
-        dataset_id,
-        name: dataset_name,
-        columns,
-        row_count,
-        message: format!("{}개 행 임포트 완료", row_count),
+// 파일 경로로 데이터셋 임포트 (큰 CSV도 메모리에 모두 올리지 않고 나눠 저장)
+// options 없으면 모든 시트를 감지한 헤더로 시트마다 데이터셋 하나씩
+#[tauri::command]
+async fn import_dataset_file(
+    app: tauri::AppHandle,
+    file_path: String,
+    options: Option<dataset_import::ImportOptions>,
+) -> Result<Vec<ImportExcelResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (file_name, head, reader) = open_dataset_file(&file_path)?;
+        let options = options.unwrap_or_default();
+        let datasets = dataset_import::import(&file_name, &head, reader, &options, |name, rows| {
+            emit_dataset_import_progress(&app, name, rows)
+        })?;
+        Ok(datasets.into_iter().map(to_import_result).collect())
     })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 // 데이터셋 목록 조회
@@ -1438,6 +3655,57 @@ fn get_datasets() -> Result<Vec<Dataset>, String> {
     db::get_all_datasets().map_err(|e| e.to_string())
 }
 
+// 타입에 맞게 변환한 데이터셋 행 조회 (페이징)
+#[tauri::command]
+async fn get_dataset_typed_rows(dataset_id: i64, offset: i64, limit: i64) -> Result<Vec<db::TypedDatasetRow>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        db::ensure_dataset_types(dataset_id).map_err(|e| e.to_string())?;
+        db::get_dataset_typed_rows(dataset_id, offset, limit).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 데이터셋 질의 (필터, 정렬, 그룹/집계, 페이지) - AI 없이 전체 행에서 바로 계산
+#[tauri::command]
+async fn run_dataset_query(
+    dataset_id: i64,
+    query: dataset_query::DatasetQuery,
+) -> Result<dataset_query::DatasetQueryResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        db::ensure_dataset_types(dataset_id).map_err(|e| e.to_string())?;
+        let dataset = db::get_dataset(dataset_id).map_err(|e| e.to_string())?;
+        let built = dataset_query::build(&dataset.columns, &dataset.column_types, &query)?;
+        db::run_dataset_query(dataset_id, &built).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 컬럼 타입 직접 지정 (컬럼 순서대로, null이면 추측한 타입) → 값 다시 변환
+#[tauri::command]
+async fn set_dataset_column_types(
+    dataset_id: i64,
+    overrides: Vec<Option<dataset_schema::ColumnType>>,
+) -> Result<Dataset, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        db::set_dataset_column_overrides(dataset_id, &overrides).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 컬럼 타입 다시 추측 (사용자 지정 타입은 유지)
+#[tauri::command]
+async fn refresh_dataset_types(dataset_id: i64) -> Result<Dataset, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        db::refresh_dataset_types(dataset_id).map_err(|e| e.to_string())?;
+        db::get_dataset(dataset_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 // 데이터셋 상세 조회
 #[tauri::command]
 fn get_dataset_detail(id: i64) -> Result<Dataset, String> {
@@ -1472,18 +3740,32 @@ async fn analyze_dataset(id: i64) -> Result<DatasetAnalysis, String> {
 
     let model = db::get_setting("gemini_model").unwrap_or_default();
     let dataset = db::get_dataset(id).map_err(|e| e.to_string())?;
-    let rows = db::get_all_dataset_rows(id).map_err(|e| e.to_string())?;
 
-    // 최대 500행만 분석 (토큰 제한)
-    let sample_rows: Vec<Vec<String>> = rows.iter().take(500).map(|r| r.data.clone()).collect();
+    // 샘플은 앞 100행, 통계는 전체 행 기준 (샌드박스에서 계산)
+    let sample_rows: Vec<Vec<String>> = db::get_dataset_rows(id, 0, 100)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| r.data)
+        .collect();
+    let (row_count, profile) = tauri::async_runtime::spawn_blocking(move || {
+        let sandbox = dataset_sql::Sandbox::load(id)?;
+        Ok::<_, String>((sandbox.row_count(), dataset_sql::profile_to_text(&sandbox.profile()?)))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
 
-    let (analysis, input_tokens, output_tokens, cost) =
-        ai::analyze_dataset_data(&api_key, &model, &dataset.name, &dataset.columns, &sample_rows).await?;
+    let (analysis, input_tokens, output_tokens, cost, cache_saved) =
+        ai::analyze_dataset_data(&api_key, &model, &dataset.name, &dataset.columns, &sample_rows, row_count, &profile).await?;
 
-    // 사용량 기록
+    // 사용량 기록 (캐시 적중 시 비용 0 + 절감액)
     let model_name = if model.is_empty() { "gemini-2.0-flash" } else { &model };
-    db::log_api_usage("dataset_analyze", model_name, input_tokens, output_tokens, cost)
-        .map_err(|e| e.to_string())?;
+    if let Some(saved) = cache_saved {
+        db::log_api_cache_hit("dataset_analyze", model_name, input_tokens, output_tokens, saved)
+            .map_err(|e| e.to_string())?;
+    } else {
+        log_usage("dataset_analyze", model_name, input_tokens, output_tokens, cost)
+            .map_err(|e| e.to_string())?;
+    }
 
     Ok(DatasetAnalysis {
         summary: analysis.summary,
@@ -1498,10 +3780,25 @@ async fn analyze_dataset(id: i64) -> Result<DatasetAnalysis, String> {
         input_tokens,
         output_tokens,
         cost_usd: cost,
+        cached: cache_saved.is_some(),
     })
 }
 
+// 샌드박스에서 SQL 실행 (블로킹 스레드에서 실행하고 샌드박스는 돌려받음)
+async fn run_dataset_sql(
+    sandbox: dataset_sql::Sandbox,
+    sql: String,
+) -> Result<(dataset_sql::Sandbox, Result<dataset_sql::SqlResult, String>), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = sandbox.execute(&sql);
+        (sandbox, result)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 // AI 데이터셋 질문 답변
+// 모델이 질문을 SELECT 문으로 바꾸면 데이터셋 전체 행(메모리 샌드박스)에서 실행하고, 그 결과로 답변
 #[tauri::command]
 async fn query_dataset(id: i64, question: String) -> Result<DatasetQAResult, String> {
     let api_key = db::get_setting("gemini_api_key").map_err(|e| e.to_string())?;
@@ -1510,32 +3807,57 @@ async fn query_dataset(id: i64, question: String) -> Result<DatasetQAResult, Str
     }
 
     let model = db::get_setting("gemini_model").unwrap_or_default();
+    let model_name = if model.is_empty() { "gemini-2.0-flash" } else { &model };
     let dataset = db::get_dataset(id).map_err(|e| e.to_string())?;
-    let rows = db::get_all_dataset_rows(id).map_err(|e| e.to_string())?;
-
-    // 최대 500행만 분석
-    let sample_rows: Vec<Vec<String>> = rows.iter().take(500).map(|r| r.data.clone()).collect();
 
-    let (answer, relevant_indices, input_tokens, output_tokens, cost) =
-        ai::query_dataset_data(&api_key, &model, &dataset.name, &dataset.columns, &sample_rows, &question).await?;
-
-    // 사용량 기록
-    let model_name = if model.is_empty() { "gemini-2.0-flash" } else { &model };
-    db::log_api_usage("dataset_query", model_name, input_tokens, output_tokens, cost)
-        .map_err(|e| e.to_string())?;
+    let (sandbox, table_description) = tauri::async_runtime::spawn_blocking(move || {
+        let sandbox = dataset_sql::Sandbox::load(id)?;
+        let description = sandbox.describe(5)?;
+        Ok::<_, String>((sandbox, description))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let (mut plan, mut input_tokens, mut output_tokens, mut cost) =
+        ai::generate_dataset_sql(&api_key, &model, &dataset.name, &table_description, &question, None).await?;
+    log_usage("dataset_query", model_name, input_tokens, output_tokens, cost).map_err(|e| e.to_string())?;
+
+    let (sandbox, mut result) = run_dataset_sql(sandbox, plan.sql.clone()).await?;
+    if let Err(error) = &result {
+        // 실행에 실패하면 오류를 알려주고 한 번 다시 작성
+        let (retry, i, o, c) =
+            ai::generate_dataset_sql(&api_key, &model, &dataset.name, &table_description, &question, Some((&plan.sql, error)))
+                .await?;
+        log_usage("dataset_query", model_name, i, o, c).map_err(|e| e.to_string())?;
+        input_tokens += i;
+        output_tokens += o;
+        cost += c;
+        plan = retry;
+        result = run_dataset_sql(sandbox, plan.sql.clone()).await?.1;
+    }
+    let result = result.map_err(|e| format!("SQL 실행 실패: {}\n{}", e, plan.sql))?;
 
-    // 관련 행 추출
-    let relevant_rows: Vec<Vec<String>> = relevant_indices
-        .iter()
-        .filter_map(|&idx| sample_rows.get(idx).cloned())
-        .collect();
+    let (answer, i, o, c) = ai::answer_dataset_question(
+        &api_key,
+        &model,
+        &dataset.name,
+        &question,
+        &plan.sql,
+        &dataset_sql::result_to_text(&result, 200),
+    )
+    .await?;
+    log_usage("dataset_query", model_name, i, o, c).map_err(|e| e.to_string())?;
 
     Ok(DatasetQAResult {
         answer,
-        relevant_rows,
-        input_tokens,
-        output_tokens,
-        cost_usd: cost,
+        relevant_rows: dataset_sql::rows_as_strings(&result),
+        columns: result.columns,
+        truncated: result.truncated,
+        sql: plan.sql,
+        sql_explanation: plan.explanation,
+        input_tokens: input_tokens + i,
+        output_tokens: output_tokens + o,
+        cost_usd: cost + c,
     })
 }
 
@@ -1565,6 +3887,7 @@ struct ExtractResponse {
     input_tokens: i64,
     output_tokens: i64,
     cost_usd: f64,
+    cached: bool,
 }
 
 #[tauri::command]
@@ -1578,8 +3901,12 @@ async fn extract_from_url(url: String, schema: String) -> Result<ExtractResponse
 
     let result = ai::extract_data_from_url(&gemini_api_key, &model, &url, &schema).await?;
 
-    // 토큰 사용량 기록
-    let _ = db::log_api_usage("extract", &model, result.input_tokens, result.output_tokens, result.cost_usd);
+    // 토큰 사용량 기록 (캐시 적중 시 비용 0 + 절감액)
+    if result.cached {
+        let _ = db::log_api_cache_hit("extract", &model, result.input_tokens, result.output_tokens, result.saved_usd);
+    } else {
+        let _ = log_usage("extract", &model, result.input_tokens, result.output_tokens, result.cost_usd);
+    }
 
     Ok(ExtractResponse {
         url: result.url,
@@ -1587,6 +3914,7 @@ async fn extract_from_url(url: String, schema: String) -> Result<ExtractResponse
         input_tokens: result.input_tokens,
         output_tokens: result.output_tokens,
         cost_usd: result.cost_usd,
+        cached: result.cached,
     })
 }
 
@@ -1608,21 +3936,27 @@ async fn extract_from_urls(urls: Vec<String>, schema: String) -> Result<Vec<Extr
     let responses: Vec<ExtractResponse> = results
         .into_iter()
         .map(|r| {
-            total_input += r.input_tokens;
-            total_output += r.output_tokens;
-            total_cost += r.cost_usd;
+            if r.cached {
+                // 캐시 적중은 개별 기록 (비용 0 + 절감액)
+                let _ = db::log_api_cache_hit("extract_batch", &model, r.input_tokens, r.output_tokens, r.saved_usd);
+            } else {
+                total_input += r.input_tokens;
+                total_output += r.output_tokens;
+                total_cost += r.cost_usd;
+            }
             ExtractResponse {
                 url: r.url,
                 data: r.data,
                 input_tokens: r.input_tokens,
                 output_tokens: r.output_tokens,
                 cost_usd: r.cost_usd,
+                cached: r.cached,
             }
         })
         .collect();
 
     // 총 토큰 사용량 기록
-    let _ = db::log_api_usage("extract_batch", &model, total_input, total_output, total_cost);
+    let _ = log_usage("extract_batch", &model, total_input, total_output, total_cost);
 
     Ok(responses)
 }
@@ -1951,7 +4285,7 @@ async fn run_browser_agent(window: tauri::Window, goal: String, start_url: Strin
     let result = ai::run_agent(&gemini_api_key, &model, &goal, &start_url, max_steps, on_progress).await?;
 
     // 토큰 사용량 기록
-    let _ = db::log_api_usage(
+    let _ = log_usage(
         "agent",
         &model,
         result.total_input_tokens,
@@ -2336,36 +4670,130 @@ pub struct AlarmData {
     pub message: String,
     pub enabled: bool,
     pub days: Vec<i32>,
+    pub date: Option<String>,
+    pub timezone: Option<String>,
+    pub schedule_id: Option<i64>,
+    pub offset_minutes: i64,
+    pub snooze_minutes: i64,
+    pub next_fire_at: Option<String>,
+}
+
+impl From<&db::Alarm> for AlarmData {
+    fn from(a: &db::Alarm) -> Self {
+        AlarmData {
+            id: a.id,
+            time: a.time.clone(),
+            message: a.message.clone(),
+            enabled: a.enabled,
+            days: serde_json::from_str(&a.days).unwrap_or_default(),
+            date: a.date.clone(),
+            timezone: a.timezone.clone(),
+            schedule_id: a.schedule_id,
+            offset_minutes: a.offset_minutes,
+            snooze_minutes: a.snooze_minutes,
+            next_fire_at: a.next_fire_at.clone(),
+        }
+    }
+}
+
+// 알람 추가 옵션 (모두 생략 가능)
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct AlarmOptions {
+    #[serde(default)]
+    pub date: Option<String>,            // YYYY-MM-DD 그날 한 번만
+    #[serde(default)]
+    pub timezone: Option<String>,        // IANA 타임존
+    #[serde(default)]
+    pub schedule_id: Option<i64>,        // 이 일정 시작 offset_minutes분 전
+    #[serde(default)]
+    pub offset_minutes: Option<i64>,
+    #[serde(default)]
+    pub snooze_minutes: Option<i64>,     // 다시 알림 간격 (기본: 설정값)
 }
 
 // 알람 저장
 #[tauri::command]
-async fn save_alarm(time: String, message: String, days: Vec<i32>) -> Result<i64, String> {
+async fn save_alarm(time: String, message: String, days: Vec<i32>, options: Option<AlarmOptions>) -> Result<i64, String> {
+    let options = options.unwrap_or_default();
+
+    let date = options.date.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(date) = &date {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("잘못된 날짜: {}", date))?;
+    }
+    let timezone = options.timezone.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if let Some(timezone) = &timezone {
+        reminder::parse_timezone(timezone)?;
+    }
+    if let Some(schedule_id) = options.schedule_id {
+        db::get_schedule(schedule_id).map_err(|e| e.to_string())?.ok_or("일정을 찾을 수 없습니다")?;
+    } else {
+        chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("잘못된 시각: {}", time))?;
+    }
+    let offset_minutes = options.offset_minutes.unwrap_or(0);
+    if offset_minutes < 0 {
+        return Err("몇 분 전인지는 0 이상이어야 합니다".to_string());
+    }
+    let snooze_minutes = match options.snooze_minutes {
+        Some(minutes) => minutes,
+        None => db::get_setting("reminder_default_snooze_minutes")
+            .map_err(|e| e.to_string())?
+            .trim()
+            .parse()
+            .unwrap_or(10),
+    };
+    if snooze_minutes <= 0 {
+        return Err("다시 알림 간격은 1분 이상이어야 합니다".to_string());
+    }
+
     let days_json = serde_json::to_string(&days).unwrap_or_default();
-    db::save_alarm(&time, &message, &days_json).map_err(|e| e.to_string())
+    let id = db::save_alarm(&db::Alarm {
+        id: 0,
+        time: time.trim().to_string(),
+        message,
+        enabled: true,
+        days: days_json,
+        created_at: String::new(),
+        date,
+        timezone,
+        schedule_id: options.schedule_id,
+        offset_minutes,
+        snooze_minutes,
+        next_fire_at: None,
+    }).map_err(|e| e.to_string())?;
+
+    refresh_alarm(id)?;
+    Ok(id)
+}
+
+// 알람의 다음 울릴 시각 다시 계산
+fn refresh_alarm(id: i64) -> Result<(), String> {
+    if let Some(alarm) = db::get_alarm(id).map_err(|e| e.to_string())? {
+        reminder::refresh_alarm(&alarm, reminder::now_local())?;
+    }
+    Ok(())
 }
 
 // 전체 알람 조회
 #[tauri::command]
 async fn get_alarms() -> Result<Vec<AlarmData>, String> {
     let alarms = db::get_all_alarms().map_err(|e| e.to_string())?;
-    let result: Vec<AlarmData> = alarms.iter().map(|a| {
-        let days: Vec<i32> = serde_json::from_str(&a.days).unwrap_or_default();
-        AlarmData {
-            id: a.id,
-            time: a.time.clone(),
-            message: a.message.clone(),
-            enabled: a.enabled,
-            days,
-        }
-    }).collect();
-    Ok(result)
+    Ok(alarms.iter().map(AlarmData::from).collect())
+}
+
+// 다가오는 알람 (hours 시간 안, 없으면 기간 제한 없음)
+#[tauri::command]
+async fn get_upcoming_alarms(hours: Option<i64>, limit: Option<i64>) -> Result<Vec<AlarmData>, String> {
+    let until = hours.map(|h| reminder::format_datetime(&(reminder::now_local() + chrono::Duration::hours(h))));
+    let alarms = db::get_upcoming_alarms(until.as_deref(), limit.unwrap_or(20))
+        .map_err(|e| e.to_string())?;
+    Ok(alarms.iter().map(AlarmData::from).collect())
 }
 
 // 알람 토글
 #[tauri::command]
 async fn toggle_alarm(id: i64) -> Result<(), String> {
-    db::toggle_alarm(id).map_err(|e| e.to_string())
+    db::toggle_alarm(id).map_err(|e| e.to_string())?;
+    refresh_alarm(id)
 }
 
 // 알람 삭제
@@ -2374,6 +4802,59 @@ async fn delete_alarm(id: i64) -> Result<(), String> {
     db::delete_alarm(id).map_err(|e| e.to_string())
 }
 
+// === 리마인더 ===
+
+// 알림 전달 (프론트엔드 이벤트 + 설정에 따라 OS 알림)
+fn notify_reminder(app: &tauri::AppHandle, fired: &reminder::FiredReminder) {
+    use tauri_plugin_notification::NotificationExt;
+
+    app.emit("reminder", fired).ok();
+    if db::get_setting("reminder_os_notification").unwrap_or_default() != "false" {
+        app.notification()
+            .builder()
+            .title(&fired.reminder.title)
+            .body(&fired.reminder.message)
+            .show()
+            .ok();
+    }
+}
+
+// 리마인더 스케줄러 (매 분 정각에 확인, 절전에서 깨어나면 놓친 알림도 울림)
+fn start_reminder_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Ok(fired) = reminder::tick(reminder::now_local()) {
+                for item in &fired {
+                    notify_reminder(&app, item);
+                }
+            }
+            tokio::time::sleep(reminder::until_next_minute(reminder::now_local())).await;
+        }
+    });
+}
+
+// 닫지 않은 알림 목록
+#[tauri::command]
+fn get_active_reminders() -> Result<Vec<db::Reminder>, String> {
+    db::get_active_reminders().map_err(|e| e.to_string())
+}
+
+// 다시 알림 (minutes가 없으면 기본 간격), 다시 울릴 시각 반환
+#[tauri::command]
+fn snooze_reminder(key: String, minutes: Option<i64>) -> Result<String, String> {
+    reminder::snooze(&key, minutes)
+}
+
+// 알림 닫기
+#[tauri::command]
+fn dismiss_reminder(key: String) -> Result<(), String> {
+    if db::dismiss_reminder(&key).map_err(|e| e.to_string())? {
+        Ok(())
+    } else {
+        Err("알림을 찾을 수 없습니다".to_string())
+    }
+}
+
 // 위젯 창 열기
 #[tauri::command]
 async fn open_widget(app: tauri::AppHandle, widget_type: String, widget_id: Option<String>) -> Result<(), String> {
@@ -2437,9 +4918,13 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             let app_dir = app.path().app_data_dir().expect("Failed to get app dir");
             db::init_db(app_dir).expect("Failed to init database");
+            let _ = APP_HANDLE.set(app.handle().clone());
+            start_reminder_scheduler(app.handle().clone());
+            start_attachment_text_watcher(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -2451,6 +4936,20 @@ pub fn run() {
             save_setting,
             get_setting,
             get_usage,
+            clear_ai_cache,
+            get_usage_stats,
+            get_top_cost_operations,
+            export_usage_csv,
+            get_model_prices,
+            save_model_price,
+            delete_model_price,
+            get_ai_output_failures,
+            clear_ai_output_failures,
+            get_budgets,
+            save_budget,
+            delete_budget,
+            get_budget_status,
+            approve_budget_overrun,
             export_db,
             import_db,
             update_memo,
@@ -2461,23 +4960,85 @@ pub fn run() {
             delete_memo,
             delete_all_memos,
             get_schedules,
+            get_schedule_occurrences,
+            set_schedule_recurrence,
+            skip_schedule_occurrence,
             delete_schedule,
+            export_ics,
+            import_ics,
+            sync_caldav,
+            test_caldav_connection,
+            get_caldav_conflicts,
+            resolve_caldav_conflict,
             get_todos,
             toggle_todo,
+            set_todo_parent,
+            set_todo_project,
+            reorder_todos,
+            get_todo_projects,
+            set_todo_recurrence,
+            get_todo_streaks,
+            get_todo_history,
             delete_todo,
             get_transactions,
             delete_transaction,
             update_transaction,
+            get_transaction_stats,
+            get_transaction_balance,
+            get_transaction_categories,
+            save_transaction_category,
+            rename_transaction_category,
+            delete_transaction_category,
+            get_transaction_budgets,
+            save_transaction_budget,
+            delete_transaction_budget,
+            get_transaction_budget_status,
+            get_accounts,
+            save_account,
+            delete_account,
+            create_transfer,
+            get_exchange_rates,
+            save_exchange_rate,
+            delete_exchange_rate,
+            set_base_currency,
+            get_bank_csv_headers,
+            preview_bank_import,
+            import_bank_statement,
+            get_bank_import_profiles,
+            save_bank_import_profile,
+            delete_bank_import_profile,
+            get_category_rules,
+            save_category_rule,
+            delete_category_rule,
             add_attachment,
             get_attachments,
             remove_attachment,
             open_attachment,
             search_attachments,
+            scan_receipt,
+            save_receipt_transaction,
+            get_transaction_receipt,
+            refresh_attachment_texts,
+            get_attachment_text,
+            scan_attachments,
+            find_attachment_by_hash,
+            relocate_attachment,
+            accept_attachment_change,
+            get_attachment_previews,
+            get_attachment_preview,
+            collect_attachment_garbage,
+            move_attachment_storage,
             scan_folder,
             analyze_files_for_organization,
             execute_organization,
             run_research,
             import_excel,
+            preview_dataset_file,
+            import_dataset_file,
+            get_dataset_typed_rows,
+            run_dataset_query,
+            set_dataset_column_types,
+            refresh_dataset_types,
             get_datasets,
             get_dataset_detail,
             get_dataset_rows,
@@ -2515,8 +5076,12 @@ pub fn run() {
             open_widget,
             save_alarm,
             get_alarms,
+            get_upcoming_alarms,
             toggle_alarm,
-            delete_alarm
+            delete_alarm,
+            get_active_reminders,
+            snooze_reminder,
+            dismiss_reminder
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
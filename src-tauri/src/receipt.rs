@@ -0,0 +1,182 @@
+// ===== 영수증 인식 (첨부 이미지 → 가계부 거래) =====
+//
+// 인식 방식 (설정 receipt_ocr_backend)
+//   vision  - 이미지를 그대로 Gemini 비전 모델에 보냄 (기본)
+//   command - 로컬 OCR 명령(receipt_ocr_command, 예: tesseract)으로 텍스트를 읽고
+//             API 키가 있으면 텍스트 모델로 구조화, 없으면 아래 규칙 기반 파서로 추출
+// 인식 결과는 바로 저장하지 않고 사용자가 검토/수정한 뒤 저장
+
+use crate::ai::{ReceiptInfo, ReceiptLineInfo};
+use crate::bank_import;
+use regex::Regex;
+
+/// 인라인 전송 가능한 최대 파일 크기 (Gemini 요청 20MB 제한, base64로 약 4/3배 커짐)
+pub const MAX_INLINE_BYTES: u64 = 15 * 1024 * 1024;
+
+/// 로컬 OCR 기본 명령 ({file}은 이미지 경로로 치환, 표준 출력을 인식 텍스트로 사용)
+pub const DEFAULT_OCR_COMMAND: &str = "tesseract {file} stdout -l kor+eng";
+
+/// 총액 줄 키워드 (뒤쪽일수록 우선)
+const TOTAL_KEYWORDS: &[&str] = &["합계", "총액", "총 금액", "총금액", "TOTAL", "판매금액", "결제금액", "결제 금액", "받을금액", "청구금액", "AMOUNT DUE"];
+
+/// 총액 후보에서 제외할 줄 (소계, 세금, 받은 돈, 거스름돈 등)
+const NON_TOTAL_KEYWORDS: &[&str] = &["소계", "SUBTOTAL", "부가세", "VAT", "TAX", "과세", "면세", "할인", "받은", "거스름", "CHANGE", "CASH", "TENDER", "포인트"];
+
+/// 상호명 줄 라벨
+const MERCHANT_LABELS: &[&str] = &["상호", "가맹점", "매장명", "점포명", "STORE"];
+
+/// 영수증으로 인식할 수 있는 파일의 MIME 타입 (확장자 기준)
+pub fn mime_type(path: &str) -> Option<&'static str> {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())?
+        .to_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "webp" => Some("image/webp"),
+        "heic" => Some("image/heic"),
+        "heif" => Some("image/heif"),
+        "pdf" => Some("application/pdf"),
+        _ => None,
+    }
+}
+
+/// 로컬 OCR 명령 실행 → 인식된 텍스트
+pub async fn run_ocr_command(template: &str, path: &str) -> Result<String, String> {
+    let template = if template.trim().is_empty() { DEFAULT_OCR_COMMAND } else { template };
+    let mut args: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| arg.replace("{file}", path))
+        .collect();
+    if !template.contains("{file}") {
+        args.push(path.to_string());
+    }
+    let program = args.remove(0);
+
+    let output = tokio::process::Command::new(&program)
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("OCR 명령 실행 실패 ({}): {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "OCR 명령 오류 ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if text.is_empty() {
+        return Err("영수증에서 글자를 읽지 못했습니다".to_string());
+    }
+    Ok(text)
+}
+
+/// 줄의 마지막 금액 (12,500원 / $12.50 → 12500 / 12.5)
+fn last_amount(line: &str) -> Option<f64> {
+    let re = Regex::new(r"-?\d[\d,]*(?:\.\d{1,2})?").ok()?;
+    re.find_iter(line)
+        .last()
+        .and_then(|m| bank_import::parse_amount(m.as_str()))
+}
+
+fn contains_any(line: &str, keywords: &[&str]) -> bool {
+    let upper = line.to_uppercase();
+    keywords.iter().any(|k| upper.contains(k))
+}
+
+/// OCR 텍스트에서 규칙 기반으로 영수증 정보 추출 (API 키 없이 로컬 OCR만 쓸 때)
+pub fn parse_receipt_text(text: &str) -> ReceiptInfo {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+
+    let date_re = Regex::new(r"(20\d{2})\s*[-./년]\s*(\d{1,2})\s*[-./월]\s*(\d{1,2})(?:\s*일)?(?:\D{0,12}(\d{1,2}):(\d{2}))?").unwrap();
+    let date = lines.iter().find_map(|line| {
+        let caps = date_re.captures(line)?;
+        let date = format!("{}-{:0>2}-{:0>2}", &caps[1], &caps[2], &caps[3]);
+        let text = match (caps.get(4), caps.get(5)) {
+            (Some(h), Some(m)) => format!("{} {:0>2}:{}", date, h.as_str(), m.as_str()),
+            _ => date,
+        };
+        bank_import::parse_date(&text, None)
+    });
+
+    let merchant = lines
+        .iter()
+        .find_map(|line| {
+            let label = MERCHANT_LABELS.iter().find(|l| line.to_uppercase().starts_with(*l))?;
+            let value = line[label.len()..]
+                .trim_start_matches(|c: char| c.is_alphabetic() && !c.is_ascii_uppercase())
+                .trim_start_matches([':', ' ', '\t'])
+                .trim();
+            (!value.is_empty()).then(|| value.to_string())
+        })
+        .or_else(|| {
+            // 라벨이 없으면 첫 줄 중 숫자/날짜가 아닌 줄 (보통 맨 위가 상호)
+            lines
+                .iter()
+                .take(5)
+                .find(|l| {
+                    !date_re.is_match(l)
+                        && !l.contains("영수증")
+                        && !l.to_uppercase().contains("RECEIPT")
+                        && l.chars().filter(|c| c.is_alphabetic()).count() >= 2
+                })
+                .map(|l| l.to_string())
+        });
+
+    // 키워드 줄 중 가장 우선순위가 높은 것, 없으면 가장 큰 금액
+    let total = lines
+        .iter()
+        .filter(|l| !contains_any(l, NON_TOTAL_KEYWORDS))
+        .filter_map(|l| {
+            let priority = TOTAL_KEYWORDS.iter().rposition(|k| l.to_uppercase().contains(k))?;
+            Some((priority, last_amount(l)?))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+        .map(|(_, amount)| amount)
+        .or_else(|| {
+            lines
+                .iter()
+                .filter(|l| !contains_any(l, NON_TOTAL_KEYWORDS) && !date_re.is_match(l))
+                .filter_map(|l| last_amount(l))
+                .max_by(f64::total_cmp)
+        })
+        .unwrap_or(0.0)
+        .abs();
+
+    // 품목 줄: "이름 [단가] 수량 금액"
+    let item_re = Regex::new(r"^(\D.*?)\s+(?:[\d,]+\s+)?(\d{1,3})\s+([\d,]+(?:\.\d{1,2})?)$").unwrap();
+    let items = lines
+        .iter()
+        .filter(|l| !contains_any(l, TOTAL_KEYWORDS) && !contains_any(l, NON_TOTAL_KEYWORDS))
+        .filter_map(|l| {
+            let caps = item_re.captures(l)?;
+            Some(ReceiptLineInfo {
+                name: caps[1].trim().to_string(),
+                quantity: caps[2].parse().ok(),
+                amount: bank_import::parse_amount(&caps[3])?,
+            })
+        })
+        .collect();
+
+    let currency = if text.contains('$') {
+        Some("USD".to_string())
+    } else if text.contains('€') {
+        Some("EUR".to_string())
+    } else if text.contains('¥') || text.contains('円') {
+        Some("JPY".to_string())
+    } else {
+        None
+    };
+
+    ReceiptInfo {
+        merchant,
+        date,
+        total,
+        currency,
+        category: None,
+        items,
+    }
+}
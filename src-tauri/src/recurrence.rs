@@ -0,0 +1,389 @@
+// ===== 반복 일정 (RFC 5545 RRULE) =====
+//
+// 지원 범위: FREQ (DAILY/WEEKLY/MONTHLY/YEARLY), INTERVAL, COUNT, UNTIL,
+// BYDAY (MONTHLY/YEARLY에서는 2TU, -1FR 같은 순번 포함 - YEARLY에서 BYMONTH가 없으면 연 단위 순번), BYMONTHDAY, BYMONTH, WKST
+// 시간은 모두 로컬 시간(타임존 없는 값)으로 처리 (UTC로 적힌 UNTIL은 로컬 시간으로 변환)
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+
+/// 한 번의 전개에서 검사하는 최대 주기 수 (잘못된 규칙으로 무한 반복 방지, 구간 시작 전 주기는 건너뛴 뒤부터 셈)
+const MAX_PERIODS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    pub by_day: Vec<(Option<i32>, Weekday)>,  // (순번, 요일) - 순번은 MONTHLY/YEARLY에서만 의미 있음
+    pub by_month_day: Vec<i32>,               // 음수는 말일부터 (-1 = 마지막 날)
+    pub by_month: Vec<u32>,
+    pub week_start: Weekday,
+}
+
+/// 요일 코드 (MO, TU, ...) → Weekday
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// 날짜/시간 문자열 파싱
+/// 일정에서 쓰는 "YYYY-MM-DD", "YYYY-MM-DDTHH:MM", "YYYY-MM-DD HH:MM[:SS]"와
+/// iCalendar 형식 "YYYYMMDD", "YYYYMMDDTHHMMSS[Z]"를 모두 허용 (Z는 무시하고 로컬 시간으로 취급)
+pub fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim().trim_end_matches('Z');
+    for fmt in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y%m%dT%H%M%S"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, fmt) {
+            return Some(dt);
+        }
+    }
+    for fmt in ["%Y-%m-%d", "%Y%m%d"] {
+        if let Ok(date) = NaiveDate::parse_from_str(value, fmt) {
+            return Some(date.and_time(NaiveTime::MIN));
+        }
+    }
+    None
+}
+
+/// 날짜만 있는 값인지 (종일 일정)
+pub fn is_date_only(value: &str) -> bool {
+    let value = value.trim();
+    !value.contains('T') && !value.contains(' ') && parse_datetime(value).is_some()
+}
+
+/// 원래 값과 같은 형식으로 출력 (날짜만 있으면 YYYY-MM-DD, 아니면 YYYY-MM-DDTHH:MM)
+pub fn format_like(dt: &NaiveDateTime, template: &str) -> String {
+    if is_date_only(template) {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.format("%Y-%m-%dT%H:%M").to_string()
+    }
+}
+
+/// RRULE 문자열 파싱 ("RRULE:" 접두사는 있어도 되고 없어도 됨)
+pub fn parse_rrule(rule: &str) -> Result<RRule, String> {
+    let rule = rule.trim();
+    let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+    let mut freq = None;
+    let mut parsed = RRule {
+        freq: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+        week_start: Weekday::Mon,
+    };
+
+    for part in rule.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("잘못된 RRULE 항목: {}", part))?;
+        let value = value.trim();
+        match key.trim().to_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match value.to_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    other => return Err(format!("지원하지 않는 FREQ: {}", other)),
+                });
+            }
+            "INTERVAL" => {
+                parsed.interval = value.parse().map_err(|_| format!("잘못된 INTERVAL: {}", value))?;
+                if parsed.interval == 0 {
+                    return Err("INTERVAL은 1 이상이어야 합니다".to_string());
+                }
+            }
+            "COUNT" => {
+                parsed.count = Some(value.parse().map_err(|_| format!("잘못된 COUNT: {}", value))?);
+            }
+            "UNTIL" => {
+                let until = parse_datetime(value).ok_or_else(|| format!("잘못된 UNTIL: {}", value))?;
+                // 날짜만 있으면 그날 하루 전체 포함, UTC(Z)면 로컬 시간으로 변환
+                parsed.until = Some(if is_date_only(value) {
+                    until + Duration::days(1) - Duration::seconds(1)
+                } else if value.ends_with('Z') {
+                    Utc.from_utc_datetime(&until).with_timezone(&Local).naive_local()
+                } else {
+                    until
+                });
+            }
+            "BYDAY" => {
+                for item in value.split(',') {
+                    let item = item.trim().to_uppercase();
+                    if item.len() < 2 {
+                        return Err(format!("잘못된 BYDAY: {}", item));
+                    }
+                    let (ordinal, code) = item.split_at(item.len() - 2);
+                    let weekday = parse_weekday(code).ok_or_else(|| format!("잘못된 요일: {}", code))?;
+                    let ordinal = if ordinal.is_empty() {
+                        None
+                    } else {
+                        let n: i32 = ordinal.trim_start_matches('+').parse().map_err(|_| format!("잘못된 BYDAY: {}", item))?;
+                        if n == 0 || n.abs() > 53 {
+                            return Err(format!("잘못된 BYDAY 순번: {}", item));
+                        }
+                        Some(n)
+                    };
+                    parsed.by_day.push((ordinal, weekday));
+                }
+            }
+            "BYMONTHDAY" => {
+                for item in value.split(',') {
+                    let day: i32 = item.trim().parse().map_err(|_| format!("잘못된 BYMONTHDAY: {}", item))?;
+                    if day == 0 || day.abs() > 31 {
+                        return Err(format!("잘못된 BYMONTHDAY: {}", item));
+                    }
+                    parsed.by_month_day.push(day);
+                }
+            }
+            "BYMONTH" => {
+                for item in value.split(',') {
+                    let month: u32 = item.trim().parse().map_err(|_| format!("잘못된 BYMONTH: {}", item))?;
+                    if !(1..=12).contains(&month) {
+                        return Err(format!("잘못된 BYMONTH: {}", item));
+                    }
+                    parsed.by_month.push(month);
+                }
+            }
+            "WKST" => {
+                parsed.week_start = parse_weekday(&value.to_uppercase()).ok_or_else(|| format!("잘못된 WKST: {}", value))?;
+            }
+            other => return Err(format!("지원하지 않는 RRULE 항목: {}", other)),
+        }
+    }
+
+    parsed.freq = freq.ok_or("RRULE에 FREQ가 없습니다")?;
+    Ok(parsed)
+}
+
+/// 해당 월의 일수
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// BYMONTHDAY 일치 여부 (음수는 말일부터)
+fn month_day_matches(rule: &RRule, date: &NaiveDate) -> bool {
+    let last_day = days_in_month(date.year(), date.month()) as i32;
+    rule.by_month_day.iter().any(|&d| {
+        let target = if d > 0 { d } else { last_day + d + 1 };
+        target == date.day() as i32
+    })
+}
+
+/// 월 단위 후보 날짜 (BYMONTHDAY / BYDAY 적용, 둘 다 없으면 시작일의 일자)
+fn month_candidates(rule: &RRule, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+    let last_day = days_in_month(year, month);
+    let all_days = (1..=last_day).filter_map(|d| NaiveDate::from_ymd_opt(year, month, d));
+
+    let day_matches = |date: &NaiveDate| month_day_matches(rule, date);
+
+    let weekday_matches = |date: &NaiveDate| -> bool {
+        rule.by_day.iter().any(|&(ordinal, weekday)| {
+            if date.weekday() != weekday {
+                return false;
+            }
+            match ordinal {
+                None => true,
+                Some(n) if n > 0 => (date.day() as i32 - 1) / 7 + 1 == n,
+                Some(n) => (last_day as i32 - date.day() as i32) / 7 + 1 == -n,
+            }
+        })
+    };
+
+    match (rule.by_month_day.is_empty(), rule.by_day.is_empty()) {
+        (true, true) => NaiveDate::from_ymd_opt(year, month, default_day).into_iter().collect(),
+        (false, true) => all_days.filter(day_matches).collect(),
+        (true, false) => all_days.filter(weekday_matches).collect(),
+        (false, false) => all_days.filter(|d| day_matches(d) && weekday_matches(d)).collect(),
+    }
+}
+
+/// 연 단위 후보 날짜 (BYMONTH 없이 BYDAY만 있을 때, 순번은 그해 전체에서 셈: 20MO = 그해 20번째 월요일)
+fn year_candidates(rule: &RRule, year: i32) -> Vec<NaiveDate> {
+    let (Some(first), Some(last)) = (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) else {
+        return Vec::new();
+    };
+    let days_in_year = last.ordinal() as i32;
+    first
+        .iter_days()
+        .take_while(|d| d.year() == year)
+        .filter(|date| {
+            rule.by_day.iter().any(|&(ordinal, weekday)| {
+                if date.weekday() != weekday {
+                    return false;
+                }
+                match ordinal {
+                    None => true,
+                    Some(n) if n > 0 => (date.ordinal() as i32 - 1) / 7 + 1 == n,
+                    Some(n) => (days_in_year - date.ordinal() as i32) / 7 + 1 == -n,
+                }
+            })
+        })
+        .collect()
+}
+
+/// n번째 주기에 해당하는 후보 날짜 목록 (정렬됨)
+fn period_candidates(rule: &RRule, start: NaiveDate, period: i64) -> Vec<NaiveDate> {
+    let step = period * rule.interval as i64;
+    let mut dates = match rule.freq {
+        Frequency::Daily => {
+            let date = start + Duration::days(step);
+            let ok = (rule.by_day.is_empty() || rule.by_day.iter().any(|&(_, w)| w == date.weekday()))
+                && (rule.by_month_day.is_empty() || month_day_matches(rule, &date));
+            if ok { vec![date] } else { vec![] }
+        }
+        Frequency::Weekly => {
+            // 주 시작 요일 기준으로 해당 주의 첫날 계산
+            let offset = (start.weekday().num_days_from_monday() + 7 - rule.week_start.num_days_from_monday()) % 7;
+            let week_start = start - Duration::days(offset as i64) + Duration::weeks(step);
+            let weekdays: Vec<Weekday> = if rule.by_day.is_empty() {
+                vec![start.weekday()]
+            } else {
+                rule.by_day.iter().map(|&(_, w)| w).collect()
+            };
+            (0..7)
+                .map(|i| week_start + Duration::days(i))
+                .filter(|d| weekdays.contains(&d.weekday()))
+                .collect()
+        }
+        Frequency::Monthly => {
+            let total = start.year() as i64 * 12 + start.month0() as i64 + step;
+            let (year, month) = ((total / 12) as i32, (total % 12) as u32 + 1);
+            month_candidates(rule, year, month, start.day())
+        }
+        Frequency::Yearly => {
+            let year = start.year() + step as i32;
+            if rule.by_month.is_empty() && !rule.by_day.is_empty() && rule.by_month_day.is_empty() {
+                // BYDAY만 있으면 그해 전체에서 찾음
+                year_candidates(rule, year)
+            } else {
+                let months: Vec<u32> = if rule.by_month.is_empty() { vec![start.month()] } else { rule.by_month.clone() };
+                months
+                    .into_iter()
+                    .flat_map(|m| month_candidates(rule, year, m, start.day()))
+                    .collect()
+            }
+        }
+    };
+
+    if !rule.by_month.is_empty() && rule.freq != Frequency::Yearly {
+        dates.retain(|d| rule.by_month.contains(&d.month()));
+    }
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+/// 구간 시작 전이라 건너뛰어도 되는 주기 수 (COUNT가 있으면 처음부터 세야 하므로 0)
+/// 주기 경계와 일정 길이를 고려해 한 주기 앞에서 시작
+fn skip_periods(rule: &RRule, dtstart: NaiveDateTime, duration: Duration, window_start: NaiveDateTime) -> i64 {
+    if rule.count.is_some() {
+        return 0;
+    }
+    let from = dtstart.date();
+    let to = (window_start - duration).date();
+    if to <= from {
+        return 0;
+    }
+    let elapsed = match rule.freq {
+        Frequency::Daily => (to - from).num_days(),
+        Frequency::Weekly => (to - from).num_days() / 7,
+        Frequency::Monthly => (to.year() as i64 * 12 + to.month0() as i64) - (from.year() as i64 * 12 + from.month0() as i64),
+        Frequency::Yearly => (to.year() - from.year()) as i64,
+    };
+    (elapsed / rule.interval as i64 - 1).max(0)
+}
+
+/// 반복 일정 전개: [window_start, window_end) 구간과 겹치는 발생 시각 목록
+/// duration: 일정 길이 (종료 시각이 없으면 0), exdates: 제외할 발생 시각 (날짜만 있으면 그날 전체 제외)
+/// COUNT는 RFC 5545대로 EXDATE 적용 전 발생 횟수 기준
+pub fn expand(
+    dtstart: NaiveDateTime,
+    rule: &RRule,
+    exdates: &[String],
+    duration: Duration,
+    window_start: NaiveDateTime,
+    window_end: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let excluded: Vec<(NaiveDateTime, bool)> = exdates
+        .iter()
+        .filter_map(|e| parse_datetime(e).map(|dt| (dt, is_date_only(e))))
+        .collect();
+    let is_excluded = |dt: &NaiveDateTime| {
+        excluded.iter().any(|(ex, date_only)| if *date_only { ex.date() == dt.date() } else { ex == dt })
+    };
+
+    let time = dtstart.time();
+    let mut occurrences = Vec::new();
+    let mut generated = 0u32;
+
+    let first_period = skip_periods(rule, dtstart, duration, window_start);
+    for period in first_period..first_period + MAX_PERIODS as i64 {
+        for date in period_candidates(rule, dtstart.date(), period) {
+            let dt = date.and_time(time);
+            if dt < dtstart {
+                continue;
+            }
+            if rule.until.is_some_and(|until| dt > until) || dt >= window_end {
+                return occurrences;
+            }
+            if rule.count.is_some_and(|count| generated >= count) {
+                return occurrences;
+            }
+            generated += 1;
+
+            let overlaps = dt >= window_start || dt + duration > window_start;
+            if overlaps && !is_excluded(&dt) {
+                occurrences.push(dt);
+            }
+        }
+    }
+
+    occurrences
+}
+
+/// 반복 할일의 다음 마감일
+/// current_due 다음 발생 시각을 찾되, 이미 지난 발생(밀린 회차)은 건너뛰고 오늘 이후 첫 발생을 반환
+/// 마감일이 없던 할일은 완료한 날을 기준으로 계산, COUNT는 호출하는 쪽에서 완료 횟수로 판단
+pub fn next_due(rule: &RRule, current_due: Option<&str>, completed_on: NaiveDate) -> Option<String> {
+    let today = completed_on.and_time(NaiveTime::MIN);
+    let template = current_due
+        .filter(|due| parse_datetime(due).is_some())
+        .map(str::to_string)
+        .unwrap_or_else(|| completed_on.format("%Y-%m-%d").to_string());
+    let anchor = parse_datetime(&template)?;
+
+    let rule = RRule { count: None, ..rule.clone() };
+    let window_start = (anchor + Duration::seconds(1)).max(today);
+    // 대부분 1년 안에 다음 회차가 있으므로 먼저 짧게 찾아보고, 없으면 넓혀서 찾음
+    [366, 366 * 100].iter().find_map(|days| {
+        expand(anchor, &rule, &[], Duration::zero(), window_start, window_start + Duration::days(*days))
+            .first()
+            .map(|dt| format_like(dt, &template))
+    })
+}
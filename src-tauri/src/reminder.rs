@@ -0,0 +1,364 @@
+// ===== 리마인더 스케줄러 =====
+//
+// 알람(HH:MM + 요일 / 특정 날짜 한 번 / 일정 N분 전, 타임존 지정 가능), 일정 시작(설정한 분만큼 미리),
+// 마감일이 있는 할일의 알림 시각을 계산해
+// 도래한 것을 알려줌. 알림마다 key(종류:ID@발생 시각)로 상태(fired/snoozed/dismissed)를 저장해
+// 같은 알림을 두 번 울리지 않고, 마지막 확인 시각을 저장해 두어 절전/종료 중 놓친 알림도 다시 울림
+//
+// 설정:
+// - reminders_enabled: "true"/"false"
+// - reminder_schedule_lead_minutes: 일정 시작 몇 분 전에 알릴지
+// - reminder_day_time: 종일 일정/날짜만 있는 할일을 알릴 시각 (HH:MM)
+// - reminder_catchup_hours: 놓친 알림을 몇 시간 전까지 다시 울릴지
+// - reminder_default_snooze_minutes: 다시 알림 기본 간격
+
+use crate::{db, recurrence};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+const LAST_CHECK_KEY: &str = "reminder_last_check";
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 상태 기록 보관 기간 (이보다 오래된 기록은 정리)
+const STATE_RETENTION_DAYS: i64 = 30;
+
+/// 반복 일정에 연결된 알람의 다음 회차를 찾는 범위
+const SCHEDULE_ALARM_LOOKAHEAD_DAYS: i64 = 366;
+
+/// 한 번의 확인에서 알람 하나가 울릴 수 있는 최대 횟수 (catchup 범위 안)
+const MAX_FIRES_PER_ALARM: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiredReminder {
+    pub reminder: db::Reminder,
+    pub missed: bool,  // 절전/종료 중 지나간 알림을 뒤늦게 울린 경우
+}
+
+struct ReminderSettings {
+    enabled: bool,
+    schedule_lead: Duration,
+    day_time: NaiveTime,
+    catchup: Duration,
+}
+
+impl ReminderSettings {
+    fn load() -> Result<Self, String> {
+        let setting = |key: &str| db::get_setting(key).map_err(|e| e.to_string());
+        Ok(Self {
+            enabled: setting("reminders_enabled")? != "false",
+            schedule_lead: Duration::minutes(setting("reminder_schedule_lead_minutes")?.trim().parse().unwrap_or(10).max(0)),
+            day_time: NaiveTime::parse_from_str(setting("reminder_day_time")?.trim(), "%H:%M")
+                .unwrap_or(NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
+            // 상태 기록을 지운 뒤에 다시 울리지 않도록 보관 기간 안으로 제한
+            catchup: Duration::hours(
+                setting("reminder_catchup_hours")?.trim().parse().unwrap_or(24).clamp(0, STATE_RETENTION_DAYS * 24),
+            ),
+        })
+    }
+}
+
+pub fn now_local() -> NaiveDateTime {
+    chrono::Local::now().naive_local()
+}
+
+pub fn format_datetime(dt: &NaiveDateTime) -> String {
+    dt.format(DATETIME_FORMAT).to_string()
+}
+
+/// 날짜만 있는 값은 day_time에, 시각이 있으면 그 시각에
+fn trigger_time(value: &str, day_time: NaiveTime) -> Option<NaiveDateTime> {
+    let dt = recurrence::parse_datetime(value)?;
+    if recurrence::is_date_only(value) {
+        Some(dt.date().and_time(day_time))
+    } else {
+        Some(dt)
+    }
+}
+
+fn reminder(kind: &str, source_id: i64, occurrence: &str, title: String, message: String, trigger_at: NaiveDateTime) -> db::Reminder {
+    db::Reminder {
+        key: format!("{}:{}@{}", kind, source_id, occurrence),
+        kind: kind.to_string(),
+        source_id,
+        title,
+        message,
+        trigger_at: format_datetime(&trigger_at),
+        status: "fired".to_string(),
+        snooze_until: None,
+        fired_at: None,
+    }
+}
+
+// ----- 알람 다음 시각 계산 -----
+
+/// IANA 타임존 이름 확인
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim().parse::<Tz>().map_err(|_| format!("알 수 없는 타임존: {}", name))
+}
+
+/// 타임존의 벽시계 시각 → 로컬 시각 (서머타임으로 없는 시각이면 1시간 뒤로)
+fn zone_to_local(tz: Option<Tz>, dt: NaiveDateTime) -> Option<NaiveDateTime> {
+    let tz = match tz {
+        Some(tz) => tz,
+        None => return Some(dt),
+    };
+    tz.from_local_datetime(&dt)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(dt + Duration::hours(1))).earliest())
+        .map(|zoned| zoned.with_timezone(&chrono::Local).naive_local())
+}
+
+/// 로컬 시각 → 타임존의 벽시계 시각
+fn local_to_zone(tz: Option<Tz>, dt: NaiveDateTime) -> Option<NaiveDateTime> {
+    let tz = match tz {
+        Some(tz) => tz,
+        None => return Some(dt),
+    };
+    chrono::Local
+        .from_local_datetime(&dt)
+        .earliest()
+        .map(|local| local.with_timezone(&tz).naive_local())
+}
+
+/// 연결된 일정의 다음 시작 시각 (after 이후)
+fn next_schedule_start(schedule: &db::Schedule, after: NaiveDateTime) -> Option<NaiveDateTime> {
+    let dtstart = recurrence::parse_datetime(schedule.start_time.as_deref()?)?;
+    let rule = match schedule.recurrence.as_deref().filter(|r| !r.trim().is_empty()) {
+        Some(rule) => recurrence::parse_rrule(rule).ok()?,
+        None => return Some(dtstart).filter(|start| *start > after),
+    };
+    let exdates: Vec<String> = schedule
+        .exdates
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    recurrence::expand(
+        dtstart,
+        &rule,
+        &exdates,
+        Duration::zero(),
+        after + Duration::seconds(1),
+        after + Duration::days(SCHEDULE_ALARM_LOOKAHEAD_DAYS),
+    )
+    .into_iter()
+    .find(|start| *start > after)
+}
+
+/// 알람이 after(로컬 시각) 이후 처음 울릴 로컬 시각
+pub fn alarm_next_fire(alarm: &db::Alarm, after: NaiveDateTime) -> Option<NaiveDateTime> {
+    // 일정 N분 전
+    if let Some(schedule_id) = alarm.schedule_id {
+        let schedule = db::get_schedule(schedule_id).ok()??;
+        let offset = Duration::minutes(alarm.offset_minutes.max(0));
+        return next_schedule_start(&schedule, after + offset).map(|start| start - offset);
+    }
+
+    let tz = match alarm.timezone.as_deref().filter(|t| !t.trim().is_empty()) {
+        Some(name) => Some(parse_timezone(name).ok()?),
+        None => None,
+    };
+    let time = NaiveTime::parse_from_str(alarm.time.trim(), "%H:%M").ok()?;
+
+    // 특정 날짜 한 번
+    if let Some(date) = alarm.date.as_deref().filter(|d| !d.trim().is_empty()) {
+        let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+        return zone_to_local(tz, date.and_time(time)).filter(|at| *at > after);
+    }
+
+    // 매일 / 요일 (요일은 알람 타임존 기준, 0=일요일)
+    let days: Vec<u32> = serde_json::from_str(&alarm.days).unwrap_or_default();
+    let start_date = local_to_zone(tz, after)?.date();
+    (0..=8)
+        .filter_map(|offset| start_date.checked_add_signed(Duration::days(offset)))
+        .filter(|date| days.is_empty() || days.contains(&date.weekday().num_days_from_sunday()))
+        .filter_map(|date| zone_to_local(tz, date.and_time(time)))
+        .find(|at| *at > after)
+}
+
+/// 한 번만 울리는 알람인지 (날짜 지정)
+fn is_one_shot(alarm: &db::Alarm) -> bool {
+    alarm.schedule_id.is_none() && alarm.date.as_deref().map(|d| !d.trim().is_empty()).unwrap_or(false)
+}
+
+/// 알람의 next_fire_at 갱신 (지난 일회성 알람은 끔)
+pub fn refresh_alarm(alarm: &db::Alarm, now: NaiveDateTime) -> Result<(), String> {
+    let next = if alarm.enabled { alarm_next_fire(alarm, now) } else { None };
+    let enabled = alarm.enabled && !(next.is_none() && is_one_shot(alarm));
+    let next_text = next.map(|n| format_datetime(&n));
+    if next_text != alarm.next_fire_at || enabled != alarm.enabled {
+        db::update_alarm_next_fire(alarm.id, next_text.as_deref(), enabled).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn alarm_message(alarm: &db::Alarm) -> String {
+    if !alarm.message.trim().is_empty() {
+        return alarm.message.clone();
+    }
+    match alarm.schedule_id.and_then(|id| db::get_schedule(id).ok().flatten()) {
+        Some(schedule) => format!("{} ({}분 전)", schedule.title, alarm.offset_minutes),
+        None => "알람".to_string(),
+    }
+}
+
+/// from < 알림 시각 <= to 인 알림 목록 (시각 순)
+fn reminders_between(from: NaiveDateTime, to: NaiveDateTime, settings: &ReminderSettings) -> Result<Vec<db::Reminder>, String> {
+    let mut reminders = Vec::new();
+    let in_window = |at: &NaiveDateTime| *at > from && *at <= to;
+
+    // 알람
+    for alarm in db::get_all_alarms().map_err(|e| e.to_string())? {
+        if !alarm.enabled {
+            continue;
+        }
+        let mut after = from;
+        for _ in 0..MAX_FIRES_PER_ALARM {
+            let at = match alarm_next_fire(&alarm, after).filter(|at| in_window(at)) {
+                Some(at) => at,
+                None => break,
+            };
+            let occurrence = format_datetime(&at);
+            reminders.push(reminder("alarm", alarm.id, &occurrence, "알람".to_string(), alarm_message(&alarm), at));
+            after = at;
+        }
+    }
+
+    // 일정: 시작 lead분 전 (종일 일정은 그날 day_time)
+    let lead = settings.schedule_lead;
+    let occurrences = db::get_schedule_occurrences(
+        &format_datetime(&(from + lead - Duration::days(1))),
+        &format_datetime(&(to + lead + Duration::days(1))),
+    ).map_err(|e| e.to_string())?;
+    for occurrence in occurrences {
+        let start = match recurrence::parse_datetime(&occurrence.occurrence_start) {
+            Some(start) => start,
+            None => continue,
+        };
+        let at = if recurrence::is_date_only(&occurrence.occurrence_start) {
+            start.date().and_time(settings.day_time)
+        } else {
+            start - lead
+        };
+        if !in_window(&at) {
+            continue;
+        }
+        let message = match occurrence.schedule.location.as_deref().filter(|l| !l.is_empty()) {
+            Some(location) => format!("{} ({}, {})", occurrence.schedule.title, occurrence.occurrence_start.replace('T', " "), location),
+            None => format!("{} ({})", occurrence.schedule.title, occurrence.occurrence_start.replace('T', " ")),
+        };
+        reminders.push(reminder(
+            "schedule",
+            occurrence.schedule.id,
+            &occurrence.occurrence_start,
+            "일정 알림".to_string(),
+            message,
+            at,
+        ));
+    }
+
+    // 할일: 마감 시각 (날짜만 있으면 그날 day_time)
+    for todo in db::get_all_todos().map_err(|e| e.to_string())? {
+        if todo.completed {
+            continue;
+        }
+        let due = match todo.due_date.as_deref() {
+            Some(due) => due,
+            None => continue,
+        };
+        if let Some(at) = trigger_time(due, settings.day_time).filter(|at| in_window(at)) {
+            reminders.push(reminder("todo", todo.id, due, "할일 마감".to_string(), todo.title.clone(), at));
+        }
+    }
+
+    reminders.sort_by(|a, b| a.trigger_at.cmp(&b.trigger_at));
+    Ok(reminders)
+}
+
+/// 마지막 확인 이후 도래한 알림과 다시 알림 시각이 된 알림을 기록하고 반환
+/// 처음 실행하면 지금부터 확인 (과거 알림을 한꺼번에 울리지 않음)
+pub fn tick(now: NaiveDateTime) -> Result<Vec<FiredReminder>, String> {
+    let settings = ReminderSettings::load()?;
+    let last_check = NaiveDateTime::parse_from_str(
+        &db::get_setting(LAST_CHECK_KEY).map_err(|e| e.to_string())?,
+        DATETIME_FORMAT,
+    ).ok();
+    db::save_setting(LAST_CHECK_KEY, &format_datetime(&now)).map_err(|e| e.to_string())?;
+
+    if !settings.enabled {
+        return Ok(Vec::new());
+    }
+
+    let mut fired = Vec::new();
+
+    if let Some(last_check) = last_check.filter(|l| *l < now) {
+        // 너무 오래 꺼져 있었으면 catchup 범위까지만
+        let from = last_check.max(now - settings.catchup);
+        // 1분 이상 지난 알림은 놓친 것으로 표시
+        let missed_before = now - Duration::minutes(1);
+        for reminder in reminders_between(from, now, &settings)? {
+            if db::reminder_exists(&reminder.key).map_err(|e| e.to_string())? {
+                continue;
+            }
+            db::record_reminder_fired(&reminder).map_err(|e| e.to_string())?;
+            let missed = NaiveDateTime::parse_from_str(&reminder.trigger_at, DATETIME_FORMAT)
+                .map(|at| at < missed_before)
+                .unwrap_or(false);
+            fired.push(FiredReminder { reminder, missed });
+        }
+    }
+
+    for reminder in db::get_due_snoozed_reminders(&format_datetime(&now)).map_err(|e| e.to_string())? {
+        db::record_reminder_fired(&reminder).map_err(|e| e.to_string())?;
+        fired.push(FiredReminder { reminder, missed: false });
+    }
+
+    db::cleanup_reminder_states(&format_datetime(&(now - Duration::days(STATE_RETENTION_DAYS))))
+        .map_err(|e| e.to_string())?;
+
+    for alarm in db::get_all_alarms().map_err(|e| e.to_string())? {
+        refresh_alarm(&alarm, now)?;
+    }
+
+    Ok(fired)
+}
+
+/// 다시 알림 (minutes가 없으면 알람에 지정한 간격, 그 외에는 기본 간격)
+pub fn snooze(key: &str, minutes: Option<i64>) -> Result<String, String> {
+    let reminder = db::get_reminder(key)
+        .map_err(|e| e.to_string())?
+        .ok_or("알림을 찾을 수 없습니다")?;
+    let alarm_minutes = match reminder.kind.as_str() {
+        "alarm" => db::get_alarm(reminder.source_id)
+            .map_err(|e| e.to_string())?
+            .map(|alarm| alarm.snooze_minutes),
+        _ => None,
+    };
+    let minutes = match minutes.or(alarm_minutes) {
+        Some(minutes) => minutes,
+        None => db::get_setting("reminder_default_snooze_minutes")
+            .map_err(|e| e.to_string())?
+            .trim()
+            .parse()
+            .unwrap_or(10),
+    };
+    if minutes <= 0 {
+        return Err("다시 알림 간격은 1분 이상이어야 합니다".to_string());
+    }
+    let until = format_datetime(&(now_local() + Duration::minutes(minutes)));
+    if !db::snooze_reminder(key, &until).map_err(|e| e.to_string())? {
+        return Err("알림을 찾을 수 없습니다".to_string());
+    }
+    Ok(until)
+}
+
+/// 다음 분 경계까지 남은 시간 (알람이 분 단위이므로 매 분 정각에 확인)
+pub fn until_next_minute(now: NaiveDateTime) -> std::time::Duration {
+    let minute_start = now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now);
+    (minute_start + Duration::minutes(1) - now)
+        .to_std()
+        .unwrap_or(std::time::Duration::from_secs(60))
+}
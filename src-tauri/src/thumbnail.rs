@@ -0,0 +1,172 @@
+// ===== 첨부파일 미리보기 (썸네일 + 이미지 정보) =====
+//
+// 이미지와 PDF 첫 페이지의 썸네일을 앱 데이터 디렉토리/thumbnails/<내용 해시>.jpg 로 만들어 둠
+// 내용 해시가 이름이라 같은 파일은 썸네일을 공유하고, 파일이 바뀌면 새로 만듦
+// 프론트엔드는 thumbnail_url (asset 프로토콜)로 원본을 열지 않고 바로 표시
+//
+// JPG/PNG/WEBP/GIF는 직접 줄이고, HEIC와 PDF는 설치된 도구가 있을 때만 만듦
+// (macOS: sips / qlmanage, 그 외: pdftoppm)
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// 썸네일 긴 변 길이 (px)
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+/// 이미지 정보 (EXIF가 없으면 크기만)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: Option<u32>,          // EXIF 회전을 적용한 크기
+    pub height: Option<u32>,
+    pub taken_at: Option<String>,    // 촬영 일시 (YYYY-MM-DDTHH:MM:SS)
+    pub camera: Option<String>,      // 제조사 + 모델
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// 이미지 crate로 직접 읽을 수 있는 형식
+fn is_decodable(path: &Path) -> bool {
+    matches!(extension(path).as_str(), "jpg" | "jpeg" | "png" | "webp" | "gif")
+}
+
+/// 미리보기를 만들 수 있는 파일인지 (확장자 기준)
+pub fn is_supported(path: &str) -> bool {
+    let path = Path::new(path);
+    is_decodable(path) || matches!(extension(path).as_str(), "heic" | "heif" | "pdf")
+}
+
+/// EXIF에서 촬영 일시, 카메라, 회전 값 읽기
+fn read_exif(path: &Path) -> Option<(Option<String>, Option<String>, u32)> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut reader = std::io::BufReader::new(file);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+
+    let text = |tag: exif::Tag| {
+        exif.get_field(tag, exif::In::PRIMARY)
+            .map(|f| f.display_value().to_string().trim_matches('"').trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let taken_at = text(exif::Tag::DateTimeOriginal)
+        .or_else(|| text(exif::Tag::DateTime))
+        .and_then(|v| chrono::NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S").ok())
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string());
+    let camera = match (text(exif::Tag::Make), text(exif::Tag::Model)) {
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    };
+    let orientation = exif
+        .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .unwrap_or(1);
+    Some((taken_at, camera, orientation))
+}
+
+/// 이미지 크기와 EXIF 정보
+pub fn read_image_info(path: &str) -> ImageInfo {
+    let path = Path::new(path);
+    let (taken_at, camera, orientation) = read_exif(path).unwrap_or((None, None, 1));
+    let (width, height) = match image::image_dimensions(path) {
+        // EXIF 회전 5~8은 가로/세로가 바뀜
+        Ok((w, h)) if (5..=8).contains(&orientation) => (Some(h), Some(w)),
+        Ok((w, h)) => (Some(w), Some(h)),
+        Err(_) => (None, None),
+    };
+    ImageInfo { width, height, taken_at, camera }
+}
+
+/// EXIF 회전 값대로 이미지를 바로 세움
+fn apply_orientation(image: image::DynamicImage, orientation: u32) -> image::DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// 외부 도구 실행 (성공하면 true)
+fn run_tool(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// 썸네일 생성 (target: .jpg 경로), 만들 수 없으면 Ok(false)
+pub fn generate(source: &str, target: &Path) -> Result<bool, String> {
+    let source = Path::new(source);
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    if is_decodable(source) {
+        let orientation = read_exif(source).map(|(_, _, o)| o).unwrap_or(1);
+        let image = image::open(source).map_err(|e| format!("이미지를 읽을 수 없습니다: {}", e))?;
+        apply_orientation(image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE), orientation)
+            .to_rgb8()
+            .save_with_format(target, image::ImageFormat::Jpeg)
+            .map_err(|e| format!("썸네일 저장 실패: {}", e))?;
+        return Ok(true);
+    }
+
+    let source_str = source.to_string_lossy().to_string();
+    let target_str = target.to_string_lossy().to_string();
+    let size = THUMBNAIL_SIZE.to_string();
+    let created = match extension(source).as_str() {
+        "heic" | "heif" => run_tool("sips", &["-s", "format", "jpeg", "-Z", &size, &source_str, "--out", &target_str]),
+        "pdf" => {
+            // pdftoppm은 <prefix>.jpg로 저장
+            let prefix = target.with_extension("").to_string_lossy().to_string();
+            run_tool("pdftoppm", &["-jpeg", "-f", "1", "-l", "1", "-singlefile", "-scale-to", &size, &source_str, &prefix])
+                || quicklook_thumbnail(source, target)
+        }
+        _ => false,
+    };
+    Ok(created && target.exists())
+}
+
+/// macOS Quick Look 썸네일 (임시 폴더에 <파일명>.png로 만든 뒤 JPEG로 변환)
+fn quicklook_thumbnail(source: &Path, target: &Path) -> bool {
+    if !cfg!(target_os = "macos") {
+        return false;
+    }
+    let temp = std::env::temp_dir().join(format!("jolajoa-ql-{}", uuid::Uuid::new_v4()));
+    if std::fs::create_dir_all(&temp).is_err() {
+        return false;
+    }
+    let source_str = source.to_string_lossy().to_string();
+    let temp_str = temp.to_string_lossy().to_string();
+    let size = THUMBNAIL_SIZE.to_string();
+    let mut created = false;
+    if run_tool("qlmanage", &["-t", "-s", &size, "-o", &temp_str, &source_str]) {
+        let file_name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let png = temp.join(format!("{}.png", file_name));
+        created = image::open(&png)
+            .and_then(|image| image.to_rgb8().save_with_format(target, image::ImageFormat::Jpeg))
+            .is_ok();
+    }
+    std::fs::remove_dir_all(&temp).ok();
+    created
+}
+
+/// asset 프로토콜 URL (프론트엔드 convertFileSrc와 같은 형식)
+pub fn asset_url(path: &str) -> String {
+    let encoded = urlencoding::encode(path);
+    if cfg!(windows) {
+        format!("http://asset.localhost/{}", encoded)
+    } else {
+        format!("asset://localhost/{}", encoded)
+    }
+}
@@ -72,6 +72,9 @@ interface SearchResult {
 
 type Tab = "input" | "search" | "schedule" | "todo" | "ledger" | "organize" | "research" | "collect" | "extract" | "agent" | "data" | "consulting" | "settings";
 
+// 데이터셋으로 가져올 수 있는 파일 확장자
+const DATASET_EXTENSIONS = ['xlsx', 'xls', 'ods', 'csv', 'tsv'];
+
 interface SearchItem {
   title: string;
   link: string;
@@ -130,6 +133,49 @@ interface Dataset {
   created_at: string;
 }
 
+interface ImportExcelResult {
+  success: boolean;
+  dataset_id: number;
+  name: string;
+  columns: string[];
+  row_count: number;
+  message: string;
+}
+
+interface DatasetHeaderLayout {
+  header_row: number;   // 헤더가 시작하는 행 (0부터)
+  header_rows: number;  // 헤더 행 수
+}
+
+interface DatasetSheetPreview {
+  name: string;
+  header: DatasetHeaderLayout;
+  columns: string[];
+  column_count: number;
+  row_count: number;
+  sample_rows: string[][];
+}
+
+interface DatasetImportPreview {
+  file_name: string;
+  format: string;
+  sheets: DatasetSheetPreview[];
+}
+
+// 가져오기 미리보기에서 고른 시트별 설정
+interface DatasetImportSheetDraft extends DatasetHeaderLayout {
+  selected: boolean;
+}
+
+interface DatasetImportDraft {
+  filePath: string;
+  preview: DatasetImportPreview;
+  sheets: DatasetImportSheetDraft[];
+  name: string;
+  union: boolean;
+  fillMerged: boolean;
+}
+
 interface DatasetRow {
   id: number;
   dataset_id: number;
@@ -161,7 +207,11 @@ interface DatasetAnalysis {
 
 interface DatasetQAResult {
   answer: string;
+  sql: string;
+  sql_explanation: string;
+  columns: string[];
   relevant_rows: string[][];
+  truncated: boolean;
   input_tokens: number;
   output_tokens: number;
   cost_usd: number;
@@ -204,13 +254,67 @@ interface Transaction {
   id: number;
   memo_id: number | null;
   tx_type: string;
-  amount: number;
+  amount: number; // 통화의 최소 단위 (KRW 원, USD 센트)
   description: string;
   category: string | null;
   tx_date: string | null;
   created_at: string;
+  currency: string;
+}
+
+// 기준 통화로 환산한 기간별 수입/지출 (get_transaction_stats)
+interface TransactionAggregate {
+  bucket: string;
+  group_key: string;
+  income: number;
+  expense: number;
+  net: number;
+  count: number;
+  unconverted: number; // 환율이 없어 합계에서 빠진 거래 수
 }
 
+// 통화 최소 단위 자릿수 (src-tauri/src/currency.rs의 exponent와 같은 규칙)
+const ZERO_DECIMAL_CURRENCIES = ['KRW', 'JPY', 'VND', 'CLP', 'ISK', 'PYG', 'UGX', 'XAF', 'XOF', 'XPF', 'KMF', 'GNF', 'RWF', 'VUV', 'BIF', 'DJF'];
+const THREE_DECIMAL_CURRENCIES = ['BHD', 'IQD', 'JOD', 'KWD', 'LYD', 'OMR', 'TND'];
+
+const currencyExponent = (code: string) =>
+  ZERO_DECIMAL_CURRENCIES.includes(code) ? 0 : THREE_DECIMAL_CURRENCIES.includes(code) ? 3 : 2;
+
+// 최소 단위 → 주 단위 (USD 1250 → 12.5)
+const toMajorAmount = (amount: number, code: string) => amount / 10 ** currencyExponent(code);
+
+// 주 단위 → 최소 단위 (USD 12.5 → 1250)
+const toMinorAmount = (value: number, code: string) => Math.round(value * 10 ** currencyExponent(code));
+
+// 최소 단위 금액 표시 (KRW는 "5,000원", 그 외는 "12.50 USD")
+const formatAmount = (amount: number, code: string) => {
+  const digits = currencyExponent(code);
+  const text = toMajorAmount(amount, code).toLocaleString(undefined, { minimumFractionDigits: digits, maximumFractionDigits: digits });
+  return code === 'KRW' ? `${text}원` : `${text} ${code}`;
+};
+
+// 헤더 행들 → 컬럼명 (src-tauri/src/dataset_import.rs의 header_names와 같은 규칙)
+const datasetHeaderNames = (header: string[][], fillMerged: boolean) => {
+  const width = Math.max(0, ...header.map(row => row.length));
+  const lines = header.map(row => Array.from({ length: width }, (_, i) => (row[i] ?? '').trim()));
+  if (fillMerged) {
+    for (const line of lines.slice(0, -1)) {
+      for (let i = 1; i < width; i++) {
+        if (!line[i]) line[i] = line[i - 1];
+      }
+    }
+  }
+  const columns: string[] = [];
+  for (let i = 0; i < width; i++) {
+    const parts = [...new Set(lines.map(line => line[i]).filter(Boolean))];
+    const name = parts.length ? parts.join(' ') : `Column${i + 1}`;
+    let unique = name;
+    for (let n = 2; columns.includes(unique); n++) unique = `${name}_${n}`;
+    columns.push(unique);
+  }
+  return columns;
+};
+
 interface Attachment {
   id: number;
   memo_id: number;
@@ -225,6 +329,8 @@ interface Attachment {
 function App() {
   const { t, i18n } = useTranslation();
   const [tab, setTab] = useState<Tab>("input");
+  const tabRef = useRef<Tab>(tab);
+  tabRef.current = tab;
   const [inputText, setInputText] = useState("");
   const [searchText, setSearchText] = useState("");
   const [loading, setLoading] = useState(false);
@@ -239,6 +345,8 @@ function App() {
   const [schedules, setSchedules] = useState<Schedule[]>([]);
   const [todos, setTodos] = useState<Todo[]>([]);
   const [transactions, setTransactions] = useState<Transaction[]>([]);
+  const [ledgerStats, setLedgerStats] = useState<TransactionAggregate[]>([]); // 월별 합계 (기준 통화)
+  const [baseCurrency, setBaseCurrency] = useState<string>('KRW');
   const [editingTx, setEditingTx] = useState<Transaction | null>(null);
   const [editTxType, setEditTxType] = useState<string>('expense');
   const [editTxAmount, setEditTxAmount] = useState<string>('');
@@ -339,6 +447,8 @@ function App() {
   const [datasetQALoading, setDatasetQALoading] = useState(false);
   const [datasetSearchQuery, setDatasetSearchQuery] = useState<string>("");
   const [isDraggingExcel, setIsDraggingExcel] = useState(false);
+  const [datasetImportDraft, setDatasetImportDraft] = useState<DatasetImportDraft | null>(null);
+  const importDatasetFileRef = useRef<((filePath: string) => Promise<void>) | null>(null);
 
   // 포모도로 관련 상태
   const [pomodoroState, setPomodoroState] = useState<'idle' | 'work' | 'break'>('idle');
@@ -579,10 +689,26 @@ function App() {
     } catch (e) { console.error(e); }
   };
 
+  // 월별 합계는 통화가 섞여 있으므로 백엔드에서 기준 통화로 환산한 값을 씀
+  const loadLedgerStats = async () => {
+    try {
+      const [stats, base] = await Promise.all([
+        invoke<TransactionAggregate[]>("get_transaction_stats", { startDate: "1900-01-01", endDate: "9999-12-31", period: "month" }),
+        invoke<string>("get_setting", { key: "base_currency" }),
+      ]);
+      setLedgerStats(stats);
+      if (base) setBaseCurrency(base);
+    } catch (e) { console.error(e); }
+  };
+
+  useEffect(() => {
+    loadLedgerStats();
+  }, [transactions]);
+
   const startEditTx = (tx: Transaction) => {
     setEditingTx(tx);
     setEditTxType(tx.tx_type);
-    setEditTxAmount(tx.amount.toString());
+    setEditTxAmount(String(toMajorAmount(tx.amount, tx.currency)));
     setEditTxDesc(tx.description);
     setEditTxCategory(tx.category || '');
     setEditTxDate(tx.tx_date || '');
@@ -594,7 +720,7 @@ function App() {
       await invoke("update_transaction", {
         id: editingTx.id,
         txType: editTxType,
-        amount: parseInt(editTxAmount) || 0,
+        amount: toMinorAmount(parseFloat(editTxAmount) || 0, editingTx.currency),
         description: editTxDesc,
         category: editTxCategory || null,
         txDate: editTxDate || null,
@@ -627,54 +753,112 @@ function App() {
     } catch (e) { console.error("Failed to load dataset rows:", e); }
   };
 
-  const handleExcelDrop = async (e: React.DragEvent<HTMLDivElement>) => {
-    e.preventDefault();
-    setIsDraggingExcel(false);
-
-    const files = e.dataTransfer.files;
-    if (files.length === 0) return;
+  // 파일 경로로 데이터셋 가져오기 (미리보기에서 시트와 헤더를 고른 뒤 confirmDatasetImport로 저장)
+  const importDatasetFile = async (filePath: string) => {
+    const fileName = filePath.split(/[\\/]/).pop() || filePath;
+    const ext = fileName.substring(fileName.lastIndexOf('.')).toLowerCase();
+    if (!DATASET_EXTENSIONS.includes(ext.slice(1))) {
+      showToast("지원되지 않는 파일 형식입니다. (xlsx, xls, ods, csv, tsv만 가능)");
+      return;
+    }
 
-    const file = files[0];
-    const validExtensions = ['.xlsx', '.xls', '.csv'];
-    const ext = file.name.substring(file.name.lastIndexOf('.')).toLowerCase();
+    setDatasetLoading(true);
+    try {
+      const preview = await invoke<DatasetImportPreview>("preview_dataset_file", { filePath });
+      if (preview.sheets.length === 0) {
+        showToast("가져올 데이터가 없습니다");
+        return;
+      }
+      setDatasetImportDraft({
+        filePath,
+        preview,
+        sheets: preview.sheets.map(sheet => ({ selected: true, ...sheet.header })),
+        name: "",
+        union: false,
+        fillMerged: true,
+      });
+    } catch (err) {
+      showToast(`❌ 임포트 실패: ${err}`);
+    } finally {
+      setDatasetLoading(false);
+    }
+  };
 
-    if (!validExtensions.includes(ext)) {
-      showToast("지원되지 않는 파일 형식입니다. (xlsx, xls, csv만 가능)");
+  // 미리보기에서 고른 설정으로 가져오기
+  const confirmDatasetImport = async () => {
+    if (!datasetImportDraft) return;
+    const { filePath, preview, sheets, name, union, fillMerged } = datasetImportDraft;
+    const selected = preview.sheets
+      .map((sheet, i) => ({ sheet, draft: sheets[i] }))
+      .filter(({ draft }) => draft.selected);
+    if (selected.length === 0) {
+      showToast("가져올 시트를 선택해주세요");
       return;
     }
 
+    setDatasetImportDraft(null);
     setDatasetLoading(true);
     try {
-      // 파일을 Base64로 변환
-      const reader = new FileReader();
-      reader.onload = async () => {
-        const base64 = (reader.result as string).split(',')[1];
-        try {
-          const result = await invoke<{ success: boolean; dataset_id: number; name: string; columns: string[]; row_count: number; message: string }>(
-            "import_excel",
-            { fileData: base64, fileName: file.name }
-          );
-
-          showToast(`✅ ${result.message}`);
-          await loadDatasets();
-
-          // 새로 추가된 데이터셋 자동 선택
-          const newDataset = await invoke<Dataset>("get_dataset_detail", { id: result.dataset_id });
-          setSelectedDataset(newDataset);
-          await loadDatasetRows(result.dataset_id);
-        } catch (err) {
-          showToast(`❌ 임포트 실패: ${err}`);
-        } finally {
-          setDatasetLoading(false);
-        }
-      };
-      reader.readAsDataURL(file);
+      const results = await invoke<ImportExcelResult[]>("import_dataset_file", {
+        filePath,
+        options: {
+          name: name.trim() || null,
+          sheets: selected.map(({ sheet, draft }) => ({
+            name: sheet.name,
+            header: { header_row: draft.header_row, header_rows: draft.header_rows },
+          })),
+          union: union && selected.length > 1,
+          fill_merged: fillMerged,
+        },
+      });
+      if (results.length === 0) {
+        showToast("가져올 데이터가 없습니다");
+        return;
+      }
+      showToast(`✅ ${results.map(r => r.message).join(", ")}`);
+      await loadDatasets();
+
+      // 새로 추가된 데이터셋 자동 선택
+      const newDataset = await invoke<Dataset>("get_dataset_detail", { id: results[0].dataset_id });
+      setSelectedDataset(newDataset);
+      await loadDatasetRows(results[0].dataset_id);
     } catch (err) {
-      showToast(`❌ 파일 읽기 실패: ${err}`);
+      showToast(`❌ 임포트 실패: ${err}`);
+    } finally {
       setDatasetLoading(false);
     }
   };
 
+  // 미리보기의 시트별 설정 변경
+  const updateDatasetImportSheet = (index: number, patch: Partial<DatasetImportSheetDraft>) => {
+    setDatasetImportDraft(prev => prev && {
+      ...prev,
+      sheets: prev.sheets.map((sheet, i) => (i === index ? { ...sheet, ...patch } : sheet)),
+    });
+  };
+  importDatasetFileRef.current = importDatasetFile;
+
+  const pickDatasetFile = async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        title: "가져올 데이터 파일 선택",
+        filters: [{ name: "스프레드시트", extensions: DATASET_EXTENSIONS }],
+      });
+      if (selected && typeof selected === 'string') {
+        await importDatasetFile(selected);
+      }
+    } catch (e) {
+      showToast(`❌ 파일 선택 실패: ${e}`);
+    }
+  };
+
+  // 파일 경로는 Tauri 드래그 앤 드롭 이벤트에서 받음 (아래 onDragDropEvent 참고)
+  const handleExcelDrop = (e: React.DragEvent<HTMLDivElement>) => {
+    e.preventDefault();
+    setIsDraggingExcel(false);
+  };
+
   const selectDataset = async (dataset: Dataset) => {
     setSelectedDataset(dataset);
     setDatasetAnalysis(null);
@@ -809,6 +993,17 @@ function App() {
 
             console.log("[Webview] 파일 드롭됨:", paths);
 
+            // 데이터 탭에서 스프레드시트를 놓으면 데이터셋으로 가져옴
+            const datasetFile = paths.find((path: string) =>
+              DATASET_EXTENSIONS.includes((path.split('.').pop() || '').toLowerCase())
+            );
+            if (tabRef.current === "data" && datasetFile) {
+              setIsDraggingFile(false);
+              setIsDraggingExcel(false);
+              importDatasetFileRef.current?.(datasetFile);
+              return;
+            }
+
             // 직접 pendingFiles에 추가 (useEffect 대신)
             setPendingFiles(prev => {
               const newFiles = paths.filter((path: string) => {
@@ -2825,6 +3020,178 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
         </div>
       )}
 
+      {/* 데이터셋 가져오기 미리보기 - 시트/헤더 위치 선택 */}
+      {datasetImportDraft && (
+        <div
+          onClick={() => setDatasetImportDraft(null)}
+          style={{
+            position: 'fixed',
+            top: 0,
+            left: 0,
+            right: 0,
+            bottom: 0,
+            background: 'rgba(0,0,0,0.5)',
+            zIndex: 300,
+            display: 'flex',
+            alignItems: 'center',
+            justifyContent: 'center',
+          }}
+        >
+          <div
+            onClick={(e) => e.stopPropagation()}
+            style={{
+              background: 'var(--bg)',
+              borderRadius: '12px',
+              padding: '20px',
+              width: 'min(720px, 92vw)',
+              maxHeight: '86vh',
+              overflowY: 'auto',
+              boxShadow: '0 8px 32px rgba(0,0,0,0.3)',
+              display: 'flex',
+              flexDirection: 'column',
+              gap: '12px',
+            }}
+          >
+            <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center' }}>
+              <h3 style={{ fontSize: '16px', fontWeight: 700, margin: 0, color: 'var(--text-primary)' }}>
+                📊 {datasetImportDraft.preview.file_name} 가져오기
+              </h3>
+              <button
+                onClick={() => setDatasetImportDraft(null)}
+                style={{ background: 'none', border: 'none', fontSize: '18px', cursor: 'pointer', color: 'var(--text-secondary)' }}
+              >
+                ✕
+              </button>
+            </div>
+
+            <input
+              value={datasetImportDraft.name}
+              onChange={(e) => setDatasetImportDraft({ ...datasetImportDraft, name: e.target.value })}
+              placeholder="데이터셋 이름 (비우면 파일 이름)"
+              style={{ padding: '8px 10px', fontSize: '13px', border: '1px solid var(--border)', borderRadius: '6px', background: 'var(--bg-secondary)', color: 'var(--text-primary)' }}
+            />
+
+            <div style={{ display: 'flex', gap: '16px', fontSize: '12px', color: 'var(--text-secondary)' }}>
+              <label style={{ display: 'flex', alignItems: 'center', gap: '6px', cursor: 'pointer' }}>
+                <input
+                  type="checkbox"
+                  checked={datasetImportDraft.fillMerged}
+                  onChange={(e) => setDatasetImportDraft({ ...datasetImportDraft, fillMerged: e.target.checked })}
+                />
+                병합된 헤더 칸 채우기
+              </label>
+              {datasetImportDraft.sheets.filter(sheet => sheet.selected).length > 1 && (
+                <label style={{ display: 'flex', alignItems: 'center', gap: '6px', cursor: 'pointer' }}>
+                  <input
+                    type="checkbox"
+                    checked={datasetImportDraft.union}
+                    onChange={(e) => setDatasetImportDraft({ ...datasetImportDraft, union: e.target.checked })}
+                  />
+                  선택한 시트를 하나의 데이터셋으로 합치기
+                </label>
+              )}
+            </div>
+
+            {datasetImportDraft.preview.sheets.map((sheet, index) => {
+              const draft = datasetImportDraft.sheets[index];
+              const headerEnd = draft.header_row + draft.header_rows;
+              const columns = datasetHeaderNames(sheet.sample_rows.slice(draft.header_row, headerEnd), datasetImportDraft.fillMerged);
+              const width = Math.max(sheet.column_count, ...sheet.sample_rows.map(row => row.length));
+              return (
+                <div key={sheet.name} className="card" style={{ padding: '12px', opacity: draft.selected ? 1 : 0.6 }}>
+                  <div style={{ display: 'flex', alignItems: 'center', gap: '12px', flexWrap: 'wrap', fontSize: '12px', color: 'var(--text-secondary)' }}>
+                    <label style={{ display: 'flex', alignItems: 'center', gap: '6px', cursor: 'pointer', fontWeight: 600, fontSize: '13px', color: 'var(--text-primary)' }}>
+                      <input
+                        type="checkbox"
+                        checked={draft.selected}
+                        onChange={(e) => updateDatasetImportSheet(index, { selected: e.target.checked })}
+                      />
+                      {sheet.name}
+                    </label>
+                    <span>약 {sheet.row_count.toLocaleString()}행 × {columns.length}열</span>
+                    <label style={{ display: 'flex', alignItems: 'center', gap: '4px' }}>
+                      헤더 시작 행
+                      <input
+                        type="number"
+                        min={1}
+                        max={Math.max(1, sheet.sample_rows.length)}
+                        value={draft.header_row + 1}
+                        onChange={(e) => updateDatasetImportSheet(index, { header_row: Math.max(0, (parseInt(e.target.value) || 1) - 1) })}
+                        style={{ width: '52px', padding: '2px 4px', border: '1px solid var(--border)', borderRadius: '4px', background: 'var(--bg-secondary)', color: 'var(--text-primary)' }}
+                      />
+                    </label>
+                    <label style={{ display: 'flex', alignItems: 'center', gap: '4px' }}>
+                      헤더 행 수
+                      <input
+                        type="number"
+                        min={1}
+                        max={5}
+                        value={draft.header_rows}
+                        onChange={(e) => updateDatasetImportSheet(index, { header_rows: Math.min(5, Math.max(1, parseInt(e.target.value) || 1)) })}
+                        style={{ width: '44px', padding: '2px 4px', border: '1px solid var(--border)', borderRadius: '4px', background: 'var(--bg-secondary)', color: 'var(--text-primary)' }}
+                      />
+                    </label>
+                  </div>
+
+                  {draft.selected && (
+                    <>
+                      <div style={{ fontSize: '11px', color: 'var(--text-secondary)', margin: '8px 0 4px' }}>
+                        컬럼: {columns.join(', ') || '-'}
+                      </div>
+                      {/* 파일 앞부분 - 행을 누르면 그 행을 헤더 시작 행으로 */}
+                      <div style={{ overflowX: 'auto', maxHeight: '200px', border: '1px solid var(--border)', borderRadius: '6px' }}>
+                        <table style={{ borderCollapse: 'collapse', fontSize: '11px', width: '100%' }}>
+                          <tbody>
+                            {sheet.sample_rows.slice(0, 12).map((row, rowIndex) => {
+                              const isHeader = rowIndex >= draft.header_row && rowIndex < headerEnd;
+                              return (
+                                <tr
+                                  key={rowIndex}
+                                  onClick={() => updateDatasetImportSheet(index, { header_row: rowIndex })}
+                                  style={{
+                                    cursor: 'pointer',
+                                    background: isHeader ? 'rgba(102, 126, 234, 0.15)' : 'transparent',
+                                    fontWeight: isHeader ? 600 : 400,
+                                    color: rowIndex < draft.header_row ? 'var(--text-muted)' : 'var(--text-primary)',
+                                  }}
+                                >
+                                  <td style={{ padding: '3px 6px', color: 'var(--text-muted)', borderBottom: '1px solid var(--border)' }}>{rowIndex + 1}</td>
+                                  {Array.from({ length: width }, (_, i) => (
+                                    <td key={i} style={{ padding: '3px 6px', whiteSpace: 'nowrap', borderBottom: '1px solid var(--border)' }}>
+                                      {row[i] ?? ''}
+                                    </td>
+                                  ))}
+                                </tr>
+                              );
+                            })}
+                          </tbody>
+                        </table>
+                      </div>
+                    </>
+                  )}
+                </div>
+              );
+            })}
+
+            <div style={{ display: 'flex', justifyContent: 'flex-end', gap: '8px' }}>
+              <button
+                onClick={() => setDatasetImportDraft(null)}
+                style={{ padding: '8px 16px', fontSize: '13px', background: 'var(--bg-secondary)', border: '1px solid var(--border)', borderRadius: '6px', cursor: 'pointer', color: 'var(--text-primary)' }}
+              >
+                취소
+              </button>
+              <button
+                onClick={confirmDatasetImport}
+                disabled={!datasetImportDraft.sheets.some(sheet => sheet.selected)}
+                style={{ padding: '8px 16px', fontSize: '13px', fontWeight: 600, background: '#667eea', color: 'white', border: 'none', borderRadius: '6px', cursor: 'pointer' }}
+              >
+                가져오기
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {/* 알람 오버레이 - 전체 화면 빨간색 */}
       {activeAlarm && (
         <div
@@ -3252,8 +3619,9 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
               const date = tx.tx_date || tx.created_at;
               return date?.startsWith(currentMonth);
             });
-            const monthIncome = monthTxs.filter(t => t.tx_type === 'income').reduce((s, t) => s + t.amount, 0);
-            const monthExpense = monthTxs.filter(t => t.tx_type === 'expense').reduce((s, t) => s + t.amount, 0);
+            const monthStats = ledgerStats.find(s => s.bucket === currentMonth);
+            const monthIncome = monthStats?.income ?? 0;
+            const monthExpense = monthStats?.expense ?? 0;
 
             return (
               <div className="flex flex-col gap-3 flex-1">
@@ -3324,16 +3692,16 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                     <div className="space-y-1">
                       <div className="flex justify-between" style={{ fontSize: '11px' }}>
                         <span style={{ color: 'var(--text-muted)' }}>수입</span>
-                        <span style={{ color: 'var(--success)', fontWeight: 500 }}>+{monthIncome.toLocaleString()}</span>
+                        <span style={{ color: 'var(--success)', fontWeight: 500 }}>+{formatAmount(monthIncome, baseCurrency)}</span>
                       </div>
                       <div className="flex justify-between" style={{ fontSize: '11px' }}>
                         <span style={{ color: 'var(--text-muted)' }}>지출</span>
-                        <span style={{ color: 'var(--error)', fontWeight: 500 }}>-{monthExpense.toLocaleString()}</span>
+                        <span style={{ color: 'var(--error)', fontWeight: 500 }}>-{formatAmount(monthExpense, baseCurrency)}</span>
                       </div>
                       <div className="flex justify-between" style={{ fontSize: '11px', borderTop: '1px solid var(--border-light)', paddingTop: '4px', marginTop: '4px' }}>
                         <span style={{ fontWeight: 500 }}>잔액</span>
                         <span style={{ fontWeight: 600, color: monthIncome - monthExpense >= 0 ? 'var(--success)' : 'var(--error)' }}>
-                          {(monthIncome - monthExpense).toLocaleString()}원
+                          {formatAmount(monthIncome - monthExpense, baseCurrency)}
                         </span>
                       </div>
                     </div>
@@ -3808,8 +4176,9 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
             const currentMonth = `${now.getFullYear()}-${String(now.getMonth() + 1).padStart(2, '0')}`;
 
             // 전체 요약
-            const totalIncome = transactions.filter(t => t.tx_type === 'income').reduce((s, t) => s + t.amount, 0);
-            const totalExpense = transactions.filter(t => t.tx_type === 'expense').reduce((s, t) => s + t.amount, 0);
+            const totalIncome = ledgerStats.reduce((s, m) => s + m.income, 0);
+            const totalExpense = ledgerStats.reduce((s, m) => s + m.expense, 0);
+            const unconverted = ledgerStats.reduce((s, m) => s + m.unconverted, 0);
 
             return (
               <div className="space-y-1">
@@ -3832,25 +4201,31 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                     }}>
                       <div style={{ flex: 1 }}>
                         <div style={{ fontSize: '11px', color: 'var(--text-muted)' }}>총 수입</div>
-                        <div style={{ fontSize: '16px', fontWeight: 600, color: 'var(--success)' }}>+{totalIncome.toLocaleString()}</div>
+                        <div style={{ fontSize: '16px', fontWeight: 600, color: 'var(--success)' }}>+{formatAmount(totalIncome, baseCurrency)}</div>
                       </div>
                       <div style={{ flex: 1 }}>
                         <div style={{ fontSize: '11px', color: 'var(--text-muted)' }}>총 지출</div>
-                        <div style={{ fontSize: '16px', fontWeight: 600, color: 'var(--error)' }}>-{totalExpense.toLocaleString()}</div>
+                        <div style={{ fontSize: '16px', fontWeight: 600, color: 'var(--error)' }}>-{formatAmount(totalExpense, baseCurrency)}</div>
                       </div>
                       <div style={{ flex: 1 }}>
                         <div style={{ fontSize: '11px', color: 'var(--text-muted)' }}>잔액</div>
                         <div style={{ fontSize: '16px', fontWeight: 600, color: totalIncome - totalExpense >= 0 ? 'var(--success)' : 'var(--error)' }}>
-                          {(totalIncome - totalExpense).toLocaleString()}원
+                          {formatAmount(totalIncome - totalExpense, baseCurrency)}
                         </div>
                       </div>
                     </div>
+                    {unconverted > 0 && (
+                      <div style={{ fontSize: '11px', color: 'var(--text-muted)', padding: '0 16px 8px' }}>
+                        환율이 없는 거래 {unconverted}건은 합계에서 빠졌습니다
+                      </div>
+                    )}
 
                     {/* 월별 섹션 */}
                     {sortedMonths.map(month => {
                       const monthTxs = monthlyGroups[month];
-                      const income = monthTxs.filter(t => t.tx_type === 'income').reduce((sum, t) => sum + t.amount, 0);
-                      const expense = monthTxs.filter(t => t.tx_type === 'expense').reduce((sum, t) => sum + t.amount, 0);
+                      const stats = ledgerStats.find(s => s.bucket === month);
+                      const income = stats?.income ?? 0;
+                      const expense = stats?.expense ?? 0;
 
                       const [, mon] = month.split('-');
                       const monthLabel = month === 'unknown' ? '날짜 미상' : `${parseInt(mon)}월`;
@@ -3865,9 +4240,9 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                               <span style={{ marginLeft: '8px', fontSize: '11px', color: 'var(--text-muted)' }}>{monthTxs.length}건</span>
                             </span>
                             <span style={{ fontSize: '11px' }}>
-                              <span style={{ color: 'var(--success)' }}>+{income.toLocaleString()}</span>
+                              <span style={{ color: 'var(--success)' }}>+{formatAmount(income, baseCurrency)}</span>
                               <span style={{ margin: '0 4px' }}>/</span>
-                              <span style={{ color: 'var(--error)' }}>-{expense.toLocaleString()}</span>
+                              <span style={{ color: 'var(--error)' }}>-{formatAmount(expense, baseCurrency)}</span>
                             </span>
                           </div>
 
@@ -3894,6 +4269,7 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                                       </select>
                                       <input
                                         type="number"
+                                        step="any"
                                         value={editTxAmount}
                                         onChange={(e) => setEditTxAmount(e.target.value)}
                                         className="input flex-1"
@@ -3962,7 +4338,7 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                                       color: tx.tx_type === 'income' ? 'var(--success)' : 'var(--error)',
                                       marginRight: '8px'
                                     }}>
-                                      {tx.tx_type === 'income' ? '+' : '-'}{tx.amount.toLocaleString()}
+                                      {tx.tx_type === 'income' ? '+' : '-'}{formatAmount(tx.amount, tx.currency)}
                                     </div>
 
                                     {/* 액션 버튼 */}
@@ -5436,7 +5812,9 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                 onDragOver={(e) => { e.preventDefault(); setIsDraggingExcel(true); }}
                 onDragLeave={() => setIsDraggingExcel(false)}
                 onDrop={handleExcelDrop}
+                onClick={() => { if (!datasetLoading) pickDatasetFile(); }}
                 style={{
+                  cursor: datasetLoading ? 'default' : 'pointer',
                   padding: '24px',
                   border: `2px dashed ${isDraggingExcel ? '#667eea' : 'var(--border)'}`,
                   borderRadius: '12px',
@@ -5454,10 +5832,10 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                   <>
                     <span style={{ fontSize: '32px', marginBottom: '8px', display: 'block' }}>📊</span>
                     <p style={{ fontSize: '14px', fontWeight: 600, color: 'var(--text-primary)', marginBottom: '4px' }}>
-                      엑셀 파일을 드래그 앤 드롭하세요
+                      엑셀 파일을 드래그 앤 드롭하거나 클릭해서 선택하세요
                     </p>
                     <p style={{ fontSize: '12px', color: 'var(--text-secondary)' }}>
-                      .xlsx, .xls, .csv 파일 지원
+                      .xlsx, .xls, .ods, .csv, .tsv 파일 지원
                     </p>
                   </>
                 )}
@@ -5711,21 +6089,27 @@ ${topMoves.slice(1).map(m => `(${m.row},${m.col}) 점수:${m.score}`).join(', ')
                           <p style={{ fontSize: '14px', lineHeight: 1.8, margin: 0, whiteSpace: 'pre-wrap' }}>{datasetQAResult.answer}</p>
                         </div>
 
-                        {/* 관련 데이터 */}
+                        {/* 실행한 SQL */}
+                        <div>
+                          <div style={{ fontSize: '11px', color: 'var(--text-secondary)', marginBottom: '8px' }}>🧮 실행한 쿼리 (전체 {selectedDataset.row_count.toLocaleString()}행 기준){datasetQAResult.sql_explanation && ` · ${datasetQAResult.sql_explanation}`}</div>
+                          <pre style={{ fontSize: '11px', margin: 0, padding: '10px', background: 'var(--bg-tertiary)', borderRadius: '6px', whiteSpace: 'pre-wrap', wordBreak: 'break-all' }}>{datasetQAResult.sql}</pre>
+                        </div>
+
+                        {/* 결과 표 */}
                         {datasetQAResult.relevant_rows.length > 0 && (
                           <div>
-                            <div style={{ fontSize: '11px', color: 'var(--text-secondary)', marginBottom: '8px' }}>📋 관련 데이터 ({datasetQAResult.relevant_rows.length}건)</div>
+                            <div style={{ fontSize: '11px', color: 'var(--text-secondary)', marginBottom: '8px' }}>📋 결과 ({datasetQAResult.relevant_rows.length}건{datasetQAResult.truncated && ', 최대 행 수에서 잘림'})</div>
                             <div style={{ overflowX: 'auto' }}>
                               <table style={{ width: '100%', borderCollapse: 'collapse', fontSize: '11px' }}>
                                 <thead>
                                   <tr style={{ background: 'var(--bg-tertiary)' }}>
-                                    {selectedDataset.columns.map((col, i) => (
+                                    {datasetQAResult.columns.map((col, i) => (
                                       <th key={i} style={{ padding: '6px 10px', textAlign: 'left', fontWeight: 600, borderBottom: '1px solid var(--border)' }}>{col}</th>
                                     ))}
                                   </tr>
                                 </thead>
                                 <tbody>
-                                  {datasetQAResult.relevant_rows.slice(0, 10).map((row, rowIdx) => (
+                                  {datasetQAResult.relevant_rows.slice(0, 50).map((row, rowIdx) => (
                                     <tr key={rowIdx} style={{ background: rowIdx % 2 === 0 ? 'var(--bg-secondary)' : 'var(--bg-tertiary)' }}>
                                       {row.map((cell, cellIdx) => (
                                         <td key={cellIdx} style={{ padding: '6px 10px', borderBottom: '1px solid var(--border-light)' }}>{cell}</td>